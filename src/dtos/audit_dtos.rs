@@ -0,0 +1,23 @@
+// src/dtos/audit_dtos.rs - DTO untuk GET /api/admin/audit
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Query GET /api/admin/audit
+#[derive(Deserialize)]
+pub struct AuditLogQuery {
+    pub actor_id: Option<Uuid>,
+    pub action: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// Satu baris tabel `audit_log`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuditLogOut {
+    pub actor_id: Option<Uuid>,
+    pub action: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub timestamp: String,
+    pub detail: Option<serde_json::Value>,
+}
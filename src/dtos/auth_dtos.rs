@@ -8,6 +8,7 @@ pub struct SignupIn {
     pub email: String,
     pub password: String,
     pub username: Option<String>,
+    pub full_name: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -16,12 +17,49 @@ pub struct LoginIn {
     pub password: String,
 }
 
+#[derive(Deserialize)]
+pub struct ForgotPasswordIn {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResendConfirmationIn {
+    pub email: String,
+}
+
+/// Body of DELETE /api/account: the current password, as confirmation
+#[derive(Deserialize)]
+pub struct DeleteAccountIn {
+    pub password: String,
+}
+
+/// `token` adalah access token (type=recovery) dari link email reset password
+#[derive(Deserialize)]
+pub struct ResetPasswordIn {
+    pub token: String,
+    pub new_password: String,
+}
+
+/// Result of POST /auth/reset-password
+#[derive(Serialize)]
+pub struct ResetPasswordOut {
+    /// true when every other session was signed out; they must log in again
+    pub other_sessions_revoked: bool,
+}
+
+#[derive(Deserialize)]
+pub struct RefreshIn {
+    pub refresh_token: String,
+}
+
 #[derive(Serialize)]
 pub struct SessionOut {
     pub access_token: String,
     pub refresh_token: Option<String>,
     pub expires_in: Option<i64>,
     pub token_type: Option<String>,
+    /// false when Supabase issued no refresh_token: the client must log in again once the session expires
+    pub refreshable: bool,
 }
 
 // NEW DTOs for complete flow
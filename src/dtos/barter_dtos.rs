@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::dtos::personal_dtos::PublicProfileOut;
+
+/// DTO untuk POST /api/barter/request (requester_id diambil dari token)
+#[derive(Debug, Deserialize)]
+pub struct CreateBarterRequestDTO {
+    pub recipient_id: Uuid,
+    pub offered_skill: String,
+    pub requested_skill: String,
+}
+
+/// DTO untuk PUT /api/barter/request/{id}: action "accept" | "reject"
+#[derive(Debug, Deserialize)]
+pub struct BarterActionDTO {
+    pub action: String,
+}
+
+/// Satu baris tabel `barter_requests`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BarterRequestOut {
+    pub id: Uuid,
+    pub requester_id: Uuid,
+    pub recipient_id: Uuid,
+    pub offered_skill: String,
+    pub requested_skill: String,
+    pub status: String, // "pending" | "accepted" | "rejected"
+    pub created_at: Option<String>,
+}
+
+/// Request plus the profile of the other party (requester for incoming, recipient for outgoing)
+#[derive(Debug, Serialize)]
+pub struct BarterRequestWithProfileOut {
+    #[serde(flatten)]
+    pub request: BarterRequestOut,
+    pub counterpart: PublicProfileOut,
+}
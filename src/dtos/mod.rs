@@ -2,6 +2,11 @@ pub mod auth_dtos;
 pub mod personal_dtos;
 pub mod profile_picture_dtos;
 pub mod post_dtos;
+pub mod barter_dtos;
+pub mod skill_dtos;
+pub mod report_dtos;
+pub mod audit_dtos;
+pub mod response;
 // alias supaya dapat dipanggil sebagai `crate::dtos::auth` dan `crate::dtos::personal`
 pub use auth_dtos as auth;
 pub use personal_dtos as personal;
\ No newline at end of file
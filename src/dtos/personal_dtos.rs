@@ -9,17 +9,104 @@ pub struct CreatePersonalDTO {
     pub primary_skill: String,
     pub skill_to_learn: String,
     pub bio: String,
+    /// opsional: false = sembunyikan profile dari pencarian/matching
+    #[serde(default)]
+    pub is_discoverable: Option<bool>,
+    /// opsional: nama tampilan (1-80 karakter); tidak dikirim = tidak diubah
+    #[serde(default)]
+    pub full_name: Option<String>,
+}
+
+/// DTO untuk PATCH /api/profile: hanya field yang dikirim yang diubah.
+/// date_of_birth yang tidak dikirim tidak diubah; "" mengosongkannya.
+#[derive(Deserialize, Debug)]
+pub struct UpdateProfilePatchDTO {
+    #[serde(default)]
+    pub date_of_birth: Option<String>,
+    #[serde(default)]
+    pub primary_skill: Option<String>,
+    #[serde(default)]
+    pub skill_to_learn: Option<String>,
+    #[serde(default)]
+    pub bio: Option<String>,
+    #[serde(default)]
+    pub is_discoverable: Option<bool>,
+    #[serde(default)]
+    pub full_name: Option<String>,
+}
+
+/// Satu baris profile untuk GET /api/admin/users (termasuk role dan status akun)
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AdminProfileOut {
+    pub user_id: Uuid,
+    pub username: Option<String>,
+    pub full_name: Option<String>,
+    pub role: Option<String>,
+    pub primary_skill: Option<String>,
+    pub skill_to_learn: Option<String>,
+    pub is_discoverable: Option<bool>,
+    pub is_active: Option<bool>,
+    /// Di-suspend oleh admin (lihat PUT /api/admin/users/{id}/suspend)
+    pub is_suspended: Option<bool>,
+    pub created_at: Option<String>,
 }
 
 /// DTO yang dikembalikan ke client setelah tersimpan
 #[derive(Serialize, Debug)]
 pub struct PersonalDataOut {
-    pub id: Uuid,
-    pub user_id: Uuid,
-    pub date_of_birth: String, // ISO "YYYY-MM-DD"
+    pub id: Uuid,      // id baris profile (di table profiles sama dengan user_id)
+    pub user_id: Uuid, // id user di Supabase Auth
+    pub full_name: Option<String>, // nama tampilan, sama dengan yang tampil di post
+    pub date_of_birth: Option<String>, // ISO "YYYY-MM-DD", null = belum diisi
+    pub age: Option<u32>, // dihitung dari date_of_birth, null kalau kosong/tidak valid
     pub primary_skill: String,
     pub skill_to_learn: String,
     pub bio: String,
     pub profile_picture_url: Option<String>, // ADDED: Profile picture URL    
-    // tambahan field seperti created_at bisa ditambahkan
+    pub is_discoverable: bool, // false = tidak muncul di pencarian/matching
+    pub is_active: bool, // false = akun dinonaktifkan sementara oleh user
+    pub created_at: Option<String>, // ISO timestamp, untuk "member since"
+    pub updated_at: Option<String>, // ISO timestamp, untuk "last updated"
+}
+
+/// Catatan non-blocking tentang profile yang sudah tersimpan (FE bisa menampilkan nudge)
+#[derive(Serialize, Debug, Clone)]
+pub struct ProfileWarning {
+    pub field: String,
+    pub code: String,
+    pub message: String,
+}
+
+/// Profile yang tersimpan beserta warning non-blocking
+#[derive(Serialize, Debug)]
+pub struct ProfileWriteOut {
+    #[serde(flatten)]
+    pub profile: PersonalDataOut,
+    pub warnings: Vec<ProfileWarning>,
+}
+
+/// Subset profile yang aman ditampilkan ke user lain (tanpa date_of_birth)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublicProfileOut {
+    pub user_id: Uuid,
+    pub full_name: Option<String>,
+    pub primary_skill: Option<String>,
+    pub skill_to_learn: Option<String>,
+    pub bio: Option<String>,
+    pub profile_picture_url: Option<String>,
+}
+
+impl From<PersonalDataOut> for PublicProfileOut {
+    /// Drop the private fields (date_of_birth, age, status flags); empty text becomes null
+    fn from(profile: PersonalDataOut) -> Self {
+        let non_empty = |s: String| Some(s).filter(|s| !s.trim().is_empty());
+        PublicProfileOut {
+            user_id: profile.user_id,
+            full_name: profile.full_name,
+            primary_skill: non_empty(profile.primary_skill),
+            skill_to_learn: non_empty(profile.skill_to_learn),
+            bio: non_empty(profile.bio),
+            profile_picture_url: profile.profile_picture_url,
+        }
+    }
 }
@@ -1,18 +1,106 @@
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use uuid::Uuid;
 
 #[derive(Debug, Deserialize)]
 pub struct CreatePostDTO {
     pub content: String,
     pub image_url: Option<String>, // optional, cocok dengan schema
+    /// opsional (RFC3339): jadwalkan post, baru tampil di feed setelah waktu ini
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
+    /// opsional: true = simpan sebagai draft, hanya terlihat oleh penulis sampai di-publish
+    #[serde(default)]
+    pub draft: bool,
+}
+
+/// DTO untuk PUT /api/posts/{id}
+/// `image_url`: field tidak dikirim = gambar tidak berubah,
+/// `null` = hapus gambar, string = ganti gambar
+#[derive(Debug, Deserialize)]
+pub struct UpdatePostDTO {
+    pub content: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub image_url: Option<Option<String>>,
+}
+
+/// Membedakan field yang absen (None) dari field yang dikirim `null` (Some(None))
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 // Add the missing PostOut struct
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PostOut {
-    pub id: String,
-    pub user_id: Option<String>,
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
     pub content: Option<String>,
+    pub content_html: Option<String>, // markdown yang sudah di-render & disanitasi
     pub image_url: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
-}
\ No newline at end of file
+    #[serde(default)]
+    pub publish_at: Option<String>, // terisi selama post masih terjadwal
+    #[serde(default)]
+    pub deleted_at: Option<String>, // terisi setelah soft delete
+    #[serde(default)]
+    pub is_draft: bool, // true sampai di-publish lewat POST /api/posts/{id}/publish
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &str) -> UpdatePostDTO {
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[test]
+    fn absent_image_url_leaves_the_image_unchanged() {
+        let dto = parse(r#"{"content":"hi"}"#);
+        assert_eq!(dto.image_url, None);
+    }
+
+    #[test]
+    fn null_image_url_removes_the_image() {
+        assert_eq!(parse(r#"{"image_url":null}"#).image_url, Some(None));
+    }
+
+    #[test]
+    fn string_image_url_replaces_the_image() {
+        assert_eq!(
+            parse(r#"{"image_url":"https://img/x.png"}"#).image_url,
+            Some(Some("https://img/x.png".to_string()))
+        );
+    }
+
+    #[test]
+    fn post_ids_round_trip_as_uuid_strings() {
+        let (id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let post: PostOut = serde_json::from_value(serde_json::json!({
+            "id": id.to_string(),
+            "user_id": user_id.to_string(),
+            "content": "hi",
+            "content_html": null,
+            "image_url": null,
+            "created_at": null,
+            "updated_at": null,
+        }))
+        .unwrap();
+
+        assert_eq!(post.id, id);
+        let out = serde_json::to_value(&post).unwrap();
+        assert_eq!(out["id"], id.to_string());
+        assert_eq!(out["user_id"], user_id.to_string());
+    }
+
+    #[test]
+    fn non_uuid_post_id_is_rejected() {
+        let parsed = serde_json::from_value::<PostOut>(serde_json::json!({ "id": "42" }));
+        assert!(parsed.is_err());
+    }
+}
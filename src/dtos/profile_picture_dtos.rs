@@ -7,14 +7,33 @@ pub struct UploadProfilePictureRequest {
     pub image_data: String, // base64 encoded image
     pub file_name: String,
     pub content_type: String, // "image/jpeg", "image/png", etc.
+    /// opsional: area yang dipilih user di FE, dalam pixel gambar asli
+    #[serde(default)]
+    pub crop: Option<CropBox>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct CropBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
 }
 
 #[derive(Serialize)]
 pub struct ProfilePictureResponse {
     pub profile_picture_url: String,
+    pub thumbnail_url: String, // 128x128 JPEG untuk feed / kartu match
     pub message: String,
 }
 
+/// Response DELETE /api/profile-picture (kedua URL selalu null)
+#[derive(Serialize)]
+pub struct ClearedProfilePictureResponse {
+    pub profile_picture_url: Option<String>,
+    pub thumbnail_url: Option<String>,
+}
+
 #[derive(Serialize)]
 pub struct SkipProfilePictureResponse {
     pub message: String,
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// DTO untuk POST /api/posts/{id}/report (reporter_id diambil dari token)
+#[derive(Debug, Deserialize)]
+pub struct CreateReportDTO {
+    pub reason: String, // "spam" | "harassment" | "other"
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+/// Satu baris tabel `post_reports`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostReportOut {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub reporter_id: Uuid,
+    pub reason: String,
+    pub detail: Option<String>,
+    pub created_at: Option<String>,
+}
+
+/// Reports for one post, for the admin moderation queue
+#[derive(Debug, Serialize)]
+pub struct ReportedPostOut {
+    pub post_id: Uuid,
+    pub report_count: usize,
+    /// jumlah report per reason, mis. {"spam": 3, "other": 1}
+    pub reasons: std::collections::BTreeMap<String, usize>,
+    pub latest_report_at: Option<String>,
+    pub reports: Vec<PostReportOut>,
+}
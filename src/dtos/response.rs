@@ -0,0 +1,114 @@
+// src/dtos/response.rs - envelope JSON yang dipakai semua handler
+use serde::Serialize;
+
+/// `{ "status": "success" | "error", "message": ..., "data": ... }`, plus `error_code`
+/// on errors the client branches on (RATE_LIMITED, FORBIDDEN, ...)
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T: Serialize> {
+    pub status: String,
+    pub message: String,
+    pub data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+}
+
+impl<T: Serialize> ApiResponse<T> {
+    /// status "success" with `data`
+    pub fn success(message: impl Into<String>, data: T) -> Self {
+        Self {
+            status: "success".to_string(),
+            message: message.into(),
+            data: Some(data),
+            error_code: None,
+        }
+    }
+
+    /// status "error" with `error_code` and details for the client in `data`
+    /// (e.g. `next_step`, or the steps of an incomplete account deletion)
+    pub fn error_with_details(code: &str, message: impl Into<String>, data: T) -> Self {
+        Self {
+            status: "error".to_string(),
+            message: message.into(),
+            data: Some(data),
+            error_code: Some(code.to_string()),
+        }
+    }
+}
+
+impl ApiResponse<()> {
+    /// status "success" without a payload (`data: null`)
+    pub fn success_message(message: impl Into<String>) -> Self {
+        Self {
+            status: "success".to_string(),
+            message: message.into(),
+            data: None,
+            error_code: None,
+        }
+    }
+
+    /// status "error" with `data: null`
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            status: "error".to_string(),
+            message: message.into(),
+            data: None,
+            error_code: None,
+        }
+    }
+
+    /// status "error" with `data: null` and a machine-readable `error_code`
+    pub fn error_with_code(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            error_code: Some(code.to_string()),
+            ..Self::error(message)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn plain_error_has_no_error_code_key() {
+        let value = serde_json::to_value(ApiResponse::error("Profile not found")).unwrap();
+        assert_eq!(
+            value,
+            json!({ "status": "error", "message": "Profile not found", "data": null })
+        );
+    }
+
+    #[test]
+    fn error_with_code_serializes_the_code() {
+        let value =
+            serde_json::to_value(ApiResponse::error_with_code("RATE_LIMITED", "Too many attempts")).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "status": "error",
+                "message": "Too many attempts",
+                "data": null,
+                "error_code": "RATE_LIMITED",
+            })
+        );
+    }
+
+    #[test]
+    fn error_with_details_puts_them_in_data() {
+        let response = ApiResponse::error_with_details(
+            "PROFILE_NOT_FOUND",
+            "Profile not found",
+            json!({ "next_step": "complete_profile" }),
+        );
+        let value = serde_json::to_value(response).unwrap();
+        assert_eq!(value["error_code"], "PROFILE_NOT_FOUND");
+        assert_eq!(value["data"]["next_step"], "complete_profile");
+    }
+
+    #[test]
+    fn success_has_no_error_code_key() {
+        let value = serde_json::to_value(ApiResponse::success("ok", 1)).unwrap();
+        assert!(value.get("error_code").is_none());
+    }
+}
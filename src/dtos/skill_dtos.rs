@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+fn default_active() -> bool {
+    true
+}
+
+/// Satu skill di POST /api/admin/skills (`active` default true)
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SkillIn {
+    pub name: String,
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+/// DTO untuk POST /api/admin/skills
+#[derive(Debug, Deserialize)]
+pub struct BulkSkillsIn {
+    pub skills: Vec<SkillIn>,
+}
+
+/// Satu baris tabel `skills`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillOut {
+    pub name: String,
+    pub active: bool,
+    pub updated_at: Option<String>,
+}
@@ -0,0 +1,197 @@
+// src/error.rs - error type untuk handler, dirender sebagai envelope ApiResponse
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use thiserror::Error;
+use crate::dtos::response::ApiResponse;
+use crate::middleware::upstream_guard::upstream_unavailable_response;
+use crate::repositories::profile_supabase_repo::RepoError;
+use crate::services::http_util::{is_unreachable, SendError};
+use crate::services::auth_services::AuthError;
+
+/// Error a handler can return with `?` (`Result<HttpResponse, AppError>`).
+/// 4xx variants show their message to the client; 5xx ones are logged and the
+/// client only gets a generic message, so upstream details don't leak.
+#[derive(Debug, Error)]
+pub enum AppError {
+    /// 400, input yang tidak valid
+    #[error("{0}")]
+    Validation(String),
+    /// 404
+    #[error("{0}")]
+    NotFound(String),
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+    #[error(transparent)]
+    Repo(#[from] RepoError),
+    /// 503, Supabase tidak bisa dihubungi (dari `Box<dyn Error>` repository)
+    #[error("{0}")]
+    Unavailable(String),
+    /// 500 dari error lain (mis. `Box<dyn Error>` dari repository)
+    #[error("{0}")]
+    Internal(String),
+    /// Another AppError with the message the client should see instead of the
+    /// generic one; the status still comes from `source` (see `ResultExt::context`)
+    #[error("{message}: {source}")]
+    Context { message: String, source: Box<AppError> },
+}
+
+/// `.context("Failed to ...")` on any result whose error converts into AppError,
+/// so a handler can use `?` and keep its own error message
+pub trait ResultExt<T> {
+    fn context(self, message: impl Into<String>) -> Result<T, AppError>;
+}
+
+impl<T, E: Into<AppError>> ResultExt<T> for Result<T, E> {
+    fn context(self, message: impl Into<String>) -> Result<T, AppError> {
+        self.map_err(|e| AppError::Context {
+            message: message.into(),
+            source: Box::new(e.into()),
+        })
+    }
+}
+
+impl From<Box<dyn std::error::Error>> for AppError {
+    fn from(e: Box<dyn std::error::Error>) -> Self {
+        let unreachable = match e.downcast_ref::<SendError>() {
+            Some(se) => se.is_unreachable(),
+            None => e.downcast_ref::<reqwest::Error>().is_some_and(is_unreachable),
+        };
+        if unreachable {
+            AppError::Unavailable(e.to_string())
+        } else {
+            AppError::Internal(e.to_string())
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
+impl From<SendError> for AppError {
+    fn from(e: SendError) -> Self {
+        if e.is_unreachable() {
+            AppError::Unavailable(e.to_string())
+        } else {
+            AppError::Internal(e.to_string())
+        }
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        if is_unreachable(&e) {
+            AppError::Unavailable(e.to_string())
+        } else {
+            AppError::Internal(e.to_string())
+        }
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for AppError {
+    fn from(e: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        AppError::from(e as Box<dyn std::error::Error>)
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::Context { source, .. } => source.status_code(),
+            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Auth(AuthError::InvalidToken) => StatusCode::UNAUTHORIZED,
+            AppError::Auth(AuthError::EmailNotConfirmed) => StatusCode::FORBIDDEN,
+            AppError::Auth(AuthError::UserNotFound | AuthError::ProfileNotFound) => StatusCode::NOT_FOUND,
+            AppError::Repo(RepoError::NotFound) => StatusCode::NOT_FOUND,
+            AppError::Auth(AuthError::Unavailable(_))
+            | AppError::Repo(RepoError::Unavailable(_))
+            | AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Auth(_) | AppError::Repo(_) | AppError::Internal(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status == StatusCode::SERVICE_UNAVAILABLE {
+            log::warn!("{}", self);
+            return upstream_unavailable_response();
+        }
+
+        if status.is_server_error() {
+            log::error!("{}", self);
+        }
+
+        let message = match self {
+            AppError::Context { message, .. } => message.clone(),
+            _ if status.is_server_error() => "Internal server error".to_string(),
+            AppError::Auth(AuthError::InvalidToken) => "Invalid token".to_string(),
+            AppError::Auth(AuthError::UserNotFound) => "User not found".to_string(),
+            AppError::Auth(AuthError::EmailNotConfirmed) => {
+                "Please confirm your email before continuing".to_string()
+            }
+            AppError::Auth(AuthError::ProfileNotFound) | AppError::Repo(RepoError::NotFound) => {
+                "Not found".to_string()
+            }
+            other => other.to_string(),
+        };
+
+        HttpResponse::build(status).json(ApiResponse::error(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+
+    async fn body_json(error: &AppError) -> serde_json::Value {
+        let body = to_bytes(error.error_response().into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn unavailable_behind_context_is_503() {
+        let result: Result<(), RepoError> = Err(RepoError::Unavailable("connect refused".into()));
+        let error = result.context("Failed to retrieve profile").unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body_json(&error).await["error_code"], "UPSTREAM_UNAVAILABLE");
+    }
+
+    #[actix_web::test]
+    async fn context_message_replaces_the_internal_error() {
+        let result: Result<(), AuthError> = Err(AuthError::Supabase("500 -> secret detail".into()));
+        let error = result.context("Failed to swap skills").unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = body_json(&error).await;
+        assert_eq!(body["message"], "Failed to swap skills");
+        assert!(!body.to_string().contains("secret detail"));
+    }
+
+    #[actix_web::test]
+    async fn unreachable_boxed_reqwest_error_is_unavailable() {
+        // Port 1 is never listening, so this is a connect error
+        let e = reqwest::Client::new().get("http://127.0.0.1:1/").send().await.unwrap_err();
+        let boxed: Box<dyn std::error::Error> = Box::new(e);
+
+        assert!(matches!(AppError::from(boxed), AppError::Unavailable(_)));
+    }
+
+    #[test]
+    fn open_circuit_is_unavailable_directly_and_boxed() {
+        assert_eq!(AppError::from(SendError::CircuitOpen).status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let boxed: Box<dyn std::error::Error> = Box::new(SendError::CircuitOpen);
+        assert!(matches!(AppError::from(boxed), AppError::Unavailable(_)));
+        assert!(matches!(
+            AuthError::from(SendError::CircuitOpen),
+            AuthError::Unavailable(_)
+        ));
+    }
+}
@@ -0,0 +1,374 @@
+// src/handlers/admin_handlers.rs - endpoint khusus admin (AdminUser)
+use std::collections::HashSet;
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use uuid::Uuid;
+use crate::dtos::audit_dtos::{AuditLogOut, AuditLogQuery};
+use crate::dtos::personal::AdminProfileOut;
+use crate::dtos::skill_dtos::BulkSkillsIn;
+use crate::handlers::post_handlers::{transform_post_with_profile, EnhancedPostOut, PostListQuery};
+use crate::handlers::skill_handlers::PageQuery;
+use crate::middleware::auth_extractor::{forget_suspension_status, AdminUser};
+use crate::repositories::audit_log_repository::{AuditEntry, AuditLogRepository};
+use crate::repositories::post_repository::PostRepository;
+use crate::repositories::profile_supabase_repo::ProfileSupabaseRepo;
+use crate::repositories::report_repository::ReportRepository;
+use crate::repositories::skill_repository::SkillRepository;
+use crate::services::auth_services::AuthService;
+use crate::AppState;
+use crate::dtos::response::ApiResponse;
+use crate::error::{AppError, ResultExt};
+
+/// Batas nama skill (sama dengan batas skill di profile)
+const MAX_SKILL_NAME_CHARS: usize = 100;
+/// Batas jumlah skill per request bulk import
+const MAX_SKILLS_PER_IMPORT: usize = 200;
+
+#[derive(serde::Serialize)]
+struct AdminUsersResponse {
+    users: Vec<AdminProfileOut>,
+    total: Option<i64>,
+    limit: u32,
+    offset: u32,
+}
+
+#[derive(serde::Serialize)]
+struct DeletedPostOut {
+    #[serde(flatten)]
+    post: EnhancedPostOut,
+    deleted_at: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct AuditLogResponse {
+    entries: Vec<AuditLogOut>,
+    total: Option<i64>,
+    limit: u32,
+    offset: u32,
+}
+
+/// Best-effort audit row for an admin action (actor = the admin)
+fn audit(
+    app_state: &AppState,
+    req: &HttpRequest,
+    admin: &AdminUser,
+    action: &str,
+    detail: serde_json::Value,
+) {
+    AuditLogRepository::record(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        AuditEntry::from_request(req, Some(admin.user_id), action, Some(detail)),
+    );
+}
+
+/// Apply a skills table change to validation right away instead of at the next refresh
+async fn refresh_active_skills(app_state: &AppState) {
+    if let Err(e) = SkillRepository::refresh_active_skills(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+    ).await {
+        log::warn!("Failed to refresh the active skill list: {}", e);
+    }
+}
+
+/// POST /api/admin/skills
+/// Bulk insert/update skills: `{ "skills": [{ "name": "Music", "active": true }] }`
+#[post("/api/admin/skills")]
+pub async fn import_skills(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    admin: AdminUser,
+    body: web::Json<BulkSkillsIn>,
+) -> Result<HttpResponse, AppError> {
+    let mut skills = body.into_inner().skills;
+
+    if skills.is_empty() {
+        return Err(AppError::Validation("At least one skill is required".to_string()));
+    }
+    if skills.len() > MAX_SKILLS_PER_IMPORT {
+        return Err(AppError::Validation(format!("At most {} skills per import", MAX_SKILLS_PER_IMPORT)));
+    }
+
+    // Nama dibandingkan tanpa beda huruf besar/kecil supaya "music" dan "Music" tidak jadi dua skill
+    let mut seen = HashSet::new();
+    for skill in skills.iter_mut() {
+        skill.name = skill.name.trim().to_string();
+
+        if skill.name.is_empty() {
+            return Err(AppError::Validation("Skill name cannot be empty".to_string()));
+        }
+        if skill.name.chars().count() > MAX_SKILL_NAME_CHARS {
+            return Err(AppError::Validation(format!(
+                "Skill name must be at most {} characters: {}",
+                MAX_SKILL_NAME_CHARS, skill.name
+            )));
+        }
+        if skill.name.chars().any(char::is_control) {
+            return Err(AppError::Validation(format!("Skill name contains invalid characters: {}", skill.name)));
+        }
+        if !seen.insert(skill.name.to_lowercase()) {
+            return Err(AppError::Validation(format!("Duplicate skill in request: {}", skill.name)));
+        }
+    }
+
+    // Nama yang sudah ada di tabel (beda huruf besar/kecil saja) memakai ejaan yang tersimpan,
+    // supaya upsert meng-update baris itu dan tidak membuat duplikat
+    let existing = SkillRepository::list_skills(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        false,
+    ).await.context("Failed to save skills")?;
+    for skill in skills.iter_mut() {
+        if let Some(row) = existing.iter().find(|row| row.name.to_lowercase() == skill.name.to_lowercase()) {
+            skill.name = row.name.clone();
+        }
+    }
+
+    log::info!("Admin {} importing {} skills", admin.user_id, skills.len());
+
+    let saved = SkillRepository::upsert_skills(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        &skills,
+    ).await.context("Failed to save skills")?;
+
+    refresh_active_skills(&app_state).await;
+    audit(
+        &app_state,
+        &req,
+        &admin,
+        "skills_imported",
+        serde_json::json!({ "skills": &skills }),
+    );
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        format!("{} skills saved", saved.len()),
+        saved,
+    )))
+}
+
+/// DELETE /api/admin/skills/{name}
+/// Soft-disable a skill: it stays in the table so existing profiles keep a valid value
+#[delete("/api/admin/skills/{name}")]
+pub async fn disable_skill(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    admin: AdminUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let name = path.into_inner().trim().to_string();
+
+    if name.is_empty() {
+        return Err(AppError::Validation("Skill name cannot be empty".to_string()));
+    }
+
+    log::info!("Admin {} disabling skill {}", admin.user_id, name);
+
+    let skill = SkillRepository::disable_skill(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        &name,
+    ).await
+        .context("Failed to disable skill")?
+        .ok_or_else(|| AppError::NotFound("Skill not found".to_string()))?;
+
+    refresh_active_skills(&app_state).await;
+    audit(&app_state, &req, &admin, "skill_disabled", serde_json::json!({ "skill": name }));
+    Ok(HttpResponse::Ok().json(ApiResponse::success("Skill disabled", skill)))
+}
+
+/// GET /api/admin/users
+/// All profiles including hidden and deactivated ones. `?limit=` (default 50, max 200) and `?offset=`
+#[get("/api/admin/users")]
+pub async fn list_users(
+    repo: web::Data<ProfileSupabaseRepo>,
+    admin: AdminUser,
+    query: web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let offset = query.offset.unwrap_or(0);
+
+    log::debug!("Admin {} listing users", admin.user_id);
+    let (users, total) = repo.list_all(limit, offset).await.context("Failed to retrieve users")?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Users retrieved successfully",
+        AdminUsersResponse {
+            users,
+            total,
+            limit,
+            offset,
+        },
+    )))
+}
+
+/// PUT /api/admin/users/{id}/suspend
+/// Suspended users get 403 on every authenticated route and can't log in
+#[put("/api/admin/users/{id}/suspend")]
+pub async fn suspend_user(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    svc: web::Data<AuthService>,
+    admin: AdminUser,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    set_user_suspended(&req, &app_state, &svc, &admin, path.into_inner(), true).await
+}
+
+/// PUT /api/admin/users/{id}/reactivate
+/// Lift a suspension
+#[put("/api/admin/users/{id}/reactivate")]
+pub async fn unsuspend_user(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    svc: web::Data<AuthService>,
+    admin: AdminUser,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    set_user_suspended(&req, &app_state, &svc, &admin, path.into_inner(), false).await
+}
+
+async fn set_user_suspended(
+    req: &HttpRequest,
+    app_state: &AppState,
+    svc: &AuthService,
+    admin: &AdminUser,
+    user_id: Uuid,
+    suspended: bool,
+) -> Result<HttpResponse, AppError> {
+    // Admin tidak bisa mengunci dirinya sendiri
+    if suspended && user_id == admin.user_id {
+        return Err(AppError::Validation("You cannot suspend your own account".to_string()));
+    }
+
+    log::info!(
+        "Admin {} {} user {}",
+        admin.user_id,
+        if suspended { "suspending" } else { "reactivating" },
+        user_id
+    );
+
+    let found = svc
+        .set_suspended(user_id, suspended)
+        .await
+        .context("Failed to update user status")?;
+    if !found {
+        return Err(AppError::NotFound("User not found".to_string()));
+    }
+
+    forget_suspension_status(user_id);
+    audit(
+        app_state,
+        req,
+        admin,
+        if suspended { "user_suspended" } else { "user_reactivated" },
+        serde_json::json!({ "user_id": user_id }),
+    );
+    Ok(HttpResponse::Ok().json(ApiResponse::success_message(if suspended {
+        "User suspended"
+    } else {
+        "User reactivated"
+    })))
+}
+
+/// GET /api/admin/audit
+/// Audit trail, newest first. Filter with `?actor_id=` and/or `?action=` (e.g. "login_failed");
+/// `?limit=` (default 50, max 200) and `?offset=`
+#[get("/api/admin/audit")]
+pub async fn list_audit_log(
+    app_state: web::Data<AppState>,
+    admin: AdminUser,
+    query: web::Query<AuditLogQuery>,
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let offset = query.offset.unwrap_or(0);
+    let action = query.action.as_deref().map(str::trim).filter(|a| !a.is_empty());
+
+    // Nama action selalu snake_case; tolak yang lain daripada meneruskannya ke PostgREST
+    if let Some(action) = action
+        && !action.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(AppError::Validation(format!("Invalid action: {}", action)));
+    }
+
+    log::debug!("Admin {} reading the audit log", admin.user_id);
+    let (entries, total) = AuditLogRepository::list_entries(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        query.actor_id,
+        action,
+        limit,
+        offset,
+    ).await.context("Failed to retrieve audit log")?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Audit log retrieved successfully",
+        AuditLogResponse {
+            entries,
+            total,
+            limit,
+            offset,
+        },
+    )))
+}
+
+/// GET /api/admin/posts/deleted
+/// Soft-deleted posts, most recently deleted first. `?limit=` (default 50, max 200)
+#[get("/api/admin/posts/deleted")]
+pub async fn list_deleted_posts(
+    app_state: web::Data<AppState>,
+    admin: AdminUser,
+    query: web::Query<PostListQuery>,
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+
+    let posts = PostRepository::list_deleted_posts(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        limit,
+    ).await.context("Failed to retrieve deleted posts")?;
+
+    let posts: Vec<DeletedPostOut> = posts
+        .into_iter()
+        .map(|mut post| DeletedPostOut {
+            deleted_at: post.deleted_at.take(),
+            post: transform_post_with_profile(post, Some(admin.user_id)),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Deleted posts retrieved successfully",
+        posts,
+    )))
+}
+
+/// GET /api/admin/reports
+/// Post reports grouped by post with counts per reason, most reported first.
+/// `?limit=` is the number of most recent reports to group (default 200, max 500).
+#[get("/api/admin/reports")]
+pub async fn list_reports(
+    app_state: web::Data<AppState>,
+    admin: AdminUser,
+    query: web::Query<PostListQuery>,
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.unwrap_or(200).clamp(1, 500);
+
+    log::debug!("Admin {} listing reports", admin.user_id);
+    let posts = ReportRepository::list_reports_by_post(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        limit,
+    ).await.context("Failed to retrieve reports")?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Reports retrieved successfully",
+        posts,
+    )))
+}
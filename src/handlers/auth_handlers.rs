@@ -1,37 +1,75 @@
-use actix_web::{get, post, web, HttpResponse, Responder};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
 use uuid::Uuid;
 use regex::Regex;
-use chrono::NaiveDate;
 use serde::Serialize;
-use crate::models::personal::get_valid_skills;
-
-use crate::dtos::auth::{SignupIn, LoginIn, SessionOut};
-use crate::dtos::personal::{CreatePersonalDTO, PersonalDataOut};
-use crate::services::auth_services::AuthService;
-use crate::middleware::auth_extractor::AuthenticatedUser;
+use crate::models::personal::{get_valid_skills, parse_and_validate_dob, profile_warnings, validate_full_name};
+
+use crate::dtos::auth::{
+    SignupIn, LoginIn, RefreshIn, SessionOut, ForgotPasswordIn, ResetPasswordIn, ResetPasswordOut, ResendConfirmationIn,
+};
+use crate::dtos::personal::{CreatePersonalDTO, PersonalDataOut, ProfileWarning};
+use crate::services::auth_services::{AuthService, AuthError};
+use crate::middleware::auth_extractor::{account_suspended_response, AuthenticatedUser};
 use crate::models::personal::NewPersonal;
 use crate::dtos::auth_dtos::CompleteProfileRequest;
 use crate::dtos::auth_dtos::LoginWithProfileResponse;
 use crate::dtos::auth_dtos::LoginNoProfileResponse;
+use crate::repositories::audit_log_repository::{AuditEntry, AuditLogRepository};
+use crate::repositories::skill_repository::SkillRepository;
+use crate::services::http_util::SendTracked;
+use crate::middleware::rate_limit::{rate_limited_response, resend_confirmation_limiter};
+use crate::dtos::response::ApiResponse;
+use crate::error::{AppError, ResultExt};
+use crate::AppState;
 
 fn looks_like_email(email: &str) -> bool {
     let re = Regex::new(r"(?i)^[A-Z0-9._%+-]+@[A-Z0-9.-]+\.[A-Z]{2,}$").unwrap();
     re.is_match(email)
 }
 
-#[derive(Serialize)]
-struct ApiResponse<T: serde::Serialize> {
-    status: String,
-    message: String,
-    data: Option<T>,
+/// 3-30 karakter: huruf, angka, `_` atau `.`
+fn is_valid_username(username: &str) -> bool {
+    let re = Regex::new(r"^[A-Za-z0-9_.]{3,30}$").unwrap();
+    re.is_match(username)
+}
+
+/// Best-effort audit trail write (never fails the request)
+fn audit(
+    svc: &AuthService,
+    req: &HttpRequest,
+    actor_id: Option<Uuid>,
+    action: &str,
+    detail: Option<serde_json::Value>,
+) {
+    AuditLogRepository::record(
+        &svc.supabase_url,
+        &svc.supabase_service_role_key,
+        &svc.client,
+        AuditEntry::from_request(req, actor_id, action, detail),
+    );
+}
+
+/// 403 for a login to an account whose email isn't confirmed yet (projects with
+/// email confirmation enabled); the client should show the resend-confirmation step
+fn email_not_confirmed_response() -> HttpResponse {
+    HttpResponse::Forbidden().json(ApiResponse::error_with_details(
+        "EMAIL_NOT_CONFIRMED",
+        "Please confirm your email before continuing",
+        serde_json::json!({ "next_step": "confirm_email" }),
+    ))
 }
 
 #[derive(Serialize)]
 struct SkillsResponse {
-    skills: Vec<&'static str>,
+    skills: Vec<String>,
     total: usize,
 }
 
+#[derive(Serialize)]
+struct EmailResponse {
+    email: String,
+}
+
 #[derive(serde::Serialize)]
 struct SignupResponse {
     user_id: Uuid,
@@ -43,6 +81,7 @@ struct SignupResponse {
 struct ProfileCompleteResponse {
     session: SessionOut,
     profile: PersonalDataOut,
+    warnings: Vec<ProfileWarning>,
     message: String,
     next_step: String,
 }
@@ -50,78 +89,166 @@ struct ProfileCompleteResponse {
 /// POST /auth/signup
 /// Step 1: Create account only, no session returned
 /// Client redirects to profile creation
+///
+/// Idempotent for retries: if the email is already registered but the given
+/// password logs in and the account has no profile yet (e.g. the first attempt
+/// created the user but the response was lost), the existing user id is
+/// returned with 200 and `next_step: "complete_profile"` instead of an error.
 #[post("/auth/signup")]
 pub async fn signup(
     svc: web::Data<AuthService>,
     body: web::Json<SignupIn>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     let email = body.email.trim().to_lowercase();
     
     // Validate email format
     if !looks_like_email(&email) {
-        return HttpResponse::BadRequest().json(ApiResponse::<()> {
-            status: "error".to_string(),
-            message: "Invalid email format".to_string(),
-            data: None,
-        });
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error("Invalid email format")));
+    }
+
+    // Optional disposable-domain blocklist (BLOCK_DISPOSABLE_EMAILS)
+    if svc.is_disposable_email(&email) {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error_with_code(
+            "DISPOSABLE_EMAIL",
+            "Disposable email addresses are not allowed. Please use a permanent email.",
+        )));
     }
 
     // Validate password length
     if body.password.len() < 6 {
-        return HttpResponse::BadRequest().json(ApiResponse::<()> {
-            status: "error".to_string(),
-            message: "Password must be at least 6 characters long".to_string(),
-            data: None,
-        });
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error(
+            "Password must be at least 6 characters long",
+        )));
+    }
+
+    let username = body
+        .username
+        .as_deref()
+        .map(str::trim)
+        .filter(|u| !u.is_empty());
+
+    // Nama kosong diabaikan seperti sebelumnya; yang diisi harus 1-80 karakter
+    if let Some(name) = body.full_name.as_deref().filter(|n| !n.trim().is_empty())
+        && let Err(msg) = validate_full_name(name)
+    {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error(msg)));
+    }
+
+    if let Some(name) = username {
+        if !is_valid_username(name) {
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::error(
+                "Username must be 3-30 characters: letters, numbers, '_' or '.'",
+            )));
+        }
+
+        match svc.is_username_taken(name).await {
+            Ok(false) => {}
+            Ok(true) => {
+                // Retry signup yang sama: username sudah milik akun ini sendiri
+                let owner = svc.username_owner(name).await.ok().flatten();
+                let resumed = resume_partial_signup(&svc, &email, &body.password).await;
+                if owner.is_none() || resumed != owner {
+                    return Ok(HttpResponse::Conflict().json(ApiResponse::error(
+                        "Username is already taken. Please choose another one.",
+                    )));
+                }
+            }
+            Err(e) => return Err(e).context("Failed to create account. Please try again."),
+        }
     }
 
     let signup_data = SignupIn {
         email: email.clone(),
         password: body.password.clone(),
-        username: body.username.clone(),
+        username: username.map(str::to_string),
+        full_name: body.full_name.clone(),
     };
 
     match svc.signup_only(signup_data).await {
         Ok(user_id) => {
+            store_signup_names(&svc, user_id, username, body.full_name.as_deref()).await;
+
             let response = SignupResponse {
                 user_id,
                 message: "Account created successfully. Please complete your profile to continue.".to_string(),
                 next_step: "complete_profile".to_string(),
             };
 
-            HttpResponse::Created().json(ApiResponse {
-                status: "success".to_string(),
-                message: "Account created".to_string(),
-                data: Some(response),
-            })
+            Ok(HttpResponse::Created().json(ApiResponse::success("Account created", response)))
         }
+        Err(e @ AuthError::Unavailable(_)) => Err(e.into()),
         Err(e) => {
-            eprintln!("Signup error: {}", e);
+            log::error!("Signup error: {}", e);
+
+            let already_registered = e.to_string().contains("already registered");
+
+            // Retry of a signup that already created the auth user: resume it
+            if already_registered
+                && let Some(user_id) = resume_partial_signup(&svc, &email, &body.password).await
+            {
+                store_signup_names(&svc, user_id, username, body.full_name.as_deref()).await;
+
+                let response = SignupResponse {
+                    user_id,
+                    message: "Account already created. Please complete your profile to continue.".to_string(),
+                    next_step: "complete_profile".to_string(),
+                };
+
+                return Ok(HttpResponse::Ok().json(ApiResponse::success("Account created", response)));
+            }
             
             // Handle specific Supabase errors
-            let error_msg = if e.to_string().contains("already registered") {
+            let error_msg = if already_registered {
                 "Email already exists. Please login instead."
             } else {
                 "Failed to create account. Please try again."
             };
 
-            HttpResponse::BadRequest().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: error_msg.to_string(),
-                data: None,
-            })
+            Ok(HttpResponse::BadRequest().json(ApiResponse::error(error_msg.to_string())))
         }
     }
 }
 
+/// Simpan username/full_name dari signup ke profiles (best-effort, akun sudah dibuat)
+async fn store_signup_names(
+    svc: &AuthService,
+    user_id: Uuid,
+    username: Option<&str>,
+    full_name: Option<&str>,
+) {
+    if let Err(e) = svc.init_profile_username(user_id, username, full_name).await
+    {
+        log::error!("Failed to store signup username for user {}: {}", user_id, e);
+    }
+}
+
+/// Returns the user id when `email`/`password` log in to an account that has no profile yet
+async fn resume_partial_signup(svc: &AuthService, email: &str, password: &str) -> Option<Uuid> {
+    let login_data = LoginIn {
+        email: email.to_string(),
+        password: password.to_string(),
+    };
+
+    let (_session, user_id) = svc.login_with_user_id(login_data).await.ok()?;
+
+    match svc.get_user_profile(user_id).await {
+        Ok(None) => {
+            log::info!("Resuming partial signup for user {}", user_id);
+            Some(user_id)
+        }
+        _ => None,
+    }
+}
+
 /// POST /auth/complete-profile
 /// Step 2: Add profile data and auto-login
 /// Returns session + profile data for dashboard redirect
 #[post("/auth/complete-profile")]
 pub async fn complete_profile(
+    req: HttpRequest,
     svc: web::Data<AuthService>,
     body: web::Json<CompleteProfileRequest>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     // Validate all required fields
     if body.email.trim().is_empty() 
         || body.password.trim().is_empty()
@@ -129,76 +256,49 @@ pub async fn complete_profile(
         || body.profile.primary_skill.trim().is_empty()
         || body.profile.skill_to_learn.trim().is_empty()
         || body.profile.bio.trim().is_empty() {
-        return HttpResponse::BadRequest().json(ApiResponse::<()> {
-            status: "error".to_string(),
-            message: "All fields are required".to_string(),
-            data: None,
-        });
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error("All fields are required")));
     }
 
-    // Parse and validate date
-    let parsed_date = match NaiveDate::parse_from_str(&body.profile.date_of_birth, "%d/%m/%Y") {
+    // Parse and validate date (format + age 13-120), sama dengan update profile
+    let parsed_date = match parse_and_validate_dob(&body.profile.date_of_birth) {
         Ok(d) => d,
-        Err(_) => {
-            // Fallback to ISO format
-            match NaiveDate::parse_from_str(&body.profile.date_of_birth, "%Y-%m-%d") {
-                Ok(d2) => d2,
-                Err(_) => {
-                    return HttpResponse::BadRequest().json(ApiResponse::<()> {
-                        status: "error".to_string(),
-                        message: "Invalid date format. Use DD/MM/YYYY".to_string(),
-                        data: None,
-                    });
-                }
-            }
-        }
+        Err(msg) => return Ok(HttpResponse::BadRequest().json(ApiResponse::error(msg))),
     };
 
-    // Validate age (13-120 years)
-    let today = chrono::Utc::now().naive_utc().date();
-    let min_date = today - chrono::Duration::days(365 * 120);
-    let max_date = today - chrono::Duration::days(365 * 13);
-
-    if parsed_date < min_date || parsed_date > max_date {
-        return HttpResponse::BadRequest().json(ApiResponse::<()> {
-            status: "error".to_string(),
-            message: "Age must be between 13 and 120 years".to_string(),
-            data: None,
-        });
-    }
-
     // Validate field lengths
-    if body.profile.primary_skill.len() > 100 || body.profile.skill_to_learn.len() > 100 {
-        return HttpResponse::BadRequest().json(ApiResponse::<()> {
-            status: "error".to_string(),
-            message: "Skills must be less than 100 characters each".to_string(),
-            data: None,
-        });
+    if body.profile.primary_skill.chars().count() > 100 || body.profile.skill_to_learn.chars().count() > 100 {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error(
+            "Skills must be less than 100 characters each",
+        )));
     }
 
-    if body.profile.bio.len() > 1000 {
-        return HttpResponse::BadRequest().json(ApiResponse::<()> {
-            status: "error".to_string(),
-            message: "Bio must be less than 1000 characters".to_string(),
-            data: None,
-        });
+    if body.profile.bio.chars().count() > 1000 {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error(
+            "Bio must be less than 1000 characters",
+        )));
     }
 
-    // Step 1: Login to get user_id and session
+    let full_name = match body.profile.full_name.as_deref().map(validate_full_name).transpose() {
+        Ok(name) => name,
+        Err(msg) => return Ok(HttpResponse::BadRequest().json(ApiResponse::error(msg))),
+    };
+
+    // Step 1: Login to get user_id and session (email lowercase, sama seperti signup)
+    let email = body.email.trim().to_lowercase();
     let login_data = LoginIn {
-        email: body.email.clone(),
+        email: email.clone(),
         password: body.password.clone(),
     };
 
     let (session, user_id) = match svc.login_with_user_id(login_data).await {
         Ok((session, user_id)) => (session, user_id),
+        Err(AuthError::EmailNotConfirmed) => return Ok(email_not_confirmed_response()),
+        Err(e @ AuthError::Unavailable(_)) => return Err(e.into()),
         Err(e) => {
-            eprintln!("Login failed during profile completion: {}", e);
-            return HttpResponse::Unauthorized().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: "Invalid credentials or account not activated".to_string(),
-                data: None,
-            });
+            log::warn!("Login failed during profile completion: {}", e);
+            return Ok(HttpResponse::Unauthorized().json(ApiResponse::error(
+                "Invalid credentials or account not activated",
+            )));
         }
     };
 
@@ -209,50 +309,61 @@ pub async fn complete_profile(
         primary_skill: body.profile.primary_skill.clone(),
         skill_to_learn: body.profile.skill_to_learn.clone(),
         bio: body.profile.bio.clone(),
+        is_discoverable: body.profile.is_discoverable,
+        full_name,
     };
 
-    
-    match svc.add_personal_sb(user_id, profile_dto).await {
-        Ok(saved_profile) => {
-            let response = ProfileCompleteResponse {
-                session,
-                profile: saved_profile,
-                message: "Profile completed successfully! Now you can upload a profile picture.".to_string(),
-                next_step: "upload_profile".to_string(), // CHANGED: redirect ke upload profile
-            };
+    let saved_profile = svc
+        .add_personal_sb(user_id, profile_dto)
+        .await
+        .context("Failed to save profile. Please try again.")?;
 
-            HttpResponse::Created().json(ApiResponse {
-                status: "success".to_string(),
-                message: "Profile completed and logged in".to_string(),
-                data: Some(response),
-            })
-        }
-        Err(e) => {
-            eprintln!("Failed to save profile for user {}: {}", user_id, e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: "Failed to save profile. Please try again.".to_string(),
-                data: None,
-            })
-        }
+    // Role hanya bisa naik ke admin lewat BOOTSTRAP_ADMIN_EMAIL, bukan dari payload profile
+    match svc.bootstrap_admin_if_configured(user_id, &email).await {
+        Ok(true) => audit(&svc, &req, Some(user_id), "admin_bootstrap", None),
+        Ok(false) => {}
+        Err(e) => log::error!("Failed to bootstrap admin role for user {}: {}", user_id, e),
     }
+
+    let warnings = profile_warnings(&saved_profile);
+    let response = ProfileCompleteResponse {
+        session,
+        profile: saved_profile,
+        warnings,
+        message: "Profile completed successfully! Now you can upload a profile picture.".to_string(),
+        next_step: "upload_profile".to_string(), // CHANGED: redirect ke upload profile
+    };
+
+    Ok(HttpResponse::Created().json(ApiResponse::success(
+        "Profile completed and logged in",
+        response,
+    )))
 }
 
 /// GET /api/skills
-/// Public endpoint to get available skill options
+/// Public endpoint to get available skill options: the active rows of the `skills`
+/// table, or the last known list (VALID_SKILLS before the table was ever read) when
+/// the table can't be read
 #[get("/api/skills")]
-pub async fn get_skills() -> impl Responder {
-    let skills = get_valid_skills();
+pub async fn get_skills(app_state: web::Data<AppState>) -> impl Responder {
+    let skills = match SkillRepository::refresh_active_skills(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+    ).await {
+        Ok(skills) if !skills.is_empty() => skills,
+        Ok(_) => get_valid_skills(),
+        Err(e) => {
+            log::warn!("Failed to read skills table, using cached skill list: {}", e);
+            get_valid_skills()
+        }
+    };
     let response = SkillsResponse {
         total: skills.len(),
         skills,
     };
 
-    HttpResponse::Ok().json(ApiResponse {
-        status: "success".to_string(),
-        message: "Skills retrieved successfully".to_string(),
-        data: Some(response),
-    })
+    HttpResponse::Ok().json(ApiResponse::success("Skills retrieved successfully", response))
 }
 
 /// POST /auth/login
@@ -260,27 +371,69 @@ pub async fn get_skills() -> impl Responder {
 /// Checks if profile exists and redirects accordingly
 #[post("/auth/login")]
 pub async fn login(
+    req: HttpRequest,
     svc: web::Data<AuthService>,
     body: web::Json<LoginIn>,
-) -> impl Responder {
-    let login_data = body.into_inner();
+) -> Result<HttpResponse, AppError> {
+    let mut login_data = body.into_inner();
+    // Sama seperti signup: email disimpan lowercase
+    login_data.email = login_data.email.trim().to_lowercase();
+    let attempted_email = login_data.email.clone();
 
     // Step 1: Authenticate user and get user_id directly from response
     let (session, user_id) = match svc.login_with_user_id(login_data).await {
-        Ok((session, user_id)) => (session, user_id),
+        Ok((session, user_id)) => {
+            audit(&svc, &req, Some(user_id), "login", None);
+            (session, user_id)
+        }
+        Err(e @ AuthError::Unavailable(_)) => return Err(e.into()),
         Err(e) => {
-            eprintln!("Login failed: {}", e);
-            return HttpResponse::Unauthorized().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: "Invalid email or password".to_string(),
-                data: None,
-            });
+            log::warn!("Login failed: {}", e);
+            audit(
+                &svc,
+                &req,
+                None,
+                "login_failed",
+                Some(serde_json::json!({ "email": attempted_email })),
+            );
+            if matches!(e, AuthError::EmailNotConfirmed) {
+                return Ok(email_not_confirmed_response());
+            }
+            return Ok(HttpResponse::Unauthorized().json(ApiResponse::error(
+                "Invalid email or password",
+            )));
         }
     };
 
+    // Akun yang di-suspend admin tidak boleh login (kalau cek gagal, login tetap jalan)
+    match svc.is_suspended(user_id).await {
+        Ok(true) => {
+            audit(&svc, &req, Some(user_id), "login_suspended", None);
+            return Ok(account_suspended_response());
+        }
+        Ok(false) => {}
+        Err(e) => log::error!("Failed to check suspension for user {}: {}", user_id, e),
+    }
+
     // Step 2: Check if user has profile
-    match svc.get_user_profile(user_id).await {
-        Ok(Some(profile)) => {
+    let profile = svc
+        .get_user_profile(user_id)
+        .await
+        .context("Failed to verify account status")?;
+
+    let response = match profile {
+        Some(profile) if !profile.is_active => {
+            // Akun dinonaktifkan sendiri oleh user - tawarkan reactivate
+            let response = LoginWithProfileResponse {
+                session,
+                profile,
+                message: "Your account is deactivated. Reactivate it to continue.".to_string(),
+                next_step: "reactivate".to_string(),
+            };
+
+            HttpResponse::Ok().json(ApiResponse::success("Account deactivated", response))
+        }
+        Some(profile) => {
             // User has profile - direct to dashboard
             let response = LoginWithProfileResponse {
                 session,
@@ -289,13 +442,9 @@ pub async fn login(
                 next_step: "dashboard".to_string(),
             };
 
-            HttpResponse::Ok().json(ApiResponse {
-                status: "success".to_string(),
-                message: "Login successful".to_string(),
-                data: Some(response),
-            })
+            HttpResponse::Ok().json(ApiResponse::success("Login successful", response))
         }
-        Ok(None) => {
+        None => {
             // User exists but no profile - redirect to profile creation
             let response = LoginNoProfileResponse {
                 session,
@@ -303,90 +452,293 @@ pub async fn login(
                 next_step: "complete_profile".to_string(),
             };
 
-            HttpResponse::Ok().json(ApiResponse {
-                status: "success".to_string(),
-                message: "Profile required".to_string(),
-                data: Some(response),
-            })
+            HttpResponse::Ok().json(ApiResponse::success("Profile required", response))
         }
-        Err(e) => {
-            eprintln!("Failed to check user profile: {}", e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: "Failed to verify account status".to_string(),
-                data: None,
-            })
+    };
+
+    Ok(response)
+}
+
+/// POST /auth/refresh
+/// Tukar refresh_token dengan session baru (access + refresh token baru)
+#[post("/auth/refresh")]
+pub async fn refresh(
+    svc: web::Data<AuthService>,
+    body: web::Json<RefreshIn>,
+) -> Result<HttpResponse, AppError> {
+    if body.refresh_token.trim().is_empty() {
+        return Err(AppError::Validation("refresh_token is required".to_string()));
+    }
+
+    match svc.refresh_session(body.refresh_token.trim()).await {
+        Ok(session) => Ok(HttpResponse::Ok().json(ApiResponse::success("Session refreshed", session))),
+        Err(AuthError::InvalidToken) => Ok(HttpResponse::Unauthorized().json(ApiResponse::error(
+            "Invalid or expired refresh token. Please log in again.",
+        ))),
+        Err(e) => Err(e).context("Failed to refresh session"),
+    }
+}
+
+/// POST /auth/forgot-password
+/// Always answers with the same message, whether or not the email has an
+/// account, so the endpoint can't be used to enumerate users.
+#[post("/auth/forgot-password")]
+pub async fn forgot_password(
+    req: HttpRequest,
+    svc: web::Data<AuthService>,
+    body: web::Json<ForgotPasswordIn>,
+) -> Result<HttpResponse, AppError> {
+    let email = body.email.trim().to_lowercase();
+
+    if !looks_like_email(&email) {
+        return Err(AppError::Validation("Invalid email format".to_string()));
+    }
+
+    // Hasil sebenarnya hanya dicatat di server
+    match svc.send_recovery_email(&email).await {
+        Ok(()) => {
+            log::info!("Password recovery email requested for {}", email);
+            audit(
+                &svc,
+                &req,
+                None,
+                "password_reset_requested",
+                Some(serde_json::json!({ "email": email })),
+            );
+        }
+        // Tidak bocor apa-apa: 503 hanya berarti Supabase tidak bisa dihubungi
+        Err(e @ AuthError::Unavailable(_)) => return Err(e.into()),
+        Err(e) => log::error!("Password recovery for {} failed: {}", email, e),
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success_message(
+        "If an account exists for this email, a password reset link has been sent.",
+    )))
+}
+
+/// POST /auth/resend-confirmation
+/// Send the signup confirmation email again. Like forgot-password, the answer
+/// doesn't reveal whether the email has an (unconfirmed) account. Besides the
+/// per-IP auth limit, each address gets a limited number of resends per minute.
+#[post("/auth/resend-confirmation")]
+pub async fn resend_confirmation(
+    svc: web::Data<AuthService>,
+    body: web::Json<ResendConfirmationIn>,
+) -> Result<HttpResponse, AppError> {
+    let email = body.email.trim().to_lowercase();
+
+    if !looks_like_email(&email) {
+        return Err(AppError::Validation("Invalid email format".to_string()));
+    }
+
+    // Dihitung per alamat, ada akun atau tidak, jadi 429 tidak membocorkan apa-apa
+    let decision = resend_confirmation_limiter().check(email.clone());
+    if !decision.allowed {
+        log::warn!("Resend confirmation limit exceeded for {}", email);
+        return Ok(rate_limited_response(&decision));
+    }
+
+    match svc.resend_confirmation(&email).await {
+        Ok(()) => log::info!("Confirmation email resent to {}", email),
+        Err(e @ AuthError::Unavailable(_)) => return Err(e.into()),
+        Err(e) => log::error!("Resending confirmation to {} failed: {}", email, e),
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success_message(
+        "If this email is waiting for confirmation, a new confirmation link has been sent.",
+    )))
+}
+
+/// POST /auth/reset-password
+/// Set a new password using the recovery token from the reset email, then sign the user
+/// out everywhere else. `other_sessions_revoked` tells whether that worked; other devices
+/// have to log in again with the new password.
+#[post("/auth/reset-password")]
+pub async fn reset_password(
+    req: HttpRequest,
+    svc: web::Data<AuthService>,
+    body: web::Json<ResetPasswordIn>,
+) -> Result<HttpResponse, AppError> {
+    if body.token.trim().is_empty() {
+        return Err(AppError::Validation("Reset token is required".to_string()));
+    }
+
+    // Sama dengan aturan signup
+    if body.new_password.len() < 6 {
+        return Err(AppError::Validation(
+            "Password must be at least 6 characters long".to_string(),
+        ));
+    }
+
+    match svc.update_password_with_token(body.token.trim(), &body.new_password).await {
+        Ok(user_id) => {
+            // Password sudah berubah; sesi lain yang gagal dicabut tidak membatalkannya
+            let other_sessions_revoked = match svc.sign_out_all_sessions(user_id).await {
+                Ok(()) => true,
+                Err(e) => {
+                    log::error!("Failed to sign out sessions of {} after password reset: {}", user_id, e);
+                    false
+                }
+            };
+            audit(
+                &svc,
+                &req,
+                Some(user_id),
+                "password_reset",
+                Some(serde_json::json!({ "other_sessions_revoked": other_sessions_revoked })),
+            );
+            Ok(HttpResponse::Ok().json(ApiResponse::success(
+                "Password updated. You can now log in with your new password.",
+                ResetPasswordOut { other_sessions_revoked },
+            )))
         }
+        Err(AuthError::InvalidToken) => Ok(HttpResponse::Unauthorized().json(ApiResponse::error(
+            "Reset link is invalid or has expired. Please request a new one.",
+        ))),
+        Err(AuthError::Other(msg)) => Err(AppError::Validation(msg)),
+        Err(e) => Err(e).context("Failed to reset password"),
     }
 }
+
 // Tambahkan ini ke handlers/auth_handlers.rs
 
 // Add this to src/handlers/auth_handlers.rs
 
-/// GET /api/profile
-/// Get current user's profile data (requires authentication)
-#[get("/api/profile")]
+/// GET /api/me/profile
+/// Completed profile of the current user, 404 while profile setup isn't done
+/// (a signup stub without skills counts as no profile).
+///
+/// This used to be registered on GET /api/profile too, where it was shadowed by
+/// `profile_handlers::get_user_profile`; that one stays canonical for the path.
+#[get("/api/me/profile")]
 pub async fn get_current_profile(
     svc: web::Data<AuthService>,
     user: AuthenticatedUser,
-) -> impl Responder {
-    println!("=== GET PROFILE REQUEST ===");
-    println!("User ID: {}", user.user_id);
-    
-    match svc.get_user_profile(user.user_id).await {
-        Ok(Some(profile)) => {
-            println!("Profile found for user {}: {:?}", user.user_id, profile);
-            HttpResponse::Ok().json(ApiResponse {
-                status: "success".to_string(),
-                message: "Profile retrieved successfully".to_string(),
-                data: Some(profile),
-            })
-        }
-        Ok(None) => {
-            println!("No profile found for user {}", user.user_id);
-            HttpResponse::NotFound().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: "Profile not found. Please complete your profile first.".to_string(),
-                data: None,
-            })
-        }
-        Err(e) => {
-            eprintln!("Failed to get profile for user {}: {}", user.user_id, e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: "Failed to retrieve profile".to_string(),
-                data: None,
-            })
-        }
-    }
+) -> Result<HttpResponse, AppError> {
+
+    let profile = svc.get_user_profile(user.user_id).await?.ok_or_else(|| {
+        AppError::NotFound("Profile not found. Please complete your profile first.".to_string())
+    })?;
+
+    log::debug!("Profile found for user {}: {:?}", user.user_id, profile);
+    Ok(HttpResponse::Ok().json(ApiResponse::success("Profile retrieved successfully", profile)))
+}
+
+/// GET /api/me/email
+/// Email of the authenticated user, taken from the token's `email` claim
+/// or fetched from Supabase when the claim is absent
+#[get("/api/me/email")]
+pub async fn get_current_email(
+    svc: web::Data<AuthService>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, AppError> {
+    let email = match user.email.clone().filter(|e| !e.trim().is_empty()) {
+        Some(email) => Some(email),
+        None => svc
+            .get_auth_user_email(&user.access_token)
+            .await
+            .context("Failed to retrieve email")?,
+    };
+
+    let email = email.ok_or_else(|| {
+        AppError::NotFound("No email associated with this account".to_string())
+    })?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Email retrieved successfully",
+        EmailResponse { email },
+    )))
 }
 
 #[get("/test/supabase")]
-pub async fn test_supabase(svc: web::Data<AuthService>) -> impl Responder {
+pub async fn test_supabase(svc: web::Data<AuthService>) -> Result<HttpResponse, AppError> {
     let url = format!("{}/rest/v1/profiles?limit=1", svc.supabase_url);
     
     let resp = svc.client
         .get(&url)
         .header("apikey", &svc.supabase_anon_key)
         .header("Authorization", format!("Bearer {}", &svc.supabase_service_role_key))
-        .send()
-        .await;
-
-    match resp {
-        Ok(response) => {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            HttpResponse::Ok().json(serde_json::json!({
-                "status": "success",
-                "supabase_status": status.as_u16(),
-                "body": body
-            }))
-        }
-        Err(e) => {
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "status": "error",
-                "message": format!("Supabase connection failed: {}", e)
-            }))
-        }
+        .send_with_retry()
+        .await
+        .context("Supabase connection failed")?;
+
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_default();
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "status": "success",
+        "supabase_status": status.as_u16(),
+        "body": body
+    })))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn username_allows_letters_digits_underscore_and_dot() {
+        assert!(is_valid_username("alice"));
+        assert!(is_valid_username("Al_ice.99"));
+        assert!(is_valid_username(&"a".repeat(30)));
+        assert!(!is_valid_username("al"));
+        assert!(!is_valid_username(&"a".repeat(31)));
+        assert!(!is_valid_username("al ice"));
+        assert!(!is_valid_username("al%ice"));
     }
-}
\ No newline at end of file
+
+    #[actix_web::test]
+    async fn login_sends_the_email_trimmed_and_lowercased() {
+        use std::sync::{Arc, Mutex};
+        use actix_web::{test, App};
+
+        let emails: Arc<Mutex<Vec<String>>> = Arc::default();
+        let seen = emails.clone();
+        let url = crate::test_support::mock_supabase(move |req, body| {
+            if req.path().ends_with("/token") {
+                let body: serde_json::Value = serde_json::from_slice(body).unwrap();
+                seen.lock().unwrap().push(body["email"].as_str().unwrap_or_default().to_string());
+                return HttpResponse::BadRequest().json(serde_json::json!({ "error": "invalid_grant" }));
+            }
+            HttpResponse::Created().finish()
+        });
+        let svc = AuthService::new(reqwest::Client::new(), url, "anon", "service");
+        let app = test::init_service(App::new().app_data(web::Data::new(svc)).service(login)).await;
+
+        let req = test::TestRequest::post()
+            .uri("/auth/login")
+            .set_json(serde_json::json!({ "email": "  Ayu@Example.COM ", "password": "secret123" }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+        assert_eq!(emails.lock().unwrap().as_slice(), ["ayu@example.com"]);
+    }
+
+    #[actix_web::test]
+    async fn password_reset_signs_out_the_other_sessions() {
+        use std::sync::{Arc, Mutex};
+        use actix_web::{test, App};
+
+        let user_id = Uuid::new_v4();
+        let calls: Arc<Mutex<Vec<String>>> = Arc::default();
+        let seen = calls.clone();
+        let url = crate::test_support::mock_supabase(move |req, _| {
+            seen.lock().unwrap().push(format!("{} {}", req.method(), req.path()));
+            if req.path() == "/auth/v1/user" {
+                return HttpResponse::Ok().json(serde_json::json!({ "id": user_id }));
+            }
+            HttpResponse::NoContent().finish()
+        });
+        let svc = AuthService::new(reqwest::Client::new(), url, "anon", "service");
+        let app = test::init_service(App::new().app_data(web::Data::new(svc)).service(reset_password)).await;
+
+        let req = test::TestRequest::post()
+            .uri("/auth/reset-password")
+            .set_json(serde_json::json!({ "token": "recovery-token", "new_password": "secret123" }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(body["data"]["other_sessions_revoked"], true);
+        let logout = format!("POST /auth/v1/admin/users/{}/logout", user_id);
+        assert!(calls.lock().unwrap().contains(&logout));
+    }
+}
@@ -0,0 +1,307 @@
+// src/handlers/barter_handlers.rs
+use std::collections::HashMap;
+use actix_web::{get, post, put, web, HttpResponse};
+use serde::Serialize;
+use uuid::Uuid;
+use crate::dtos::barter_dtos::{
+    BarterActionDTO, BarterRequestOut, BarterRequestWithProfileOut, CreateBarterRequestDTO,
+};
+use crate::dtos::personal::PublicProfileOut;
+use crate::handlers::skill_handlers::PageQuery;
+use crate::middleware::auth_extractor::AuthenticatedUser;
+use crate::models::personal::is_valid_skill;
+use crate::repositories::barter_repository::{
+    BarterRepository, CreateOutcome, StatusUpdate, STATUS_ACCEPTED, STATUS_REJECTED,
+};
+use crate::repositories::connection_repository::ConnectionRepository;
+use crate::repositories::profile_supabase_repo::ProfileSupabaseRepo;
+use crate::AppState;
+use crate::dtos::response::ApiResponse;
+use crate::error::{AppError, ResultExt};
+
+/// POST /api/barter/request
+/// Propose a skill swap to another user
+#[post("/api/barter/request")]
+pub async fn create_barter_request(
+    app_state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    body: web::Json<CreateBarterRequestDTO>,
+) -> Result<HttpResponse, AppError> {
+    let mut request = body.into_inner();
+    request.offered_skill = request.offered_skill.trim().to_string();
+    request.requested_skill = request.requested_skill.trim().to_string();
+
+    if request.recipient_id == user.user_id {
+        return Err(AppError::Validation(
+            "You cannot send a barter request to yourself".to_string(),
+        ));
+    }
+
+    if !is_valid_skill(&request.offered_skill) || !is_valid_skill(&request.requested_skill) {
+        return Err(AppError::Validation(
+            "Invalid skill. Please select from available options.".to_string(),
+        ));
+    }
+
+    let (url, key, client) = (&app_state.supabase_url, &app_state.supabase_key, &app_state.http_client);
+
+    if let Some(existing) = BarterRepository::find_pending_between(url, key, client, user.user_id, request.recipient_id)
+        .await
+        .context("Failed to create barter request")?
+    {
+        return Ok(pending_exists(existing, user.user_id, &request));
+    }
+
+    let outcome = BarterRepository::create_request(url, key, client, user.user_id, &request)
+        .await
+        .context("Failed to create barter request")?;
+
+    Ok(match outcome {
+        CreateOutcome::Created(created) => HttpResponse::Created().json(ApiResponse::success(
+            "Barter request sent",
+            created,
+        )),
+        CreateOutcome::UnknownRecipient => {
+            HttpResponse::NotFound().json(ApiResponse::error("Recipient not found"))
+        }
+        // Request lain (mis. double-tap) menang balapan insert: kembalikan yang sudah ada
+        CreateOutcome::DuplicatePending => {
+            match BarterRepository::find_pending_between(url, key, client, user.user_id, request.recipient_id)
+                .await
+                .context("Failed to create barter request")?
+            {
+                Some(existing) => pending_exists(existing, user.user_id, &request),
+                None => HttpResponse::Conflict().json(ApiResponse::error(
+                    "A pending barter request between you two already exists",
+                )),
+            }
+        }
+    })
+}
+
+/// Response when the pair already has a pending request: the same request sent
+/// again (double-tap, retry) gets the existing one back, anything else is a 409
+fn pending_exists(
+    existing: BarterRequestOut,
+    requester_id: Uuid,
+    request: &CreateBarterRequestDTO,
+) -> HttpResponse {
+    let same_request = existing.requester_id == requester_id
+        && existing.recipient_id == request.recipient_id
+        && existing.offered_skill == request.offered_skill
+        && existing.requested_skill == request.requested_skill;
+
+    if same_request {
+        HttpResponse::Ok().json(ApiResponse::success("Barter request already sent", existing))
+    } else {
+        HttpResponse::Conflict().json(ApiResponse::error(
+            "A pending barter request between you two already exists",
+        ))
+    }
+}
+
+/// PUT /api/barter/request/{id}
+/// Recipient accepts or rejects a pending request: `{ "action": "accept" | "reject" }`
+#[put("/api/barter/request/{id}")]
+pub async fn respond_barter_request(
+    app_state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<BarterActionDTO>,
+) -> Result<HttpResponse, AppError> {
+    let request_id = path.into_inner();
+
+    let new_status = match body.action.trim().to_lowercase().as_str() {
+        "accept" => STATUS_ACCEPTED,
+        "reject" => STATUS_REJECTED,
+        _ => {
+            return Err(AppError::Validation(
+                "Action must be 'accept' or 'reject'".to_string(),
+            ));
+        }
+    };
+
+    let update = BarterRepository::update_request_status(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        request_id,
+        user.user_id,
+        new_status,
+    ).await.context("Failed to update barter request")?;
+
+    if let StatusUpdate::Updated(ref updated) = update
+        && updated.status == STATUS_ACCEPTED
+    {
+        // Barter sudah diterima; connection yang gagal dibuat tidak membatalkannya.
+        // Baris yang hilang diisi ulang oleh backfill di ConnectionRepository::create.
+        if let Err(e) = ConnectionRepository::create(
+            &app_state.supabase_url,
+            &app_state.supabase_key,
+            &app_state.http_client,
+            updated.requester_id,
+            updated.recipient_id,
+        ).await {
+            log::error!(
+                "Failed to record connection for barter request {} ({} <-> {}), run the connections backfill: {}",
+                updated.id, updated.requester_id, updated.recipient_id, e
+            );
+        }
+    }
+
+    Ok(match update {
+        StatusUpdate::Updated(updated) => HttpResponse::Ok().json(ApiResponse::success(
+            format!("Barter request {}", new_status),
+            updated,
+        )),
+        StatusUpdate::NotFound => HttpResponse::NotFound().json(ApiResponse::error(
+            "Barter request not found",
+        )),
+        StatusUpdate::Forbidden => HttpResponse::Forbidden().json(ApiResponse::error_with_code(
+            "FORBIDDEN",
+            "Only the recipient can respond to this barter request",
+        )),
+        StatusUpdate::NotPending(current) => HttpResponse::Conflict().json(ApiResponse::error(
+            format!("Barter request is no longer pending ({})", current),
+        )),
+    })
+}
+
+/// Berapa request terbaru yang dikembalikan oleh list incoming/outgoing
+const BARTER_LIST_LIMIT: u32 = 50;
+
+/// Stand-in for a party whose profile was deleted or deactivated
+fn unavailable_profile(user_id: Uuid) -> PublicProfileOut {
+    PublicProfileOut {
+        user_id,
+        full_name: Some("Unavailable user".to_string()),
+        primary_skill: None,
+        skill_to_learn: None,
+        bio: None,
+        profile_picture_url: None,
+    }
+}
+
+/// Shared by incoming/outgoing: load the caller's requests, then the other party's
+/// profiles with one batched lookup
+async fn list_barter_requests(
+    app_state: &AppState,
+    profile_repo: &ProfileSupabaseRepo,
+    user_id: Uuid,
+    incoming: bool,
+) -> Result<HttpResponse, AppError> {
+    let requests = BarterRepository::list_for_user(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        user_id,
+        incoming,
+        BARTER_LIST_LIMIT,
+    ).await.context("Failed to retrieve barter requests")?;
+
+    let counterpart_of = |r: &BarterRequestOut| if incoming { r.requester_id } else { r.recipient_id };
+
+    let mut ids: Vec<Uuid> = requests.iter().map(counterpart_of).collect();
+    ids.sort();
+    ids.dedup();
+
+    // Profil gagal dimuat bukan alasan untuk menggagalkan seluruh inbox
+    let profiles: HashMap<Uuid, PublicProfileOut> = match profile_repo.get_by_user_ids(&ids).await {
+        Ok(profiles) => profiles.into_iter().map(|p| (p.user_id, p)).collect(),
+        Err(e) => {
+            log::error!("Failed to load barter counterpart profiles: {}", e);
+            HashMap::new()
+        }
+    };
+
+    let data: Vec<BarterRequestWithProfileOut> = requests
+        .into_iter()
+        .map(|request| {
+            let other = counterpart_of(&request);
+            // Satu user bisa muncul di beberapa request, jadi profilnya di-clone
+            let counterpart = profiles
+                .get(&other)
+                .cloned()
+                .unwrap_or_else(|| unavailable_profile(other));
+            BarterRequestWithProfileOut { request, counterpart }
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success("Barter requests retrieved successfully", data)))
+}
+
+/// GET /api/barters/incoming
+/// Requests sent to the caller, newest first, each with the requester's profile
+#[get("/api/barters/incoming")]
+pub async fn list_incoming_barters(
+    app_state: web::Data<AppState>,
+    profile_repo: web::Data<ProfileSupabaseRepo>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, AppError> {
+    list_barter_requests(&app_state, &profile_repo, user.user_id, true).await
+}
+
+/// GET /api/barters/outgoing
+/// Requests the caller sent, newest first, each with the recipient's profile
+#[get("/api/barters/outgoing")]
+pub async fn list_outgoing_barters(
+    app_state: web::Data<AppState>,
+    profile_repo: web::Data<ProfileSupabaseRepo>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, AppError> {
+    list_barter_requests(&app_state, &profile_repo, user.user_id, false).await
+}
+
+#[derive(Serialize)]
+struct ConnectionsResponse {
+    connections: Vec<PublicProfileOut>,
+    total: Option<i64>,
+    limit: u32,
+    offset: u32,
+}
+
+/// GET /api/me/connections
+/// Everyone the caller has accepted a barter with (either direction), newest connection first.
+/// Supports `?limit=` (default 20, max 100) and `?offset=`.
+#[get("/api/me/connections")]
+pub async fn list_connections(
+    app_state: web::Data<AppState>,
+    profile_repo: web::Data<ProfileSupabaseRepo>,
+    user: AuthenticatedUser,
+    query: web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0);
+
+    let (page, total) = ConnectionRepository::list_partner_ids(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        user.user_id,
+        limit,
+        offset,
+    ).await.context("Failed to retrieve connections")?;
+
+    let mut profiles: HashMap<Uuid, PublicProfileOut> = profile_repo
+        .get_by_user_ids(&page)
+        .await
+        .context("Failed to retrieve connections")?
+        .into_iter()
+        .map(|p| (p.user_id, p))
+        .collect();
+
+    let connections: Vec<PublicProfileOut> = page
+        .into_iter()
+        .map(|id| profiles.remove(&id).unwrap_or_else(|| unavailable_profile(id)))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Connections retrieved successfully",
+        ConnectionsResponse {
+            connections,
+            total,
+            limit,
+            offset,
+        },
+    )))
+}
@@ -0,0 +1,51 @@
+// src/handlers/fallback_handlers.rs
+use actix_web::error::{Error, InternalError, JsonPayloadError};
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse};
+use crate::dtos::response::ApiResponse;
+
+/// Default service for requests that don't match any registered route.
+/// Returns 405 when the path exists but not for this method, otherwise 404,
+/// both using the same JSON envelope as the rest of the API.
+pub async fn route_not_found(req: HttpRequest) -> HttpResponse {
+    if req.resource_map().has_resource(req.path()) {
+        return HttpResponse::MethodNotAllowed()
+            .json(ApiResponse::error_with_code("METHOD_NOT_ALLOWED", "Method not allowed"));
+    }
+
+    HttpResponse::NotFound().json(ApiResponse::error_with_code("NOT_FOUND", "Not found"))
+}
+
+/// Error handler for `web::Json` bodies (registered via JsonConfig), so a bad body gets
+/// the JSON envelope instead of actix's plain text. A multipart upload sent to the base64
+/// profile picture endpoints is pointed at the multipart endpoint.
+pub fn json_payload_error(err: JsonPayloadError, req: &HttpRequest) -> Error {
+    let is_multipart = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.trim_start().to_ascii_lowercase().starts_with("multipart/form-data"));
+
+    let response = match &err {
+        JsonPayloadError::ContentType if is_multipart && req.path().starts_with("/api/profile-picture") => {
+            HttpResponse::UnsupportedMediaType().json(ApiResponse::error_with_code(
+                "USE_MULTIPART_ENDPOINT",
+                "This endpoint expects JSON with a base64 image. Upload files as multipart/form-data to /api/profile-picture/upload-multipart instead.",
+            ))
+        }
+        JsonPayloadError::ContentType => HttpResponse::UnsupportedMediaType().json(
+            ApiResponse::error_with_code("UNSUPPORTED_MEDIA_TYPE", "Content-Type must be application/json"),
+        ),
+        JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+            HttpResponse::PayloadTooLarge()
+                .json(ApiResponse::error_with_code("PAYLOAD_TOO_LARGE", "Request body is too large"))
+        }
+        JsonPayloadError::Deserialize(e) => HttpResponse::BadRequest().json(
+            ApiResponse::error_with_code("INVALID_JSON", format!("Invalid JSON body: {}", e)),
+        ),
+        _ => HttpResponse::BadRequest()
+            .json(ApiResponse::error_with_code("INVALID_BODY", "Unable to read request body")),
+    };
+
+    InternalError::from_response(err, response).into()
+}
@@ -0,0 +1,97 @@
+// src/handlers/health_handlers.rs
+use std::env;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use actix_web::{get, web, HttpResponse, Responder};
+use serde::Serialize;
+use crate::services::http_util::supabase_breaker;
+use crate::AppState;
+
+#[derive(Serialize)]
+struct PoolStatus {
+    max_size: usize,
+    size: usize,
+    available: usize,
+    /// Request yang sedang menunggu koneksi
+    waiting: usize,
+}
+
+#[derive(Serialize)]
+struct HealthResponse {
+    status: &'static str,
+    database: &'static str,
+    /// "circuit_open" selama circuit breaker Supabase terbuka
+    supabase: &'static str,
+    pool: PoolStatus,
+    /// Detik sejak pool terakhir punya koneksi kosong (None = tidak jenuh)
+    saturated_for_secs: Option<u64>,
+}
+
+/// Kapan pool mulai jenuh (tidak ada koneksi tersedia), dibagi antar request /health
+fn saturated_since() -> &'static Mutex<Option<Instant>> {
+    static SATURATED_SINCE: OnceLock<Mutex<Option<Instant>>> = OnceLock::new();
+    SATURATED_SINCE.get_or_init(|| Mutex::new(None))
+}
+
+/// HEALTH_POOL_SATURATION_SECS: berapa lama pool boleh jenuh sebelum status "degraded" (default 30)
+fn saturation_threshold() -> Duration {
+    let secs = env::var("HEALTH_POOL_SATURATION_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(30);
+    Duration::from_secs(secs)
+}
+
+/// GET /health
+/// Database reachability plus deadpool saturation (size, available, waiting).
+/// 503 when no connection can be acquired; "degraded" when the pool has had
+/// no available connections for longer than HEALTH_POOL_SATURATION_SECS
+/// or while the Supabase circuit breaker is open.
+#[get("/health")]
+pub async fn health(app_state: web::Data<AppState>) -> impl Responder {
+    // Baca status dulu: koneksi yang diambil health check sendiri tidak ikut dihitung
+    let status = app_state.pg_pool.status();
+    let pool = PoolStatus {
+        max_size: status.max_size,
+        size: status.size,
+        available: status.available.max(0) as usize,
+        waiting: (-status.available).max(0) as usize,
+    };
+
+    let saturated_for = {
+        let mut since = saturated_since().lock().unwrap_or_else(|e| e.into_inner());
+        if status.available <= 0 && status.size >= status.max_size {
+            Some(since.get_or_insert_with(Instant::now).elapsed())
+        } else {
+            *since = None;
+            None
+        }
+    };
+
+    let database_up = matches!(
+        tokio::time::timeout(Duration::from_secs(2), app_state.pg_pool.get()).await,
+        Ok(Ok(_))
+    );
+
+    let circuit_open = supabase_breaker().is_open();
+    let degraded = circuit_open || saturated_for.is_some_and(|d| d >= saturation_threshold());
+    let body = HealthResponse {
+        status: if !database_up {
+            "down"
+        } else if degraded {
+            "degraded"
+        } else {
+            "ok"
+        },
+        database: if database_up { "up" } else { "down" },
+        supabase: if circuit_open { "circuit_open" } else { "up" },
+        pool,
+        saturated_for_secs: saturated_for.map(|d| d.as_secs()),
+    };
+
+    if database_up {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
+}
@@ -0,0 +1,154 @@
+// src/handlers/match_handlers.rs
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use crate::dtos::personal::PublicProfileOut;
+use crate::middleware::auth_extractor::AuthenticatedUser;
+use crate::models::personal::is_valid_skill;
+use crate::repositories::profile_supabase_repo::ProfileSupabaseRepo;
+use crate::services::auth_services::{AuthError, AuthService};
+use crate::services::matching::{score_match, SCORE_MUTUAL};
+use crate::dtos::response::ApiResponse;
+use crate::error::{AppError, ResultExt};
+
+/// 404 for a caller who hasn't completed their profile yet
+fn profile_required() -> AppError {
+    AppError::NotFound("Complete your profile to see matches".to_string())
+}
+
+#[derive(Serialize)]
+struct MatchOut {
+    /// Hanya field publik: tanggal lahir dan status akun user lain tidak ikut
+    #[serde(flatten)]
+    profile: PublicProfileOut,
+    /// "mutual" = saling cocok, "one_way" = hanya satu arah
+    match_type: &'static str,
+}
+
+#[derive(Serialize)]
+struct MatchesResponse {
+    matches: Vec<MatchOut>,
+    total: Option<i64>,
+    limit: u32,
+    offset: u32,
+}
+
+#[derive(Serialize)]
+struct SkillMatchOut {
+    #[serde(flatten)]
+    profile: PublicProfileOut,
+    match_score: u8,
+}
+
+#[derive(Serialize)]
+struct SkillMatchesResponse {
+    matches: Vec<SkillMatchOut>,
+    total: Option<i64>,
+    limit: u32,
+    offset: u32,
+}
+
+#[derive(Deserialize)]
+pub struct MatchListQuery {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct MatchSearchQuery {
+    pub skill: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// GET /api/matches
+/// Barter partners for the caller, mutual matches first.
+/// Supports `?limit=` (default 20, max 100) and `?offset=`; `total` counts all matches.
+#[get("/api/matches")]
+pub async fn get_matches(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+    query: web::Query<MatchListQuery>,
+) -> Result<HttpResponse, AppError> {
+    let me = svc
+        .get_user_profile(auth_user.user_id)
+        .await
+        .context("Failed to retrieve matches")?
+        .ok_or_else(profile_required)?;
+
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0);
+
+    let (matches, total) = match svc.find_mutual_matches(auth_user.user_id, limit, offset).await {
+        Err(AuthError::ProfileNotFound) => return Err(profile_required()),
+        result => result.context("Failed to retrieve matches")?,
+    };
+
+    let matches: Vec<MatchOut> = matches
+        .into_iter()
+        .map(|profile| {
+            let mutual = score_match(&me, &profile) == SCORE_MUTUAL;
+            MatchOut {
+                profile: profile.into(),
+                match_type: if mutual { "mutual" } else { "one_way" },
+            }
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Matches retrieved successfully",
+        MatchesResponse {
+            matches,
+            total,
+            limit,
+            offset,
+        },
+    )))
+}
+
+/// GET /api/matches/search?skill=Cooking
+/// Everyone who teaches `skill`, each with a match_score (0-100) against the caller.
+/// Supports `?limit=` (default 20, max 100) and `?offset=`.
+#[get("/api/matches/search")]
+pub async fn search_matches(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+    repo: web::Data<ProfileSupabaseRepo>,
+    query: web::Query<MatchSearchQuery>,
+) -> Result<HttpResponse, AppError> {
+    let skill = query.skill.as_deref().unwrap_or("").trim();
+    if !is_valid_skill(skill) {
+        return Err(AppError::Validation("A valid skill is required".to_string()));
+    }
+
+    let me = svc
+        .get_user_profile(auth_user.user_id)
+        .await
+        .context("Failed to retrieve matches")?
+        .ok_or_else(profile_required)?;
+
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0);
+
+    let (profiles, total) = repo
+        .list_by_primary_skill(skill, Some(auth_user.user_id), limit, offset)
+        .await
+        .context("Failed to retrieve matches")?;
+
+    let matches = profiles
+        .into_iter()
+        .map(|profile| SkillMatchOut {
+            match_score: score_match(&me, &profile),
+            profile,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Matches retrieved successfully",
+        SkillMatchesResponse {
+            matches,
+            total,
+            limit,
+            offset,
+        },
+    )))
+}
@@ -1,4 +1,11 @@
 pub mod auth_handlers;
 pub mod profile_picture_handlers;
 pub mod post_handlers;
-pub mod profile_handlers;
\ No newline at end of file
+pub mod profile_handlers;
+pub mod fallback_handlers;
+pub mod skill_handlers;
+pub mod search_handlers;
+pub mod health_handlers;
+pub mod match_handlers;
+pub mod barter_handlers;
+pub mod admin_handlers;
\ No newline at end of file
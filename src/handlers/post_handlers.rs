@@ -1,24 +1,26 @@
 // src/handlers/post_handlers.rs - Updated with proper profile support for logged-in users
 
-use actix_web::{post, web, get, HttpResponse};
-use crate::dtos::post_dtos::CreatePostDTO;
+use actix_web::{delete, post, put, web, get, HttpResponse};
+use uuid::Uuid;
+use crate::dtos::post_dtos::{CreatePostDTO, UpdatePostDTO};
+use crate::dtos::report_dtos::CreateReportDTO;
 use crate::repositories::post_repository::{PostRepository, PostWithProfile};
+use crate::repositories::report_repository::{ReportRepository, REPORT_REASONS};
 use crate::middleware::auth_extractor::AuthenticatedUser;
+use crate::services::content_filter::contains_banned_word;
+use crate::services::markdown::{render_markdown, sanitize_html};
 use crate::AppState;
-
-#[derive(serde::Serialize)]
-struct ApiResponse<T: serde::Serialize> {
-    status: String,
-    message: String,
-    data: Option<T>,
-}
+use crate::dtos::response::ApiResponse;
+use crate::error::{AppError, ResultExt};
 
 // Add Debug derive to fix the compilation error
 #[derive(Debug, serde::Serialize)]
 pub struct EnhancedPostOut {
-    pub id: String,
-    pub user_id: String,
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
     pub content: Option<String>,
+    /// Sanitized HTML rendered from the markdown `content`
+    pub content_html: Option<String>,
     pub image_url: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
@@ -30,40 +32,187 @@ pub struct EnhancedPostOut {
     pub is_own_post: bool,
 }
 
+/// Post plus the ids of its neighbors in the feed, for swipe navigation
+#[derive(serde::Serialize)]
+pub struct PostContextOut {
+    pub post: EnhancedPostOut,
+    /// next post further down the feed; None for the oldest post
+    pub older_id: Option<Uuid>,
+    /// next post further up the feed; None for the newest post
+    pub newer_id: Option<Uuid>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct PostListQuery {
+    pub limit: Option<u32>,
+}
+
+/// 403 for an authenticated caller who doesn't own the resource
+fn forbidden(message: &str) -> HttpResponse {
+    HttpResponse::Forbidden().json(ApiResponse::error_with_code("FORBIDDEN", message))
+}
+
+/// Batas panjang konten post (dalam karakter)
+const MAX_POST_CONTENT_CHARS: usize = 2000;
+
+/// Rules shared by create and update: not blank, at most MAX_POST_CONTENT_CHARS,
+/// and no banned words. Returns the content with trailing whitespace trimmed.
+fn validate_post_content(content: &str) -> Result<String, String> {
+    let content = content.trim_end();
+
+    if content.trim().is_empty() {
+        return Err("Post content cannot be empty".to_string());
+    }
+
+    if content.chars().count() > MAX_POST_CONTENT_CHARS {
+        return Err(format!("Post content must be at most {} characters", MAX_POST_CONTENT_CHARS));
+    }
+
+    if contains_banned_word(content) {
+        return Err("Your post contains language that isn't allowed. Please edit it and try again.".to_string());
+    }
+
+    Ok(content.to_string())
+}
+
 #[post("/posts")]
 pub async fn create_post(
     app_state: web::Data<AppState>,
     user: AuthenticatedUser,
     body: web::Json<CreatePostDTO>,
-) -> HttpResponse {
-    println!("=== CREATE POST DEBUG ===");
-    println!("User ID: {}", user.user_id);
-    println!("Content: {}", body.content);
-    println!("Image URL: {:?}", body.image_url);
+) -> Result<HttpResponse, AppError> {
+    log::debug!(
+        "Creating post for user {}: content={:?}, image_url={:?}",
+        user.user_id, body.content, body.image_url
+    );
 
-    match PostRepository::create_post(
+    let mut post = body.into_inner();
+    post.content = validate_post_content(&post.content).map_err(AppError::Validation)?;
+
+    // Jadwal harus di masa depan dan tidak lebih jauh dari POST_SCHEDULE_MAX_DAYS
+    if let Some(publish_at) = post.publish_at {
+        // Draft di-publish manual; jadwal otomatis hanya untuk post biasa
+        if post.draft {
+            return Err(AppError::Validation(
+                "A draft can't be scheduled; publish it instead".to_string(),
+            ));
+        }
+
+        let max_days = std::env::var("POST_SCHEDULE_MAX_DAYS")
+            .ok()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .unwrap_or(30);
+        let now = chrono::Utc::now();
+
+        if publish_at <= now {
+            return Err(AppError::Validation("publish_at must be in the future".to_string()));
+        }
+
+        if publish_at > now + chrono::Duration::days(max_days) {
+            return Err(AppError::Validation(format!(
+                "Posts can be scheduled at most {} days ahead",
+                max_days
+            )));
+        }
+    }
+
+    let post = PostRepository::create_post(
         &app_state.supabase_url,
         &app_state.supabase_key,
         &app_state.http_client,
         user.user_id,
-        body.into_inner(),
-    ).await {
-        Ok(post) => {
-            println!("Post created successfully: {:?}", post);
-            HttpResponse::Ok().json(ApiResponse {
-                status: "success".to_string(),
-                message: "Post created successfully".to_string(),
-                data: Some(post),
-            })
-        }
-        Err(e) => {
-            println!("Failed to create post: {:?}", e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: format!("Failed to create post: {}", e),
-                data: None,
-            })
+        post,
+    ).await?;
+
+    log::debug!("Post created successfully: {:?}", post);
+    Ok(HttpResponse::Ok().json(ApiResponse::success("Post created successfully", post)))
+}
+
+/// PUT /api/posts/{id}
+/// Update content and/or image of the caller's own post.
+/// Omitting `image_url` keeps the current image, sending `null` removes it.
+#[put("/posts/{id}")]
+pub async fn update_post(
+    app_state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<UpdatePostDTO>,
+) -> Result<HttpResponse, AppError> {
+    let post_id = path.into_inner();
+    let mut update = body.into_inner();
+
+    log::debug!("Updating post {} for user {}", post_id, user.user_id);
+
+    if update.content.is_none() && update.image_url.is_none() {
+        return Err(AppError::Validation("Nothing to update".to_string()));
+    }
+
+    if let Some(content) = update.content.as_deref() {
+        update.content = Some(validate_post_content(content).map_err(AppError::Validation)?);
+    }
+
+    let updated = PostRepository::update_post(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        post_id,
+        user.user_id,
+        update,
+    ).await.context("Failed to update post")?;
+
+    if let Some(post) = updated {
+        log::debug!("Post updated successfully: {:?}", post);
+        return Ok(HttpResponse::Ok().json(ApiResponse::success(
+            "Post updated successfully",
+            with_safe_html(post),
+        )));
+    }
+
+    // Tidak ada row yang cocok: bedakan post milik orang lain (403) dari post yang tidak ada (404)
+    let owner = PostRepository::get_post_owner(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        post_id,
+    ).await.context("Failed to update post")?;
+
+    match owner {
+        Some(_) => Ok(forbidden("You can only edit your own posts")),
+        None => Err(AppError::NotFound("Post not found".to_string())),
+    }
+}
+
+/// DELETE /api/posts/{id}
+/// Soft delete one of the caller's own posts (it disappears from feeds, the row is kept).
+/// A post that doesn't exist and a post owned by someone else both return 404,
+/// so ownership isn't revealed.
+#[delete("/posts/{id}")]
+pub async fn delete_post(
+    app_state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let post_id = path.into_inner();
+
+    log::debug!("Deleting post {} for user {}", post_id, user.user_id);
+
+    let deleted = PostRepository::soft_delete_post(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        post_id,
+        user.user_id,
+    ).await.context("Failed to delete post")?;
+
+    match deleted {
+        Some(post) => {
+            log::info!("Post deleted: {}", post.id);
+            Ok(HttpResponse::Ok().json(ApiResponse::success(
+                "Post deleted successfully",
+                with_safe_html(post),
+            )))
         }
+        None => Err(AppError::NotFound("Post not found".to_string())),
     }
 }
 
@@ -71,11 +220,10 @@ pub async fn create_post(
 pub async fn list_posts(
     app_state: web::Data<AppState>,
     user: Option<AuthenticatedUser>,
-) -> HttpResponse {
-    println!("=== LIST POSTS WITH PROFILES DEBUG ===");
+) -> Result<HttpResponse, AppError> {
     
-    let current_user_id = user.as_ref().map(|u| u.user_id.to_string());
-    println!("Current user ID: {:?}", current_user_id);
+    let current_user_id = user.as_ref().map(|u| u.user_id);
+    log::debug!("Listing posts for viewer {:?}", current_user_id);
     
     match PostRepository::list_posts_with_profiles(
         &app_state.supabase_url,
@@ -84,79 +232,317 @@ pub async fn list_posts(
         50
     ).await {
         Ok(posts) => {
-            println!("Posts with profiles retrieved: {} items", posts.len());
+            log::debug!("Posts with profiles retrieved: {} items", posts.len());
             
             // Transform posts to enhanced format
             let enhanced_posts: Vec<EnhancedPostOut> = posts
                 .into_iter()
                 .map(|post| {
-                    println!("Processing post: ID={}, UserID={}, Profile={:?}", 
+                    log::debug!("Processing post: ID={}, UserID={}, Profile={:?}", 
                             post.id, post.user_id, post.profiles);
-                    transform_post_with_profile(post, current_user_id.as_deref())
+                    transform_post_with_profile(post, current_user_id)
                 })
                 .collect();
             
-            println!("Enhanced posts: {:?}", enhanced_posts);
+            log::debug!("Enhanced posts: {:?}", enhanced_posts);
             
-            HttpResponse::Ok().json(ApiResponse {
-                status: "success".to_string(),
-                message: "Posts retrieved successfully".to_string(),
-                data: Some(enhanced_posts),
-            })
+            Ok(HttpResponse::Ok().json(ApiResponse::success(
+                "Posts retrieved successfully",
+                enhanced_posts,
+            )))
         }
         Err(e) => {
-            println!("Failed to list posts with profiles: {:?}", e);
+            log::error!("Failed to list posts with profiles: {:?}", e);
             
             // Fallback to basic posts if profile join fails
-            println!("Falling back to basic posts...");
-            match PostRepository::list_posts(
+            log::warn!("Falling back to posts without profiles");
+            let basic_posts = PostRepository::list_posts(
                 &app_state.supabase_url,
                 &app_state.supabase_key,
                 &app_state.http_client,
                 50
-            ).await {
-                Ok(basic_posts) => {
-                    let enhanced_posts: Vec<EnhancedPostOut> = basic_posts
-                        .into_iter()
-                        .map(|post| transform_basic_post(post, current_user_id.as_deref()))
-                        .collect();
-                    
-                    HttpResponse::Ok().json(ApiResponse {
-                        status: "success".to_string(),
-                        message: "Posts retrieved successfully (basic mode)".to_string(),
-                        data: Some(enhanced_posts),
-                    })
-                }
-                Err(e2) => {
-                    println!("Failed to retrieve basic posts: {:?}", e2);
-                    HttpResponse::InternalServerError().json(ApiResponse::<()> {
-                        status: "error".to_string(),
-                        message: "Failed to retrieve posts".to_string(),
-                        data: None,
-                    })
-                }
-            }
+            ).await.context("Failed to retrieve posts")?;
+
+            let enhanced_posts: Vec<EnhancedPostOut> = basic_posts
+                .into_iter()
+                .map(|post| transform_basic_post(post, current_user_id))
+                .collect();
+
+            Ok(HttpResponse::Ok().json(ApiResponse::success(
+                "Posts retrieved successfully (basic mode)",
+                enhanced_posts,
+            )))
         }
     }
 }
 
+/// GET /api/posts/{id}
+/// Single post with its author's profile, e.g. for a permalink page
+#[get("/posts/{id}")]
+pub async fn get_post(
+    app_state: web::Data<AppState>,
+    user: Option<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let post_id = path.into_inner();
+    let current_user_id = user.as_ref().map(|u| u.user_id);
+
+    let post = PostRepository::get_post_with_profile(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        post_id,
+    ).await
+        .context("Failed to retrieve post")?
+        .ok_or_else(|| AppError::NotFound("Post not found".to_string()))?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Post retrieved successfully",
+        transform_post_with_profile(post, current_user_id),
+    )))
+}
+
+/// GET /api/posts/{id}/context
+/// The post plus the ids of the next older and next newer post, so the client can swipe without refetching the feed
+#[get("/posts/{id}/context")]
+pub async fn get_post_context(
+    app_state: web::Data<AppState>,
+    user: Option<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let post_id = path.into_inner();
+    let current_user_id = user.as_ref().map(|u| u.user_id);
+    let (url, key, client) = (&app_state.supabase_url, &app_state.supabase_key, &app_state.http_client);
+
+    let post = PostRepository::get_post_with_profile(url, key, client, post_id)
+        .await
+        .context("Failed to retrieve post")?
+        .ok_or_else(|| AppError::NotFound("Post not found".to_string()))?;
+
+    let (older_id, newer_id) = match post.created_at.as_deref() {
+        Some(created_at) => {
+            let (older, newer) = futures::join!(
+                PostRepository::get_neighbor_post_id(url, key, client, post_id, created_at, true),
+                PostRepository::get_neighbor_post_id(url, key, client, post_id, created_at, false),
+            );
+            (
+                older.context("Failed to retrieve post")?,
+                newer.context("Failed to retrieve post")?,
+            )
+        }
+        None => (None, None),
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Post retrieved successfully",
+        PostContextOut {
+            post: transform_post_with_profile(post, current_user_id),
+            older_id,
+            newer_id,
+        },
+    )))
+}
+
+/// GET /api/users/{user_id}/posts
+/// Published posts of one user, newest first. `?limit=` (default 50, max 100).
+/// An unknown user or a user without posts gives an empty list.
+#[get("/users/{user_id}/posts")]
+pub async fn list_user_posts(
+    app_state: web::Data<AppState>,
+    user: Option<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    query: web::Query<PostListQuery>,
+) -> Result<HttpResponse, AppError> {
+    let author_id = path.into_inner();
+    let current_user_id = user.as_ref().map(|u| u.user_id);
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+
+    let posts = PostRepository::get_user_posts_with_profile(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        author_id,
+        limit,
+    ).await.context("Failed to retrieve posts")?;
+
+    let enhanced_posts: Vec<EnhancedPostOut> = posts
+        .into_iter()
+        .map(|post| transform_post_with_profile(post, current_user_id))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Posts retrieved successfully",
+        enhanced_posts,
+    )))
+}
+
+/// GET /api/me/posts/scheduled
+/// The caller's scheduled posts that aren't published yet, soonest first, with their
+/// `publish_at`. Only the author sees them; `?limit=` (default 50, max 100).
+#[get("/me/posts/scheduled")]
+pub async fn list_scheduled_posts(
+    app_state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    query: web::Query<PostListQuery>,
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+
+    let posts = PostRepository::list_scheduled_posts(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        user.user_id,
+        limit,
+    ).await?;
+    let posts: Vec<_> = posts.into_iter().map(with_safe_html).collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success("Scheduled posts retrieved successfully", posts)))
+}
+
+/// GET /api/me/drafts
+/// The caller's unpublished posts, most recently edited first: drafts, and scheduled
+/// posts until they are published (those have `publish_at` set). Only the author
+/// sees them; `?limit=` (default 50, max 100).
+#[get("/me/drafts")]
+pub async fn list_drafts(
+    app_state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    query: web::Query<PostListQuery>,
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+
+    let drafts = PostRepository::list_drafts(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        user.user_id,
+        limit,
+    ).await.context("Failed to retrieve drafts")?;
+    let drafts: Vec<_> = drafts.into_iter().map(with_safe_html).collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success("Drafts retrieved successfully", drafts)))
+}
+
+/// POST /api/posts/{id}/publish
+/// Publish one of the caller's drafts. It gets a fresh created_at, so it shows at the
+/// top of the feed. 403 for someone else's post, 409 when it isn't a draft.
+#[post("/posts/{id}/publish")]
+pub async fn publish_draft(
+    app_state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let post_id = path.into_inner();
+
+    let published = PostRepository::publish_draft(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        post_id,
+        user.user_id,
+    ).await.context("Failed to publish post")?;
+
+    if let Some(post) = published {
+        return Ok(HttpResponse::Ok().json(ApiResponse::success(
+            "Post published successfully",
+            with_safe_html(post),
+        )));
+    }
+
+    let owner = PostRepository::get_post_owner(
+        &app_state.supabase_url,
+        &app_state.supabase_key,
+        &app_state.http_client,
+        post_id,
+    ).await.context("Failed to publish post")?;
+
+    match owner {
+        Some(owner) if owner == user.user_id => Ok(HttpResponse::Conflict().json(
+            ApiResponse::error_with_code("NOT_A_DRAFT", "Post is already published"),
+        )),
+        Some(_) => Ok(forbidden("You can only publish your own posts")),
+        None => Err(AppError::NotFound("Post not found".to_string())),
+    }
+}
+
+/// Batas panjang detail report (dalam karakter)
+const MAX_REPORT_DETAIL_CHARS: usize = 1000;
+
+/// POST /api/posts/{id}/report
+/// Report a post for moderation: `{ "reason": "spam" | "harassment" | "other", "detail": "..." }`.
+/// A user can report the same post only once (409 on the second try).
+#[post("/posts/{id}/report")]
+pub async fn report_post(
+    app_state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<CreateReportDTO>,
+) -> Result<HttpResponse, AppError> {
+    let post_id = path.into_inner();
+    let mut report = body.into_inner();
+
+    report.reason = report.reason.trim().to_ascii_lowercase();
+    if !REPORT_REASONS.contains(&report.reason.as_str()) {
+        return Err(AppError::Validation(format!(
+            "Invalid reason. Allowed values: {}",
+            REPORT_REASONS.join(", ")
+        )));
+    }
+
+    report.detail = report.detail.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    if report.detail.as_ref().is_some_and(|d| d.chars().count() > MAX_REPORT_DETAIL_CHARS) {
+        return Err(AppError::Validation(format!(
+            "Detail must be at most {} characters",
+            MAX_REPORT_DETAIL_CHARS
+        )));
+    }
+
+    let (url, key, client) = (&app_state.supabase_url, &app_state.supabase_key, &app_state.http_client);
+
+    // Draft dan post terjadwal tidak terlihat orang lain, jadi juga tidak bisa dilaporkan
+    let owner = PostRepository::get_public_post_owner(url, key, client, post_id)
+        .await
+        .context("Failed to report post")?
+        .ok_or_else(|| AppError::NotFound("Post not found".to_string()))?;
+    if owner == user.user_id {
+        return Err(AppError::Validation("You can't report your own post".to_string()));
+    }
+
+    let created = ReportRepository::create_report(url, key, client, post_id, user.user_id, &report)
+        .await
+        .context("Failed to report post")?;
+
+    Ok(match created {
+        Some(created) => HttpResponse::Created().json(ApiResponse::success(
+            "Post reported. Thank you for helping keep BarterUp safe.",
+            created,
+        )),
+        None => HttpResponse::Conflict().json(ApiResponse::error_with_code(
+            "ALREADY_REPORTED",
+            "You have already reported this post",
+        )),
+    })
+}
+
 /// Transform PostWithProfile to EnhancedPostOut
-fn transform_post_with_profile(post: PostWithProfile, current_user_id: Option<&str>) -> EnhancedPostOut {
+pub(crate) fn transform_post_with_profile(post: PostWithProfile, current_user_id: Option<Uuid>) -> EnhancedPostOut {
     let profile = post.profiles.as_ref();
-    let is_own_post = current_user_id == Some(&post.user_id);
+    let is_own_post = current_user_id == Some(post.user_id);
     
-    println!("Transform debug - Post user: {}, Current user: {:?}, Is own: {}", 
+    log::debug!("Transform debug - Post user: {}, Current user: {:?}, Is own: {}", 
              post.user_id, current_user_id, is_own_post);
     
-    // Use profile data if available, otherwise fallback to defaults
+    // Use profile data if available: full_name, then username, then defaults
     let author_name = profile
         .and_then(|p| p.full_name.clone())
         .filter(|name| !name.trim().is_empty())
+        .or_else(|| profile.and_then(|p| p.username.clone()))
+        .filter(|name| !name.trim().is_empty())
         .unwrap_or_else(|| {
             if is_own_post {
                 "You".to_string()  // Show "You" for current user's posts
             } else {
-                "Anonymous User".to_string()
+                "Member".to_string()
             }
         });
     
@@ -175,10 +561,13 @@ fn transform_post_with_profile(post: PostWithProfile, current_user_id: Option<&s
         .and_then(|p| p.primary_skill.clone())
         .filter(|skill| !skill.trim().is_empty());
     
+    let content_html = safe_content_html(post.content_html, post.content.as_deref());
+
     EnhancedPostOut {
         id: post.id,
-        user_id: post.user_id.clone(),
+        user_id: Some(post.user_id),
         content: post.content,
+        content_html,
         image_url: post.image_url,
         created_at: post.created_at,
         updated_at: post.updated_at,
@@ -190,15 +579,33 @@ fn transform_post_with_profile(post: PostWithProfile, current_user_id: Option<&s
     }
 }
 
+/// HTML for the response: the stored `content_html` is sanitized again on read (it may
+/// have been written outside the API); posts created before markdown support have
+/// none and are rendered on the fly
+fn safe_content_html(stored: Option<String>, content: Option<&str>) -> Option<String> {
+    match stored {
+        Some(html) => Some(sanitize_html(&html)),
+        None => content.map(render_markdown),
+    }
+}
+
+/// `post` as stored, with its `content_html` made safe by [`safe_content_html`]
+fn with_safe_html(mut post: crate::dtos::post_dtos::PostOut) -> crate::dtos::post_dtos::PostOut {
+    post.content_html = safe_content_html(post.content_html.take(), post.content.as_deref());
+    post
+}
+
 /// Transform basic PostOut to EnhancedPostOut (fallback)
-fn transform_basic_post(post: crate::dtos::post_dtos::PostOut, current_user_id: Option<&str>) -> EnhancedPostOut {
-    let post_user_id = post.user_id.as_ref().map(|s| s.as_str()).unwrap_or("");
-    let is_own_post = current_user_id == Some(post_user_id) && !post_user_id.is_empty();
+fn transform_basic_post(post: crate::dtos::post_dtos::PostOut, current_user_id: Option<Uuid>) -> EnhancedPostOut {
+    let is_own_post = current_user_id.is_some() && current_user_id == post.user_id;
     
+    let content_html = safe_content_html(post.content_html, post.content.as_deref());
+
     EnhancedPostOut {
         id: post.id,
-        user_id: post.user_id.unwrap_or_default(),
+        user_id: post.user_id,
         content: post.content,
+        content_html,
         image_url: post.image_url,
         created_at: post.created_at,
         updated_at: post.updated_at,
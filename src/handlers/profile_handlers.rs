@@ -1,130 +1,171 @@
 // src/handlers/profile_handlers.rs
-use actix_web::{get, put, web, HttpResponse, Responder};
-use serde::{Deserialize, Serialize};
-use crate::services::auth_services::AuthService;
+use actix_web::{delete, get, patch, post, put, routes, web, HttpRequest, HttpResponse};
+use serde::Serialize;
+use crate::services::auth_services::{AuthError, AuthService};
+use crate::dtos::auth::{DeleteAccountIn, LoginIn};
+use crate::handlers::profile_picture_handlers::remove_profile_picture_files;
+use crate::repositories::audit_log_repository::{AuditEntry, AuditLogRepository};
+use crate::repositories::post_repository::PostRepository;
+use crate::repositories::barter_repository::BarterRepository;
+use crate::repositories::connection_repository::ConnectionRepository;
+use crate::repositories::report_repository::ReportRepository;
+use crate::services::user_locks::UserLocks;
 use crate::middleware::auth_extractor::AuthenticatedUser;
-use crate::dtos::personal::{PersonalDataOut, CreatePersonalDTO};
-use chrono::NaiveDate;
+use crate::dtos::personal::{PersonalDataOut, CreatePersonalDTO, ProfileWriteOut, PublicProfileOut, UpdateProfilePatchDTO};
+use crate::repositories::profile_supabase_repo::{ProfileSupabaseRepo, RepoError};
+use crate::services::matching::{match_label, score_match};
+use crate::models::personal::{Personal, age_from_iso_date, get_valid_skills, is_valid_skill, parse_and_validate_dob, profile_warnings, validate_full_name};
+use crate::repositories::profile_history_repository::{ProfileFieldChange, ProfileHistoryRepository};
+use crate::dtos::response::ApiResponse;
+use crate::error::{AppError, ResultExt};
 use uuid::Uuid;
 
-#[derive(Serialize)]
-struct ApiResponse<T: serde::Serialize> {
-    status: String,
-    message: String,
-    data: Option<T>,
+/// Profile row -> response DTO (handler edge of ProfileSupabaseRepo)
+fn personal_out(profile: Personal) -> PersonalDataOut {
+    let date_of_birth = profile.date_of_birth.map(|d| d.format("%Y-%m-%d").to_string());
+    PersonalDataOut {
+        id: profile.id,
+        user_id: profile.user_id,
+        full_name: profile.full_name,
+        age: age_from_iso_date(date_of_birth.as_deref()),
+        date_of_birth,
+        primary_skill: profile.primary_skill,
+        skill_to_learn: profile.skill_to_learn,
+        bio: profile.bio,
+        profile_picture_url: profile.profile_picture_url,
+        is_discoverable: profile.is_discoverable.unwrap_or(true),
+        is_active: profile.is_active.unwrap_or(true),
+        created_at: profile.created_at,
+        updated_at: profile.updated_at,
+    }
+}
+
+/// Profile row of `user_id`; None when the user has no row yet
+async fn find_profile(repo: &ProfileSupabaseRepo, user_id: Uuid) -> Result<Option<Personal>, RepoError> {
+    match repo.get_by_user_id(user_id).await {
+        Ok(profile) => Ok(Some(profile)),
+        Err(RepoError::NotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct ProfileDbRecord {
-    pub id: String,
-    pub date_of_birth: Option<String>,
-    pub primary_skill: Option<String>,
-    pub skill_to_learn: Option<String>,
-    pub bio: Option<String>,
-    pub profile_picture_url: Option<String>,
-    pub full_name: Option<String>,
-    pub role: Option<String>,
+#[derive(Serialize)]
+struct PublicProfileView {
+    #[serde(flatten)]
+    profile: PublicProfileOut,
+    /// "mutual" | "one_way" | "none" dibanding profile viewer; tidak ada untuk anonymous
+    #[serde(skip_serializing_if = "Option::is_none")]
+    match_with_me: Option<&'static str>,
 }
 
 /// GET /api/profile
-/// Get current user's profile data 
+/// Get current user's profile data. Canonical handler for this path.
+///
+/// Contract: 200 with the profile once it is completed; 404 PROFILE_NOT_FOUND with
+/// `next_step: "complete_profile"` when there is no row yet or only the signup stub
+/// (no primary_skill), the same hint login gives.
 #[get("/api/profile")]
 pub async fn get_user_profile(
     auth_user: AuthenticatedUser,
-    svc: web::Data<AuthService>,
-) -> impl Responder {
-    println!("=== GET PROFILE DEBUG ===");
-    println!("User ID from auth: {}", auth_user.user_id);
+    repo: web::Data<ProfileSupabaseRepo>,
+) -> Result<HttpResponse, AppError> {
+    log::debug!("Getting profile of user {}", auth_user.user_id);
 
     // Get profile from profiles table
-    match get_user_profile_data(&svc, auth_user.user_id).await {
-        Ok(profile_opt) => {
-            if let Some(profile) = profile_opt {
-                // Convert to PersonalDataOut using your exact DTO structure
-                let personal_data = PersonalDataOut {
-                    id: Uuid::parse_str(&profile.id).unwrap_or(auth_user.user_id),
-                    user_id: auth_user.user_id,
-                    date_of_birth: profile.date_of_birth.unwrap_or_default(),
-                    primary_skill: profile.primary_skill.unwrap_or_default(),
-                    skill_to_learn: profile.skill_to_learn.unwrap_or_default(),
-                    bio: profile.bio.unwrap_or_default(),
-                    profile_picture_url: profile.profile_picture_url,
-                };
-
-                println!("Profile found: {:?}", personal_data);
-
-                HttpResponse::Ok().json(ApiResponse {
-                    status: "success".to_string(),
-                    message: "Profile retrieved successfully".to_string(),
-                    data: Some(personal_data),
-                })
-            } else {
-                println!("No profile found for user {}", auth_user.user_id);
-                HttpResponse::Ok().json(ApiResponse::<PersonalDataOut> {
-                    status: "success".to_string(),
-                    message: "No profile found".to_string(),
-                    data: None,
-                })
-            }
-        }
-        Err(e) => {
-            println!("Failed to get user profile: {}", e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: "Failed to retrieve profile".to_string(),
-                data: None,
-            })
-        }
+    let completed = find_completed_profile(&repo, auth_user.user_id)
+        .await
+        .context("Failed to retrieve profile")?;
+
+    if let Some(profile) = completed {
+        let personal_data = personal_out(profile);
+
+        log::debug!("Profile found: {:?}", personal_data);
+
+        Ok(HttpResponse::Ok().json(ApiResponse::success(
+            "Profile retrieved successfully",
+            personal_data,
+        )))
+    } else {
+        log::debug!("No profile found for user {}", auth_user.user_id);
+        Ok(profile_not_found())
     }
 }
 
+/// Completed profile of `user_id`; None without a row or for the signup stub (no primary_skill)
+async fn find_completed_profile(
+    repo: &ProfileSupabaseRepo,
+    user_id: Uuid,
+) -> Result<Option<Personal>, RepoError> {
+    let profile = find_profile(repo, user_id).await?;
+    Ok(profile.filter(|p| !p.primary_skill.trim().is_empty()))
+}
+
+fn profile_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(ApiResponse::error_with_details(
+        "PROFILE_NOT_FOUND",
+        "Profile not found. Please complete your profile first.",
+        serde_json::json!({ "next_step": "complete_profile" }),
+    ))
+}
+
+/// Both skills must be active skills and differ from each other
+fn check_skill_pair(primary_skill: &str, skill_to_learn: &str) -> Result<(), String> {
+    if !is_valid_skill(primary_skill) || !is_valid_skill(skill_to_learn) {
+        return Err(format!(
+            "Invalid skill. Allowed values: {}",
+            get_valid_skills().join(", ")
+        ));
+    }
+
+    if primary_skill == skill_to_learn {
+        return Err("Primary skill and skill to learn cannot be the same.".to_string());
+    }
+
+    Ok(())
+}
+
 /// PUT /api/profile
 /// Update user's profile data
 #[put("/api/profile")]
 pub async fn update_user_profile(
     auth_user: AuthenticatedUser,
     svc: web::Data<AuthService>,
+    repo: web::Data<ProfileSupabaseRepo>,
     body: web::Json<CreatePersonalDTO>,
-) -> impl Responder {
-    println!("=== UPDATE PROFILE DEBUG ===");
-    println!("User ID: {}", auth_user.user_id);
-    println!("Update data: {:?}", body);
+) -> Result<HttpResponse, AppError> {
+    log::debug!("Updating profile of user {}: {:?}", auth_user.user_id, body);
 
     // Validate required fields
     if body.primary_skill.trim().is_empty() {
-        return HttpResponse::BadRequest().json(ApiResponse::<()> {
-            status: "error".to_string(),
-            message: "Primary skill is required".to_string(),
-            data: None,
-        });
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error("Primary skill is required")));
     }
 
     if body.skill_to_learn.trim().is_empty() {
-        return HttpResponse::BadRequest().json(ApiResponse::<()> {
-            status: "error".to_string(),
-            message: "Skill to learn is required".to_string(),
-            data: None,
-        });
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error("Skill to learn is required")));
     }
 
+    // Skill harus dari daftar skill aktif (tabel skills), kalau tidak matching jadi rusak
+    if let Err(message) = check_skill_pair(body.primary_skill.trim(), body.skill_to_learn.trim()) {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error(message)));
+    }
+
+    // Panjang dihitung dalam karakter (bukan byte) supaya emoji/non-ASCII tidak terpotong lebih awal
+    if body.bio.trim().chars().count() > 1000 {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error(
+            "Bio must be less than 1000 characters",
+        )));
+    }
+
+    let full_name = match body.full_name.as_deref().map(validate_full_name).transpose() {
+        Ok(name) => name,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(ApiResponse::error(message))),
+    };
+
     // Validate and convert date format - allow empty dates
-    let iso_date = if body.date_of_birth.trim().is_empty() {
-        "".to_string()
-    } else {
-        // The frontend should already send in YYYY-MM-DD format, but let's be flexible
-        match NaiveDate::parse_from_str(&body.date_of_birth, "%Y-%m-%d")
-            .or_else(|_| NaiveDate::parse_from_str(&body.date_of_birth, "%d/%m/%Y"))
-            .or_else(|_| NaiveDate::parse_from_str(&body.date_of_birth, "%m/%d/%Y"))
-        {
-            Ok(d) => d.format("%Y-%m-%d").to_string(),
-            Err(e) => {
-                println!("Invalid date format received: '{}', error: {}", body.date_of_birth, e);
-                return HttpResponse::BadRequest().json(ApiResponse::<()> {
-                    status: "error".to_string(),
-                    message: format!("Invalid date format: '{}'. Use YYYY-MM-DD", body.date_of_birth),
-                    data: None,
-                });
-            }
+    let iso_date = match normalize_date_of_birth(&body.date_of_birth) {
+        Ok(date) => date,
+        Err(message) => {
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::error(message)));
         }
     };
 
@@ -133,155 +174,532 @@ pub async fn update_user_profile(
         primary_skill: body.primary_skill.trim().to_string(),
         skill_to_learn: body.skill_to_learn.trim().to_string(),
         bio: body.bio.trim().to_string(),
+        is_discoverable: body.is_discoverable,
+        full_name,
     };
 
-    println!("Processed profile DTO: {:?}", profile_dto);
+    log::debug!("Processed profile DTO: {:?}", profile_dto);
 
-    match upsert_profile_data(&svc, auth_user.user_id, profile_dto).await {
-        Ok(updated_profile) => {
-            println!("Profile updated successfully: {:?}", updated_profile);
-            HttpResponse::Ok().json(ApiResponse {
-                status: "success".to_string(),
-                message: "Profile updated successfully".to_string(),
-                data: Some(updated_profile),
-            })
+    // Row sebelum update, untuk riwayat perubahan per field (best-effort)
+    let previous = find_profile(&repo, auth_user.user_id).await.ok().flatten();
+
+    let updated_profile = repo
+        .upsert_profile(auth_user.user_id, profile_dto)
+        .await
+        .context("Failed to update profile")?;
+
+    let updated_profile = personal_out(updated_profile);
+    log::debug!("Profile updated successfully: {:?}", updated_profile);
+    ProfileHistoryRepository::record(
+        &svc.supabase_url,
+        &svc.supabase_service_role_key,
+        &svc.client,
+        profile_field_changes(auth_user.user_id, previous.as_ref(), &updated_profile),
+    );
+    let warnings = profile_warnings(&updated_profile);
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Profile updated successfully",
+        ProfileWriteOut {
+            profile: updated_profile,
+            warnings,
+        },
+    )))
+}
+
+/// PATCH /api/profile
+/// Partial update: only the fields present in the body are written, the rest of the row is untouched
+#[patch("/api/profile")]
+pub async fn patch_user_profile(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+    repo: web::Data<ProfileSupabaseRepo>,
+    body: web::Json<UpdateProfilePatchDTO>,
+) -> Result<HttpResponse, AppError> {
+    let bad_request = |message: String| Err(AppError::Validation(message));
+
+    let previous = find_profile(&repo, auth_user.user_id)
+        .await
+        .context("Failed to update profile")?
+        .ok_or_else(|| AppError::NotFound("Complete your profile first".to_string()))?;
+
+    let mut fields = serde_json::Map::new();
+
+    let primary_skill = body.primary_skill.as_deref().map(str::trim);
+    let skill_to_learn = body.skill_to_learn.as_deref().map(str::trim);
+    for skill in [primary_skill, skill_to_learn].into_iter().flatten() {
+        if !is_valid_skill(skill) {
+            return bad_request(format!(
+                "Invalid skill. Allowed values: {}",
+                get_valid_skills().join(", ")
+            ));
         }
-        Err(e) => {
-            println!("Failed to update profile: {}", e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: format!("Failed to update profile: {}", e),
-                data: None,
-            })
+    }
+
+    // Aturan skill berbeda dicek terhadap hasil akhir, bukan hanya field yang dikirim
+    if primary_skill.is_some() || skill_to_learn.is_some() {
+        let final_primary =
+            primary_skill.or(Some(previous.primary_skill.as_str()).filter(|s| !s.is_empty()));
+        let final_learn =
+            skill_to_learn.or(Some(previous.skill_to_learn.as_str()).filter(|s| !s.is_empty()));
+        if final_primary.is_some() && final_primary == final_learn {
+            return bad_request("Primary skill and skill to learn cannot be the same.".to_string());
         }
     }
-}
 
-// Remove the get_user_auth_info function since we're not using it anymore
+    if let Some(skill) = primary_skill {
+        fields.insert("primary_skill".into(), skill.into());
+    }
+    if let Some(skill) = skill_to_learn {
+        fields.insert("skill_to_learn".into(), skill.into());
+    }
 
-// Helper function to get profile from profiles table
-async fn get_user_profile_data(
-    svc: &AuthService,
-    user_id: uuid::Uuid,
-) -> Result<Option<ProfileDbRecord>, Box<dyn std::error::Error + Send + Sync>> {
-    let url = format!("{}/rest/v1/profiles", svc.supabase_url);
-    
-    println!("Getting profile data from: {}", url);
-    
-    let response = svc.client
-        .get(&url)
-        .header("apikey", &svc.supabase_service_role_key)
-        .header("Authorization", format!("Bearer {}", &svc.supabase_service_role_key))
-        .query(&[
-            ("id", format!("eq.{}", user_id)),
-            ("select", "*".to_string())
-        ])
-        .send()
-        .await?;
-
-    println!("Profile response status: {}", response.status());
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        println!("Failed to get profile: {} - {}", status, error_text);
-        return Err(format!("Failed to get profile: {}", error_text).into());
+    if let Some(bio) = body.bio.as_deref().map(str::trim) {
+        if bio.chars().count() > 1000 {
+            return bad_request("Bio must be less than 1000 characters".to_string());
+        }
+        fields.insert("bio".into(), bio.into());
     }
 
-    let profiles: Vec<serde_json::Value> = response.json().await?;
-    println!("Profile data: {:?}", profiles);
-    
-    if let Some(profile_data) = profiles.first() {
-        Ok(Some(ProfileDbRecord {
-            id: profile_data["id"].as_str().unwrap_or("").to_string(),
-            date_of_birth: profile_data["date_of_birth"].as_str().map(|s| s.to_string()),
-            primary_skill: profile_data["primary_skill"].as_str().map(|s| s.to_string()),
-            skill_to_learn: profile_data["skill_to_learn"].as_str().map(|s| s.to_string()),
-            bio: profile_data["bio"].as_str().map(|s| s.to_string()),
-            profile_picture_url: profile_data["profile_picture_url"].as_str().map(|s| s.to_string()),
-            full_name: profile_data["full_name"].as_str().map(|s| s.to_string()),
-            role: profile_data["role"].as_str().map(|s| s.to_string()),
-        }))
-    } else {
-        Ok(None)
+    if let Some(raw) = body.date_of_birth.as_deref() {
+        let iso_date = match normalize_date_of_birth(raw) {
+            Ok(date) => date,
+            Err(message) => return bad_request(message),
+        };
+        let value = if iso_date.is_empty() {
+            serde_json::Value::Null
+        } else {
+            serde_json::Value::String(iso_date)
+        };
+        fields.insert("date_of_birth".into(), value);
+    }
+
+    if let Some(is_discoverable) = body.is_discoverable {
+        fields.insert("is_discoverable".into(), is_discoverable.into());
     }
+
+    if let Some(raw) = body.full_name.as_deref() {
+        match validate_full_name(raw) {
+            Ok(name) => fields.insert("full_name".into(), name.into()),
+            Err(message) => return bad_request(message),
+        };
+    }
+
+    if fields.is_empty() {
+        return bad_request("No fields to update".to_string());
+    }
+
+    let updated_profile = repo
+        .patch_profile(auth_user.user_id, &fields)
+        .await
+        .context("Failed to update profile")?
+        .map(personal_out)
+        .ok_or_else(|| AppError::NotFound("Complete your profile first".to_string()))?;
+
+    ProfileHistoryRepository::record(
+        &svc.supabase_url,
+        &svc.supabase_service_role_key,
+        &svc.client,
+        profile_field_changes(auth_user.user_id, Some(&previous), &updated_profile),
+    );
+    let warnings = profile_warnings(&updated_profile);
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Profile updated successfully",
+        ProfileWriteOut {
+            profile: updated_profile,
+            warnings,
+        },
+    )))
 }
 
-// Helper function to upsert profile data (insert or update)
-async fn upsert_profile_data(
+/// Date of birth from the FE as YYYY-MM-DD; "" (no date) stays "".
+/// Same formats and age bounds as signup, see `parse_and_validate_dob`.
+fn normalize_date_of_birth(raw: &str) -> Result<String, String> {
+    if raw.trim().is_empty() {
+        return Ok(String::new());
+    }
+
+    parse_and_validate_dob(raw)
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .inspect_err(|e| log::warn!("Rejected date of birth '{}': {}", raw, e))
+}
+
+/// GET /api/profiles/{user_id}
+/// Public profile of any user. Logged-in viewers also get `match_with_me`,
+/// computed with the same score_match used by the matches endpoints.
+/// Also served at /api/profile/{user_id}; date_of_birth is never included.
+#[routes]
+#[get("/api/profiles/{user_id}")]
+#[get("/api/profile/{user_id}")]
+pub async fn get_public_profile(
+    viewer: Option<AuthenticatedUser>,
+    repo: web::Data<ProfileSupabaseRepo>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = path.into_inner();
+
+    let profile = repo
+        .get_public_profile(user_id)
+        .await
+        .context("Failed to retrieve profile")?
+        .ok_or_else(|| AppError::NotFound("Profile not found".to_string()))?;
+
+    // Badge hanya untuk viewer lain yang sudah login
+    let match_with_me = match viewer {
+        Some(viewer) if viewer.user_id != user_id => {
+            match find_profile(&repo, viewer.user_id).await {
+                Ok(Some(me)) if !me.primary_skill.trim().is_empty() => {
+                    Some(match_label(score_match(&me, &profile)))
+                }
+                Ok(_) => Some("none"),
+                Err(e) => {
+                    log::error!("Failed to load viewer profile {}: {}", viewer.user_id, e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Profile retrieved successfully",
+        PublicProfileView {
+            profile,
+            match_with_me,
+        },
+    )))
+}
+
+/// Fields of `after` that differ from the stored row (empty string == not set)
+fn profile_field_changes(
+    user_id: Uuid,
+    before: Option<&Personal>,
+    after: &PersonalDataOut,
+) -> Vec<ProfileFieldChange> {
+    let non_empty = |v: Option<&String>| v.filter(|s| !s.is_empty()).cloned();
+    let old = |f: fn(&Personal) -> &String| non_empty(before.map(f));
+    let old_date = before
+        .and_then(|p| p.date_of_birth)
+        .map(|d| d.format("%Y-%m-%d").to_string());
+
+    [
+        ("date_of_birth", old_date, non_empty(after.date_of_birth.as_ref())),
+        ("primary_skill", old(|p| &p.primary_skill), non_empty(Some(&after.primary_skill))),
+        ("skill_to_learn", old(|p| &p.skill_to_learn), non_empty(Some(&after.skill_to_learn))),
+        ("bio", old(|p| &p.bio), non_empty(Some(&after.bio))),
+        (
+            "full_name",
+            non_empty(before.and_then(|p| p.full_name.as_ref())),
+            non_empty(after.full_name.as_ref()),
+        ),
+        (
+            "is_discoverable",
+            Some(before.and_then(|p| p.is_discoverable).unwrap_or(true).to_string()),
+            Some(after.is_discoverable.to_string()),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(field, old_value, new_value)| {
+        ProfileFieldChange::if_changed(user_id, field, old_value, new_value)
+    })
+    .collect()
+}
+
+/// GET /api/me/profile/history
+/// Recent changes to the caller's profile fields, newest first
+#[get("/api/me/profile/history")]
+pub async fn get_profile_history(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+) -> Result<HttpResponse, AppError> {
+    let changes = ProfileHistoryRepository::list_recent(
+        &svc.supabase_url,
+        &svc.supabase_service_role_key,
+        &svc.client,
+        auth_user.user_id,
+        50,
+    )
+    .await
+    .context("Failed to retrieve profile history")?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Profile history retrieved successfully",
+        changes,
+    )))
+}
+
+/// POST /api/me/skills/swap
+/// Tukar skill yang ditawarkan dengan skill yang ingin dipelajari (mis. setelah skill-nya dikuasai)
+#[post("/api/me/skills/swap")]
+pub async fn swap_skills(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+    repo: web::Data<ProfileSupabaseRepo>,
+) -> Result<HttpResponse, AppError> {
+    let current = find_profile(&repo, auth_user.user_id)
+        .await
+        .context("Failed to swap skills")?
+        .filter(|p| !p.primary_skill.trim().is_empty())
+        .ok_or_else(|| AppError::NotFound("Complete your profile first".to_string()))?;
+
+    if current.primary_skill == current.skill_to_learn {
+        return Err(AppError::Validation(
+            "Primary skill and skill to learn must be different".to_string(),
+        ));
+    }
+
+    let Some(updated) = repo.swap_skills(&current).await.context("Failed to swap skills")? else {
+        return Ok(HttpResponse::Conflict().json(ApiResponse::error(
+            "Your profile changed while swapping skills. Please try again.",
+        )));
+    };
+
+    ProfileHistoryRepository::record(
+        &svc.supabase_url,
+        &svc.supabase_service_role_key,
+        &svc.client,
+        [
+            ("primary_skill", &current.primary_skill, &updated.primary_skill),
+            ("skill_to_learn", &current.skill_to_learn, &updated.skill_to_learn),
+        ]
+        .into_iter()
+        .filter_map(|(field, old, new)| {
+            ProfileFieldChange::if_changed(auth_user.user_id, field, Some(old.clone()), Some(new.clone()))
+        })
+        .collect(),
+    );
+
+    let updated = personal_out(updated);
+    let warnings = profile_warnings(&updated);
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Skills swapped successfully",
+        ProfileWriteOut {
+            profile: updated,
+            warnings,
+        },
+    )))
+}
+
+/// POST /api/me/deactivate
+/// Sembunyikan akun sementara (data tetap tersimpan)
+#[post("/api/me/deactivate")]
+pub async fn deactivate_account(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+) -> Result<HttpResponse, AppError> {
+    set_account_active(&svc, auth_user.user_id, false).await
+}
+
+/// POST /api/me/reactivate
+/// Aktifkan kembali akun yang dinonaktifkan
+#[post("/api/me/reactivate")]
+pub async fn reactivate_account(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+) -> Result<HttpResponse, AppError> {
+    set_account_active(&svc, auth_user.user_id, true).await
+}
+
+async fn set_account_active(
     svc: &AuthService,
-    user_id: uuid::Uuid,
-    profile_dto: CreatePersonalDTO,
-) -> Result<PersonalDataOut, Box<dyn std::error::Error + Send + Sync>> {
-    let url = format!("{}/rest/v1/profiles", svc.supabase_url);
-    
-    // Prepare the upsert data - ensure all fields are present
-    let upsert_data = serde_json::json!({
-        "id": user_id,
-        "date_of_birth": if profile_dto.date_of_birth.is_empty() { 
-            serde_json::Value::Null 
-        } else { 
-            serde_json::Value::String(profile_dto.date_of_birth.clone()) 
+    user_id: Uuid,
+    active: bool,
+) -> Result<HttpResponse, AppError> {
+    let action = if active { "reactivated" } else { "deactivated" };
+
+    let updated = svc
+        .set_account_active(user_id, active)
+        .await
+        .context("Failed to update account status")?;
+    if !updated {
+        return Err(AppError::NotFound("Profile not found".to_string()));
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success_message(format!("Account {}", action))))
+}
+
+/// DELETE /api/account
+/// Permanently delete the caller's account. Body `{ "password": "..." }` re-confirms it.
+///
+/// Data rows go before the auth user, so a failure never leaves data behind without a
+/// login that can retry: post reports, barter requests and connections (their foreign
+/// keys would block the next steps), posts, profile picture files, the profile row, then the
+/// Supabase auth user. If a step fails the response is 500 ACCOUNT_DELETE_INCOMPLETE
+/// naming `failed_step` and `completed_steps`; calling again is safe.
+#[delete("/api/account")]
+pub async fn delete_account(
+    req: HttpRequest,
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+    repo: web::Data<ProfileSupabaseRepo>,
+    upload_locks: web::Data<UserLocks>,
+    body: web::Json<DeleteAccountIn>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = auth_user.user_id;
+
+    if body.password.is_empty() {
+        return Err(AppError::Validation("Password is required".to_string()));
+    }
+
+    // Konfirmasi password: login ulang harus menghasilkan user yang sama
+    let email = match auth_user.email.clone().filter(|e| !e.trim().is_empty()) {
+        Some(email) => email,
+        None => match svc.get_auth_user_email(&auth_user.access_token).await {
+            Ok(Some(email)) => email,
+            Ok(None) | Err(AuthError::InvalidToken) => {
+                return Ok(HttpResponse::Unauthorized().json(ApiResponse::error("Invalid token")));
+            }
+            Err(e) => return Err(e).context("Failed to delete account"),
         },
-        "primary_skill": profile_dto.primary_skill,
-        "skill_to_learn": profile_dto.skill_to_learn,
-        "bio": profile_dto.bio,
-    });
-
-    println!("Upserting profile data: {}", serde_json::to_string_pretty(&upsert_data).unwrap_or_default());
-
-    let response = svc.client
-        .post(&url)
-        .header("apikey", &svc.supabase_service_role_key)
-        .header("Authorization", format!("Bearer {}", &svc.supabase_service_role_key))
-        .header("Content-Type", "application/json")
-        .header("Prefer", "resolution=merge-duplicates,return=representation")
-        .json(&upsert_data)
-        .send()
-        .await?;
-
-    let status = response.status();
-    println!("Upsert response status: {}", status);
-
-    if !status.is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        println!("Upsert failed: {} - {}", status, error_text);
-        
-        // Try to parse error details for better debugging
-        if let Ok(error_json) = serde_json::from_str::<serde_json::Value>(&error_text) {
-            println!("Parsed error: {}", serde_json::to_string_pretty(&error_json).unwrap_or_default());
+    };
+
+    let login = LoginIn {
+        email,
+        password: body.password.clone(),
+    };
+    match svc.login_with_user_id(login).await {
+        Ok((_, confirmed_id)) if confirmed_id == user_id => {}
+        Err(e @ AuthError::Unavailable(_)) => return Err(e.into()),
+        _ => {
+            return Ok(HttpResponse::Unauthorized().json(ApiResponse::error("Password is incorrect")));
         }
-        
-        return Err(format!("Failed to upsert profile: {} - {}", status, error_text).into());
     }
 
-    // Try to get the response as JSON
-    let response_text = response.text().await?;
-    println!("Upsert response body: {}", response_text);
-
-    let updated_profiles: Vec<serde_json::Value> = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse response JSON: {} - Response: {}", e, response_text))?;
-    
-    if let Some(profile_data) = updated_profiles.first() {
-        // Parse the UUID from the response
-        let id_str = profile_data["id"].as_str()
-            .ok_or("Missing id in profile response")?;
-        let parsed_id = uuid::Uuid::parse_str(id_str)
-            .map_err(|e| format!("Invalid UUID format for id: {}", e))?;
-
-        let result = PersonalDataOut {
-            id: parsed_id,
-            user_id: parsed_id, // In profiles table, id is the user_id
-            date_of_birth: profile_data["date_of_birth"].as_str().unwrap_or("").to_string(),
-            primary_skill: profile_data["primary_skill"].as_str().unwrap_or("").to_string(),
-            skill_to_learn: profile_data["skill_to_learn"].as_str().unwrap_or("").to_string(),
-            bio: profile_data["bio"].as_str().unwrap_or("").to_string(),
-            profile_picture_url: profile_data["profile_picture_url"].as_str().map(|s| s.to_string()),
-        };
+    // Jangan balapan dengan upload foto profile yang sedang berjalan
+    let _upload_guard = upload_locks.lock(user_id).await;
 
-        println!("Successfully parsed result: {:?}", result);
-        Ok(result)
-    } else {
-        Err("No profile data returned from upsert".into())
+    let mut completed: Vec<&str> = Vec::new();
+    let failed = |step: &str, completed: &[&str], error: String| {
+        log::warn!("Account deletion of {} failed at {}: {}", user_id, step, error);
+        HttpResponse::InternalServerError().json(ApiResponse::error_with_details(
+            "ACCOUNT_DELETE_INCOMPLETE",
+            format!("Account deletion stopped at '{}'. Please try again.", step),
+            serde_json::json!({ "failed_step": step, "completed_steps": completed }),
+        ))
+    };
+
+    if let Err(e) = ReportRepository::delete_reports_for_user(
+        &svc.supabase_url,
+        &svc.supabase_service_role_key,
+        &svc.client,
+        user_id,
+    )
+    .await
+    {
+        return Ok(failed("reports", &completed, e.to_string()));
+    }
+    completed.push("reports");
+
+    if let Err(e) = BarterRepository::delete_requests_for_user(
+        &svc.supabase_url,
+        &svc.supabase_service_role_key,
+        &svc.client,
+        user_id,
+    )
+    .await
+    {
+        return Ok(failed("barter_requests", &completed, e.to_string()));
+    }
+    completed.push("barter_requests");
+
+    if let Err(e) = ConnectionRepository::delete_for_user(
+        &svc.supabase_url,
+        &svc.supabase_service_role_key,
+        &svc.client,
+        user_id,
+    )
+    .await
+    {
+        return Ok(failed("connections", &completed, e.to_string()));
+    }
+    completed.push("connections");
+
+    if let Err(e) = PostRepository::delete_posts_by_user(
+        &svc.supabase_url,
+        &svc.supabase_service_role_key,
+        &svc.client,
+        user_id,
+    )
+    .await
+    {
+        return Ok(failed("posts", &completed, e.to_string()));
+    }
+    completed.push("posts");
+
+    remove_profile_picture_files(user_id);
+    completed.push("profile_picture");
+
+    if let Err(e) = repo.delete_by_user_id(user_id).await {
+        return Ok(failed("profile", &completed, e.to_string()));
+    }
+    completed.push("profile");
+
+    if let Err(e) = svc.delete_auth_user(user_id).await {
+        return Ok(failed("auth_user", &completed, e.to_string()));
     }
-}
\ No newline at end of file
+
+    AuditLogRepository::record(
+        &svc.supabase_url,
+        &svc.supabase_service_role_key,
+        &svc.client,
+        AuditEntry::from_request(&req, Some(user_id), "account_deleted", None),
+    );
+    log::info!("Deleted account {}", user_id);
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success_message("Account deleted")))
+}
+
+// Remove the get_user_auth_info function since we're not using it anymore
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skill_pair_must_be_active_and_different() {
+        let skills = get_valid_skills();
+        assert!(check_skill_pair(&skills[0], &skills[1]).is_ok());
+
+        let err = check_skill_pair("Underwater Basket Weaving", &skills[1]).unwrap_err();
+        assert!(err.starts_with("Invalid skill. Allowed values:"));
+        assert!(err.contains(&skills[0]));
+
+        assert!(check_skill_pair(&skills[0], "").is_err());
+        assert_eq!(
+            check_skill_pair(&skills[0], &skills[0]).unwrap_err(),
+            "Primary skill and skill to learn cannot be the same."
+        );
+    }
+
+    #[actix_web::test]
+    async fn missing_row_or_signup_stub_is_profile_not_found() {
+        let (stub, completed) = (Uuid::new_v4(), Uuid::new_v4());
+        let url = crate::test_support::mock_supabase(move |req, _| {
+            let row = |id: Uuid, skill: Option<&str>| {
+                serde_json::json!([{ "id": id, "user_id": id, "date_of_birth": null,
+                    "primary_skill": skill, "skill_to_learn": null, "bio": null,
+                    "profile_picture_url": null, "full_name": null, "is_discoverable": null,
+                    "is_active": null, "created_at": null, "updated_at": null }])
+            };
+            let query = req.query_string();
+            if query.contains(&format!("id=eq.{}", stub)) {
+                HttpResponse::Ok().json(row(stub, None))
+            } else if query.contains(&format!("id=eq.{}", completed)) {
+                HttpResponse::Ok().json(row(completed, Some("Music")))
+            } else {
+                HttpResponse::Ok().json(serde_json::json!([]))
+            }
+        });
+        let repo = ProfileSupabaseRepo::new(reqwest::Client::new(), &url, "key");
+
+        assert!(find_completed_profile(&repo, Uuid::new_v4()).await.unwrap().is_none());
+        assert!(find_completed_profile(&repo, stub).await.unwrap().is_none());
+        assert!(find_completed_profile(&repo, completed).await.unwrap().is_some());
+
+        let res = profile_not_found();
+        assert_eq!(res.status(), actix_web::http::StatusCode::NOT_FOUND);
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error_code"], "PROFILE_NOT_FOUND");
+        assert_eq!(body["data"]["next_step"], "complete_profile");
+    }
+}
@@ -1,143 +1,395 @@
 // src/handlers/profile_picture_handlers.rs - FIXED VERSION
-use actix_web::{post, web, HttpResponse, Responder};
+use actix_multipart::Multipart;
+use actix_web::{delete, post, web, HttpResponse, Responder};
+use futures::TryStreamExt;
 use base64::{Engine as _, engine::general_purpose};
 use uuid::Uuid;
-use serde::Serialize;
 use crate::middleware::auth_extractor::AuthenticatedUser;
-use crate::dtos::profile_picture_dtos::{UploadProfilePictureRequest, ProfilePictureResponse, SkipProfilePictureResponse};
+use crate::dtos::profile_picture_dtos::{
+    ClearedProfilePictureResponse, CropBox, UploadProfilePictureRequest, ProfilePictureResponse,
+    SkipProfilePictureResponse,
+};
 use crate::services::auth_services::AuthService;
+use crate::services::images::{
+    center_square, check_base64_size, check_image_dimensions, check_image_size, crop_image,
+    detect_image_mime, extension_for_mime, make_thumbnail, max_image_bytes, normalize_mime, split_data_url,
+};
+use crate::services::user_locks::UserLocks;
+use crate::dtos::response::ApiResponse;
+use crate::error::{AppError, ResultExt};
 use std::path::Path;
 
-#[derive(Serialize)]
-struct ApiResponse<T: serde::Serialize> {
-    status: String,
-    message: String,
-    data: Option<T>,
-}
+const UPLOAD_DIR: &str = "uploads/profile_pictures";
+const ALLOWED_CONTENT_TYPES: [&str; 5] = ["image/jpeg", "image/jpg", "image/png", "image/gif", "image/webp"];
 
 /// POST /api/profile-picture/upload
-/// Upload profile picture (authenticated endpoint)
+/// Upload profile picture (authenticated endpoint), optionally cropped to `crop`
 #[post("/api/profile-picture/upload")]
 pub async fn upload_profile_picture(
     auth_user: AuthenticatedUser,
     svc: web::Data<AuthService>,
+    upload_locks: web::Data<UserLocks>,
     body: web::Json<UploadProfilePictureRequest>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
+    save_profile_picture(auth_user, svc, upload_locks, body.into_inner()).await
+}
+
+/// POST /api/profile-picture/crop
+/// Same as upload, but the `crop` box is required
+#[post("/api/profile-picture/crop")]
+pub async fn crop_profile_picture(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+    upload_locks: web::Data<UserLocks>,
+    body: web::Json<UploadProfilePictureRequest>,
+) -> Result<HttpResponse, AppError> {
+    if body.crop.is_none() {
+        return Err(AppError::Validation("Crop area is required".to_string()));
+    }
+
+    save_profile_picture(auth_user, svc, upload_locks, body.into_inner()).await
+}
+
+/// POST /api/profile-picture/upload-multipart
+/// Same as upload, but as multipart/form-data: a `file` part with the image and
+/// an optional `crop` part holding the crop box as JSON
+#[post("/api/profile-picture/upload-multipart")]
+pub async fn upload_profile_picture_multipart(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+    upload_locks: web::Data<UserLocks>,
+    mut payload: Multipart,
+) -> Result<HttpResponse, AppError> {
+    let bad_request = |message: &str| {
+        HttpResponse::BadRequest().json(ApiResponse::error(message.to_string()))
+    };
+
+    let max_bytes = max_image_bytes();
+    let mut file: Option<(String, Vec<u8>)> = None;
+    let mut crop: Option<CropBox> = None;
+
+    loop {
+        let mut field = match payload.try_next().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => {
+                log::warn!("Invalid multipart upload: {}", e);
+                return Ok(bad_request("Invalid multipart upload"));
+            }
+        };
+
+        let name = field.name().unwrap_or_default().to_string();
+        let content_type = field.content_type().map(|m| m.essence_str().to_string());
+
+        // Baca per chunk dan berhenti begitu melewati batas, tanpa menampung sisa body
+        let limit = if name == "file" { max_bytes } else { 1024 };
+        let mut data = Vec::new();
+        loop {
+            match field.try_next().await {
+                Ok(Some(chunk)) => {
+                    if data.len() + chunk.len() > limit {
+                        if name == "file" {
+                            return Ok(HttpResponse::PayloadTooLarge().json(ApiResponse::error(
+                                check_image_size(data.len() + chunk.len())
+                                    .err()
+                                    .unwrap_or_default(),
+                            )));
+                        }
+                        return Ok(bad_request(&format!("Field '{}' is too large", name)));
+                    }
+                    data.extend_from_slice(&chunk);
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    log::warn!("Invalid multipart upload: {}", e);
+                    return Ok(bad_request("Invalid multipart upload"));
+                }
+            }
+        }
+
+        match name.as_str() {
+            "file" => {
+                let Some(content_type) = content_type else {
+                    return Ok(bad_request("The file part must have a Content-Type"));
+                };
+                file = Some((content_type, data));
+            }
+            "crop" => match serde_json::from_slice::<CropBox>(&data) {
+                Ok(parsed) => crop = Some(parsed),
+                Err(_) => return Ok(bad_request("Invalid crop area")),
+            },
+            _ => {} // field lain diabaikan
+        }
+    }
+
+    let Some((content_type, image_bytes)) = file else {
+        return Ok(bad_request("Missing 'file' part"));
+    };
+
+    log::debug!("Multipart profile picture upload from user {}: {}, {} bytes", auth_user.user_id, content_type, image_bytes.len());
+
+    if !ALLOWED_CONTENT_TYPES.contains(&content_type.as_str()) {
+        return Ok(bad_request("Invalid file type. Only JPEG, PNG, GIF, and WEBP are allowed."));
+    }
+
+    store_profile_picture(auth_user.user_id, svc, upload_locks, &content_type, crop, image_bytes).await
+}
+
+async fn save_profile_picture(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+    upload_locks: web::Data<UserLocks>,
+    body: UploadProfilePictureRequest,
+) -> Result<HttpResponse, AppError> {
     let user_id = auth_user.user_id;
     
-    println!("=== UPLOAD PROFILE PICTURE DEBUG ===");
-    println!("User ID: {}", user_id);
-    println!("Content Type: {}", body.content_type);
-    println!("File Name: {}", body.file_name);
-    println!("Image data length: {}", body.image_data.len());
+    log::debug!(
+        "Profile picture upload from user {}: {} ({}), {} base64 chars",
+        user_id, body.file_name, body.content_type, body.image_data.len()
+    );
 
     // Validate content type
-    let allowed_types = ["image/jpeg", "image/jpg", "image/png", "image/gif", "image/webp"];
-    if !allowed_types.contains(&body.content_type.as_str()) {
-        println!("Invalid content type: {}", body.content_type);
-        return HttpResponse::BadRequest().json(ApiResponse::<()> {
-            status: "error".to_string(),
-            message: "Invalid file type. Only JPEG, PNG, GIF, and WEBP are allowed.".to_string(),
-            data: None,
-        });
+    if !ALLOWED_CONTENT_TYPES.contains(&body.content_type.as_str()) {
+        log::warn!("Invalid content type: {}", body.content_type);
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error(
+            "Invalid file type. Only JPEG, PNG, GIF, and WEBP are allowed.",
+        )));
     }
 
-    // Remove data URL prefix if present (data:image/jpeg;base64,)
-    let base64_data = if body.image_data.contains(',') {
-        let split_data = body.image_data.split(',').nth(1).unwrap_or(&body.image_data);
-        println!("Removed data URL prefix");
-        split_data
-    } else {
-        &body.image_data
+    // Remove data URL prefix if present (data:image/jpeg;base64,) and make sure
+    // the mime it declares matches content_type (e.g. no SVG posing as PNG)
+    let base64_data = match split_data_url(&body.image_data) {
+        Ok((Some(data_url_mime), data)) => {
+            if data_url_mime != normalize_mime(&body.content_type) {
+                log::warn!("Data URL mime {} doesn't match content type {}", data_url_mime, body.content_type);
+                return Ok(HttpResponse::BadRequest().json(ApiResponse::error(
+                    "Image data type does not match the declared content type",
+                )));
+            }
+            log::debug!("Removed data URL prefix");
+            data
+        }
+        Ok((None, data)) => data,
+        Err(msg) => {
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::error(msg)));
+        }
     };
 
+    // Tolak sebelum decode kalau base64-nya saja sudah melebihi batas, supaya buffer tidak dialokasikan
+    if let Err(msg) = check_base64_size(base64_data.len()) {
+        log::warn!("Rejected oversized base64 payload: {} chars", base64_data.len());
+        return Ok(HttpResponse::PayloadTooLarge().json(ApiResponse::error(msg)));
+    }
+
     // Decode base64
     let image_bytes = match general_purpose::STANDARD.decode(base64_data) {
         Ok(bytes) => {
-            println!("Successfully decoded base64, {} bytes", bytes.len());
+            log::debug!("Successfully decoded base64, {} bytes", bytes.len());
             bytes
         },
         Err(e) => {
-            println!("Failed to decode base64: {}", e);
-            return HttpResponse::BadRequest().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: "Invalid base64 image data".to_string(),
-                data: None,
-            });
+            log::warn!("Failed to decode base64: {}", e);
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::error(
+                "Invalid base64 image data",
+            )));
         }
     };
 
-    // Generate unique filename
-    let extension = match body.content_type.as_str() {
-        "image/jpeg" | "image/jpg" => "jpg",
-        "image/png" => "png",
-        "image/gif" => "gif",
-        "image/webp" => "webp",
-        _ => "jpg", // fallback
+    store_profile_picture(user_id, svc, upload_locks, &body.content_type, body.crop, image_bytes).await
+}
+
+/// Shared by the base64 and multipart uploads: size, magic-byte, dimension checks,
+/// optional crop, center crop to a square, thumbnail, files on disk and the profile row
+async fn store_profile_picture(
+    user_id: Uuid,
+    svc: web::Data<AuthService>,
+    upload_locks: web::Data<UserLocks>,
+    content_type: &str,
+    crop: Option<CropBox>,
+    image_bytes: Vec<u8>,
+) -> Result<HttpResponse, AppError> {
+    if let Err(msg) = check_image_size(image_bytes.len()) {
+        log::warn!("Rejected image size: {}", msg);
+        return Ok(HttpResponse::PayloadTooLarge().json(ApiResponse::error(msg)));
+    }
+
+    // Format asli dari magic bytes harus sama dengan content_type yang diklaim client
+    let detected_mime = match detect_image_mime(&image_bytes) {
+        Some(mime) if mime == normalize_mime(content_type) => mime,
+        Some(mime) => {
+            log::warn!("Declared content type {} but data is {}", content_type, mime);
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::error(
+                "Image data does not match the declared content type",
+            )));
+        }
+        None => {
+            log::warn!("Uploaded data is not a recognized image format");
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::error(
+                "Invalid file type. Only JPEG, PNG, GIF, and WEBP are allowed.",
+            )));
+        }
+    };
+
+    // Validate pixel dimensions before anything is written to disk
+    let dimensions = match check_image_dimensions(&image_bytes) {
+        Ok(dimensions) => dimensions,
+        Err(msg) => {
+            log::warn!("Rejected image dimensions: {}", msg);
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::error(msg)));
+        }
     };
+
+    // Crop ke area pilihan user; hasil crop disimpan sebagai avatar utama
+    let (image_bytes, (width, height)) = match crop {
+        Some(crop) => {
+            let cropped = crop_image(&image_bytes, &crop)
+                .and_then(|bytes| check_image_dimensions(&bytes).map(|dims| (bytes, dims)));
+            match cropped {
+                Ok(cropped) => {
+                    log::debug!("Cropped image to {:?}", crop);
+                    cropped
+                }
+                Err(msg) => {
+                    log::warn!("Rejected crop: {}", msg);
+                    return Ok(HttpResponse::BadRequest().json(ApiResponse::error(msg)));
+                }
+            }
+        }
+        None => (image_bytes, dimensions),
+    };
+
+    // Avatar selalu persegi: sisi yang lebih panjang dipotong dari tengah
+    let image_bytes = match center_square(width, height) {
+        Some(square) => match crop_image(&image_bytes, &square) {
+            Ok(bytes) => {
+                log::debug!("Center-cropped {}x{} image to {:?}", width, height, square);
+                bytes
+            }
+            Err(msg) => {
+                log::error!("Failed to square image: {}", msg);
+                return Ok(HttpResponse::BadRequest().json(ApiResponse::error(msg)));
+            }
+        },
+        None => image_bytes,
+    };
+
+    // Thumbnail 128x128 (center-crop) untuk feed dan kartu match
+    let thumbnail_bytes = match make_thumbnail(&image_bytes) {
+        Ok(bytes) => bytes,
+        Err(msg) => {
+            log::error!("Failed to create thumbnail: {}", msg);
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::error(msg)));
+        }
+    };
+
+    // Generate unique filename (ekstensi dari format yang terdeteksi, bukan dari client)
+    let extension = extension_for_mime(detected_mime);
     
+    // Upload dari user yang sama (mis. double-click) antre di sini, supaya file
+    // di disk dan URL di database selalu berasal dari upload yang sama
+    let _upload_guard = upload_locks.lock(user_id).await;
+
     let filename = format!("{}_profile.{}", user_id, extension);
-    println!("Generated filename: {}", filename);
+    log::debug!("Generated filename: {}", filename);
     
     // For development, save to local storage
-    let upload_dir = "uploads/profile_pictures";
+    let upload_dir = UPLOAD_DIR;
     
     // Create directory if it doesn't exist
-    if let Err(e) = std::fs::create_dir_all(upload_dir) {
-        println!("Failed to create upload directory: {}", e);
-        return HttpResponse::InternalServerError().json(ApiResponse::<()> {
-            status: "error".to_string(),
-            message: "Failed to prepare file storage".to_string(),
-            data: None,
-        });
-    }
+    std::fs::create_dir_all(upload_dir).context("Failed to prepare file storage")?;
 
     let file_path = format!("{}/{}", upload_dir, filename);
-    println!("Saving to: {}", file_path);
+    let thumbnail_filename = format!("{}_thumb.jpg", user_id);
+    let thumbnail_path = format!("{}/{}", upload_dir, thumbnail_filename);
+    log::debug!("Saving to: {} (thumbnail: {})", file_path, thumbnail_path);
     
-    // Save file
-    if let Err(e) = std::fs::write(&file_path, &image_bytes) {
-        println!("Failed to save profile picture: {}", e);
-        return HttpResponse::InternalServerError().json(ApiResponse::<()> {
-            status: "error".to_string(),
-            message: "Failed to save profile picture".to_string(),
-            data: None,
-        });
-    }
+    write_file_atomically(&file_path, &image_bytes)
+        .and_then(|_| write_file_atomically(&thumbnail_path, &thumbnail_bytes))
+        .context("Failed to save profile picture")?;
 
-    println!("File saved successfully!");
+    log::debug!("File saved successfully!");
 
     // Generate public URL (adjust this based on your setup)
     let public_url = format!("/api/uploads/profile_pictures/{}", filename);
-    println!("Public URL: {}", public_url);
+    let thumbnail_url = format!("/api/uploads/profile_pictures/{}", thumbnail_filename);
+    log::debug!("Public URL: {}", public_url);
 
     // Update user profile with picture URL
-    println!("Updating database...");
-    match svc.update_profile_picture(user_id, Some(public_url.clone())).await {
-        Ok(_) => {
-            println!("Database updated successfully!");
-            let response = ProfilePictureResponse {
-                profile_picture_url: public_url,
-                message: "Profile picture uploaded successfully!".to_string(),
-            };
+    log::debug!("Updating database...");
+    let updated = svc
+        .update_profile_picture(user_id, Some(public_url.clone()), Some(thumbnail_url.clone()))
+        .await;
+    if updated.is_err() {
+        // Clean up uploaded file if database update fails
+        let _ = std::fs::remove_file(&file_path);
+        let _ = std::fs::remove_file(&thumbnail_path);
+    }
+    updated.context("Failed to save profile picture information")?;
 
-            HttpResponse::Ok().json(ApiResponse {
-                status: "success".to_string(),
-                message: "Profile picture uploaded".to_string(),
-                data: Some(response),
-            })
-        }
-        Err(e) => {
-            println!("Failed to update profile picture in database: {}", e);
-            
-            // Clean up uploaded file if database update fails
-            let _ = std::fs::remove_file(&file_path);
-            
-            HttpResponse::InternalServerError().json(ApiResponse::<()> {
-                status: "error".to_string(),
-                message: "Failed to save profile picture information".to_string(),
-                data: None,
-            })
+    log::debug!("Database updated successfully!");
+    let response = ProfilePictureResponse {
+        profile_picture_url: public_url,
+        thumbnail_url,
+        message: "Profile picture uploaded successfully!".to_string(),
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success("Profile picture uploaded", response)))
+}
+
+/// Write via temp file + rename so serve_profile_picture never reads a half-written image
+fn write_file_atomically(path: &str, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp_path = format!("{}.tmp", path);
+    std::fs::write(&tmp_path, bytes)
+        .and_then(|_| std::fs::rename(&tmp_path, path))
+        .inspect_err(|_| {
+            let _ = std::fs::remove_file(&tmp_path);
+        })
+}
+
+/// DELETE /api/profile-picture
+/// Remove the caller's avatar and thumbnail. Succeeds even if no picture was set.
+#[delete("/api/profile-picture")]
+pub async fn delete_profile_picture(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+    upload_locks: web::Data<UserLocks>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = auth_user.user_id;
+    log::debug!("Deleting profile picture of user {}", user_id);
+
+    // Jangan balapan dengan upload yang sedang berjalan untuk user yang sama
+    let _upload_guard = upload_locks.lock(user_id).await;
+
+    // Kosongkan URL di database dulu, supaya tidak ada URL yang menunjuk ke file yang sudah dihapus
+    svc.update_profile_picture(user_id, None, None)
+        .await
+        .context("Failed to remove profile picture")?;
+
+    remove_profile_picture_files(user_id);
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Profile picture removed",
+        ClearedProfilePictureResponse {
+            profile_picture_url: None,
+            thumbnail_url: None,
+        },
+    )))
+}
+
+/// Delete every stored avatar/thumbnail file of `user_id`. Missing files are fine;
+/// other IO errors are only logged.
+pub fn remove_profile_picture_files(user_id: Uuid) {
+    // Semua kemungkinan file milik user (ekstensi tergantung format upload terakhir)
+    let filenames = ["jpg", "png", "gif", "webp"]
+        .iter()
+        .map(|ext| format!("{}_profile.{}", user_id, ext))
+        .chain(std::iter::once(format!("{}_thumb.jpg", user_id)));
+    for filename in filenames {
+        let file_path = format!("{}/{}", UPLOAD_DIR, filename);
+        match std::fs::remove_file(&file_path) {
+            Ok(_) => log::debug!("Deleted {}", file_path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => log::error!("Failed to delete {}: {}", file_path, e),
         }
     }
 }
@@ -148,17 +400,12 @@ pub async fn upload_profile_picture(
 pub async fn skip_profile_picture(
     _auth_user: AuthenticatedUser,
 ) -> impl Responder {
-    println!("=== SKIP PROFILE PICTURE ===");
     let response = SkipProfilePictureResponse {
         message: "Profile picture skipped. You can add one later from your profile settings.".to_string(),
         next_step: "dashboard".to_string(),
     };
 
-    HttpResponse::Ok().json(ApiResponse {
-        status: "success".to_string(),
-        message: "Profile setup completed".to_string(),
-        data: Some(response),
-    })
+    HttpResponse::Ok().json(ApiResponse::success("Profile setup completed", response))
 }
 
 /// GET /api/uploads/profile_pictures/{filename}
@@ -190,6 +437,7 @@ pub async fn serve_profile_picture(path: web::Path<String>) -> impl Responder {
 
             HttpResponse::Ok()
                 .content_type(content_type)
+                .insert_header(("X-Content-Type-Options", "nosniff"))
                 .body(data)
         }
         Err(_) => {
@@ -199,4 +447,56 @@ pub async fn serve_profile_picture(path: web::Path<String>) -> impl Responder {
             }))
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+    use image::{DynamicImage, ImageFormat, Rgb, RgbImage};
+    use super::*;
+    use crate::test_support::mock_supabase;
+
+    fn solid(value: u8, format: ImageFormat) -> Vec<u8> {
+        let mut out = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(96, 96, Rgb([value; 3])))
+            .write_to(&mut Cursor::new(&mut out), format)
+            .unwrap();
+        out
+    }
+
+    #[actix_web::test]
+    async fn concurrent_uploads_leave_the_files_of_the_last_saved_url() {
+        let user_id = Uuid::new_v4();
+        let urls: Arc<Mutex<Vec<String>>> = Arc::default();
+        let seen = urls.clone();
+        let supabase = mock_supabase(move |req, body| {
+            if req.method() == "PATCH" {
+                let update: serde_json::Value = serde_json::from_slice(body).unwrap();
+                seen.lock().unwrap().push(update["profile_picture_url"].as_str().unwrap().to_string());
+            }
+            HttpResponse::NoContent().finish()
+        });
+        let svc = web::Data::new(AuthService::new(reqwest::Client::new(), supabase, "anon", "service"));
+        let locks = web::Data::new(UserLocks::new());
+        let (white, black) = (solid(255, ImageFormat::Png), solid(0, ImageFormat::Jpeg));
+
+        let (first, second) = futures::join!(
+            store_profile_picture(user_id, svc.clone(), locks.clone(), "image/png", None, white.clone()),
+            store_profile_picture(user_id, svc.clone(), locks.clone(), "image/jpeg", None, black.clone()),
+        );
+        assert!(first.unwrap().status().is_success());
+        assert!(second.unwrap().status().is_success());
+
+        let last_url = urls.lock().unwrap().last().cloned().unwrap();
+        let filename = last_url.rsplit('/').next().unwrap();
+        let (expected, shade) = if filename.ends_with(".png") { (white, 255) } else { (black, 0) };
+        let served = std::fs::read(format!("{}/{}", UPLOAD_DIR, filename)).unwrap();
+        let thumbnail = image::open(format!("{}/{}_thumb.jpg", UPLOAD_DIR, user_id)).unwrap().to_rgb8();
+        remove_profile_picture_files(user_id);
+
+        assert_eq!(served, expected);
+        // Thumbnail berasal dari upload yang sama (JPEG: toleransi kecil)
+        assert!(thumbnail.get_pixel(0, 0)[0].abs_diff(shade) < 16);
+    }
+}
@@ -0,0 +1,75 @@
+// src/handlers/search_handlers.rs
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use crate::AppState;
+use crate::dtos::personal::PublicProfileOut;
+use crate::handlers::post_handlers::{transform_post_with_profile, EnhancedPostOut};
+use crate::middleware::auth_extractor::AuthenticatedUser;
+use crate::repositories::post_repository::PostRepository;
+use crate::repositories::profile_supabase_repo::ProfileSupabaseRepo;
+use crate::repositories::search_filter::clean_search_term;
+use crate::dtos::response::ApiResponse;
+use crate::error::{AppError, ResultExt};
+
+// Batas query dan jumlah hasil per bagian (users / posts)
+const MIN_QUERY_CHARS: usize = 2;
+const MAX_QUERY_CHARS: usize = 100;
+const RESULTS_PER_SECTION: u32 = 10;
+
+#[derive(Serialize)]
+struct SearchResponse {
+    users: Vec<PublicProfileOut>,
+    posts: Vec<EnhancedPostOut>,
+}
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+}
+
+/// GET /api/search?q=
+/// One search box for people (username, name, skills, bio) and post content.
+/// Both searches run concurrently and each returns at most 10 results.
+#[get("/api/search")]
+pub async fn search(
+    app_state: web::Data<AppState>,
+    repo: web::Data<ProfileSupabaseRepo>,
+    user: Option<AuthenticatedUser>,
+    query: web::Query<SearchQuery>,
+) -> Result<HttpResponse, AppError> {
+    // Panjang dicek setelah wildcard dibuang, supaya "%%" tidak jadi pencarian kosong (`**`)
+    let q = clean_search_term(query.q.as_deref().unwrap_or(""));
+    let q_chars = q.chars().count();
+
+    if !(MIN_QUERY_CHARS..=MAX_QUERY_CHARS).contains(&q_chars) {
+        return Err(AppError::Validation(format!(
+            "Search query must be between {} and {} characters",
+            MIN_QUERY_CHARS, MAX_QUERY_CHARS
+        )));
+    }
+
+    let (users, posts) = futures::join!(
+        repo.search_profiles(&q, RESULTS_PER_SECTION),
+        PostRepository::search_posts(
+            &app_state.supabase_url,
+            &app_state.supabase_key,
+            &app_state.http_client,
+            &q,
+            RESULTS_PER_SECTION,
+        )
+    );
+
+    let users = users.context("Failed to search")?;
+    let posts = posts.context("Failed to search")?;
+
+    let current_user_id = user.as_ref().map(|u| u.user_id);
+    let posts = posts
+        .into_iter()
+        .map(|post| transform_post_with_profile(post, current_user_id))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Search results retrieved successfully",
+        SearchResponse { users, posts },
+    )))
+}
@@ -0,0 +1,135 @@
+// src/handlers/skill_handlers.rs
+use std::collections::HashMap;
+use actix_web::{get, web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use crate::middleware::auth_extractor::AuthenticatedUser;
+use crate::dtos::personal::PublicProfileOut;
+use crate::models::personal::is_valid_skill;
+use crate::models::skill_graph::{rank_skill_suggestions, related_skills, SkillSuggestion};
+use crate::repositories::profile_supabase_repo::ProfileSupabaseRepo;
+use crate::services::auth_services::AuthService;
+use crate::dtos::response::ApiResponse;
+use crate::error::{AppError, ResultExt};
+
+#[derive(Serialize)]
+struct SkillUsersResponse {
+    users: Vec<PublicProfileOut>,
+    total: Option<i64>,
+    limit: u32,
+    offset: u32,
+}
+
+#[derive(Serialize)]
+struct RelatedSkillsResponse {
+    skill: String,
+    related: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+struct SkillRecommendationsResponse {
+    current_skills: Vec<String>,
+    suggestions: Vec<SkillSuggestion>,
+}
+
+#[derive(Deserialize)]
+pub struct PageQuery {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// GET /api/skills/{skill}/users
+/// Public profiles of users who can teach `skill` (their primary_skill).
+/// Supports `?limit=` (default 20, max 100) and `?offset=`; the caller is excluded when logged in.
+#[get("/api/skills/{skill}/users")]
+pub async fn list_skill_users(
+    repo: web::Data<ProfileSupabaseRepo>,
+    user: Option<AuthenticatedUser>,
+    path: web::Path<String>,
+    query: web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let skill = path.into_inner();
+
+    if !is_valid_skill(&skill) {
+        return Err(AppError::NotFound(format!("Unknown skill: {}", skill)));
+    }
+
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0);
+    let exclude = user.as_ref().map(|u| u.user_id);
+
+    let (users, total) = repo
+        .list_by_primary_skill(&skill, exclude, limit, offset)
+        .await
+        .context("Failed to retrieve users")?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Users retrieved successfully",
+        SkillUsersResponse {
+            users,
+            total,
+            limit,
+            offset,
+        },
+    )))
+}
+
+/// GET /api/skills/{skill}/related
+/// Skills adjacent to `skill` in the skill graph, for suggesting alternatives
+#[get("/api/skills/{skill}/related")]
+pub async fn get_related_skills(path: web::Path<String>) -> impl Responder {
+    let skill = path.into_inner();
+
+    if !is_valid_skill(&skill) {
+        return HttpResponse::NotFound().json(ApiResponse::error(
+            format!("Unknown skill: {}", skill),
+        ));
+    }
+
+    let related = related_skills(&skill);
+    HttpResponse::Ok().json(ApiResponse::success(
+        "Related skills retrieved successfully",
+        RelatedSkillsResponse { skill, related },
+    ))
+}
+
+
+/// GET /api/me/recommendations/skills
+/// Skills the caller might want to learn: neighbours of their own skills in the
+/// skill graph first, then the skills most users offer. Their current skills are excluded.
+#[get("/api/me/recommendations/skills")]
+pub async fn get_skill_recommendations(
+    auth_user: AuthenticatedUser,
+    svc: web::Data<AuthService>,
+    repo: web::Data<ProfileSupabaseRepo>,
+) -> Result<HttpResponse, AppError> {
+    let me = svc
+        .get_user_profile(auth_user.user_id)
+        .await
+        .context("Failed to retrieve recommendations")?
+        .ok_or_else(|| AppError::NotFound("Complete your profile to get recommendations".to_string()))?;
+
+    // Popularitas = jumlah user lain yang mengajarkan skill itu; gagal = hanya saran dari graph
+    let counts = match repo.count_by_primary_skill(Some(auth_user.user_id)).await {
+        Ok(counts) => counts,
+        Err(e) => {
+            log::error!("Failed to count teachers per skill: {}", e);
+            HashMap::new()
+        }
+    };
+    let teachers: HashMap<&str, i64> =
+        counts.iter().map(|(skill, total)| (skill.as_str(), *total)).collect();
+
+    let current: Vec<&str> = [me.primary_skill.as_str(), me.skill_to_learn.as_str()]
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect();
+    let suggestions = rank_skill_suggestions(&current, &teachers);
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        "Recommendations retrieved successfully",
+        SkillRecommendationsResponse {
+            current_skills: current.into_iter().map(|s| s.to_string()).collect(),
+            suggestions,
+        },
+    )))
+}
\ No newline at end of file
@@ -0,0 +1,62 @@
+// src/logging.rs - setup env_logger: satu objek JSON per baris (default) atau teks biasa
+use std::env;
+use std::io::Write;
+use env_logger::fmt::Formatter;
+use log::kv::{self, Key, VisitSource};
+use log::Record;
+use serde_json::{json, Map, Value};
+use crate::middleware::request_id::RequestId;
+
+/// Init the global logger. Level comes from RUST_LOG (default "info").
+/// LOG_FORMAT=text keeps env_logger's human readable format for local development;
+/// anything else writes JSON lines with ts, level, target, msg, the current
+/// request_id and any structured fields of the record.
+pub fn init() {
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+
+    let text = env::var("LOG_FORMAT")
+        .map(|v| v.trim().eq_ignore_ascii_case("text"))
+        .unwrap_or(false);
+    if !text {
+        builder.format(write_json_line);
+    }
+
+    builder.init();
+}
+
+fn write_json_line(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    let mut line = Map::new();
+    line.insert("ts".into(), json!(buf.timestamp_millis().to_string()));
+    line.insert("level".into(), json!(record.level().as_str()));
+    line.insert("target".into(), json!(record.target()));
+    if let Some(id) = RequestId::current() {
+        line.insert("request_id".into(), json!(id.to_string()));
+    }
+    line.insert("msg".into(), json!(record.args().to_string()));
+
+    // Field terstruktur (`log::info!(key = value; "...")`) jadi key JSON sendiri
+    let _ = record.key_values().visit(&mut JsonFields(&mut line));
+
+    writeln!(buf, "{}", Value::Object(line))
+}
+
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(n) = value.to_u64() {
+            json!(n)
+        } else if let Some(n) = value.to_i64() {
+            json!(n)
+        } else if let Some(n) = value.to_f64() {
+            json!(n)
+        } else if let Some(b) = value.to_bool() {
+            json!(b)
+        } else {
+            json!(value.to_string())
+        };
+        self.0.insert(key.as_str().to_string(), value);
+        Ok(())
+    }
+}
@@ -6,30 +6,78 @@ mod repositories;
 mod models;
 mod middleware;
 mod config;
+mod error;
+mod logging;
+#[cfg(test)]
+mod test_support;
 
 use std::env;
-use actix_web::{App, HttpServer, web, middleware::Logger};
+use actix_web::{App, HttpServer, web, middleware::from_fn};
 use deadpool_postgres::Pool;
 use actix_cors::Cors;
 use reqwest::Client;
 use log::{info, error};
-use crate::handlers::profile_handlers::{get_user_profile, update_user_profile};
+use crate::handlers::profile_handlers::{
+    get_user_profile,
+    update_user_profile,
+    deactivate_account,
+    reactivate_account,
+    get_profile_history,
+    get_public_profile,
+    swap_skills,
+    patch_user_profile,
+    delete_account,
+};
 
 use crate::handlers::auth_handlers::{
     signup, 
     complete_profile, 
     login, 
+    refresh,
+    forgot_password,
+    reset_password,
+    resend_confirmation,
     get_skills, 
     test_supabase, 
-    get_current_profile
+    get_current_profile,
+    get_current_email
 };
 use crate::services::auth_services::AuthService;
+use crate::services::user_locks::UserLocks;
+use crate::services::http_util::build_supabase_client;
+use crate::services::images::{base64_len, max_image_bytes};
 use crate::handlers::profile_picture_handlers::{
     upload_profile_picture,
+    upload_profile_picture_multipart,
+    crop_profile_picture,
+    delete_profile_picture,
     skip_profile_picture, 
     serve_profile_picture,
 };
-use crate::handlers::post_handlers::{create_post, list_posts};
+use crate::handlers::post_handlers::{create_post, list_posts, list_user_posts, get_post, get_post_context, update_post, delete_post, report_post, list_scheduled_posts, list_drafts, publish_draft};
+use crate::handlers::fallback_handlers::{json_payload_error, route_not_found};
+use crate::middleware::request_id::{assign_request_id, X_REQUEST_ID};
+use crate::middleware::rate_limit::{
+    limit_auth_attempts, limit_writes, X_RATELIMIT_LIMIT, X_RATELIMIT_REMAINING, X_RATELIMIT_RESET,
+};
+use crate::handlers::skill_handlers::{list_skill_users, get_related_skills, get_skill_recommendations};
+use crate::handlers::search_handlers::search;
+use crate::handlers::health_handlers::health;
+use crate::handlers::match_handlers::{get_matches, search_matches};
+use crate::handlers::barter_handlers::{
+    create_barter_request,
+    respond_barter_request,
+    list_incoming_barters,
+    list_outgoing_barters,
+    list_connections,
+};
+use crate::handlers::admin_handlers::{
+    import_skills, disable_skill, list_users, suspend_user, unsuspend_user, list_audit_log, list_deleted_posts,
+    list_reports,
+};
+use crate::repositories::profile_supabase_repo::ProfileSupabaseRepo;
+use crate::repositories::post_repository::PostRepository;
+use crate::repositories::skill_repository::SkillRepository;
 
 fn mask_key(k: &str) -> String {
     if k.len() <= 8 { "[REDACTED]".to_string() }
@@ -46,8 +94,8 @@ pub struct AppState {
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    env_logger::init();
     dotenv::dotenv().ok();
+    logging::init();
 
     let supabase_url = env::var("SUPABASE_URL")
         .expect("SUPABASE_URL must be set");
@@ -65,13 +113,20 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
-    let http_client = Client::builder()
-        .user_agent("barterup-be/0.1")
-        .build()
-        .expect("failed to build http client");
+    // Satu client (dan connection pool) dengan timeout SUPABASE_TIMEOUT_MS untuk semua panggilan Supabase
+    let http_client = build_supabase_client();
 
-    let auth_service = AuthService::new_from_env();
+    let supabase_anon_key = env::var("SUPABASE_ANON_KEY").unwrap_or_default();
+    let auth_service = AuthService::new(
+        http_client.clone(),
+        supabase_url.clone(),
+        supabase_anon_key,
+        supabase_key.clone(),
+    );
     let auth_data = web::Data::new(auth_service);
+    let profile_repo = web::Data::new(ProfileSupabaseRepo::new_from_env().with_client(http_client.clone()));
+    // Per-user lock supaya upload foto profile yang bersamaan tidak saling menimpa
+    let upload_locks = web::Data::new(UserLocks::new());
 
     let state = web::Data::new(AppState {
         pg_pool,
@@ -80,24 +135,103 @@ async fn main() -> std::io::Result<()> {
         http_client,
     });
 
+    // Publish scheduled posts in the background (POST_SCHEDULER_INTERVAL_SECS, default 60)
+    let scheduler_interval = env::var("POST_SCHEDULER_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(60);
+    let scheduler_state = state.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(scheduler_interval));
+        loop {
+            interval.tick().await;
+            match PostRepository::publish_due_posts(
+                &scheduler_state.supabase_url,
+                &scheduler_state.supabase_key,
+                &scheduler_state.http_client,
+            ).await {
+                Ok(0) => {}
+                Ok(count) => info!("Published {} scheduled post(s)", count),
+                Err(e) => error!("Failed to publish scheduled posts: {}", e),
+            }
+        }
+    });
+
+    // Skill aktif dari tabel `skills` untuk validasi (SKILL_REFRESH_SECS, default 300).
+    // Tick pertama langsung jalan; sampai tabel terbaca VALID_SKILLS yang dipakai.
+    let skill_refresh_interval = env::var("SKILL_REFRESH_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(300);
+    let skill_state = state.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(skill_refresh_interval));
+        loop {
+            interval.tick().await;
+            if let Err(e) = SkillRepository::refresh_active_skills(
+                &skill_state.supabase_url,
+                &skill_state.supabase_key,
+                &skill_state.http_client,
+            ).await {
+                error!("Failed to refresh skills from the skills table: {}", e);
+            }
+        }
+    });
+
+    // Indeks kandidat match, opsional (MATCH_INDEX_REFRESH_SECS). Tanpa indeks yang
+    // segar /api/matches memakai query langsung ke tabel profiles.
+    if let Some(match_index_interval) = auth_data.match_index.refresh_interval() {
+        let match_svc = auth_data.clone();
+        actix_web::rt::spawn(async move {
+            let mut interval = tokio::time::interval(match_index_interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = match_svc.refresh_match_index().await {
+                    error!("Failed to rebuild the match index: {}", e);
+                }
+            }
+        });
+    }
+
+    // Body JSON harus muat foto profile base64 sebesar IMAGE_MAX_BYTES (+ ruang untuk field lain)
+    let json_limit = (base64_len(max_image_bytes()) + 64 * 1024).max(2 * 1024 * 1024);
+
     let allowed_origins = env::var("ALLOWED_ORIGINS")
         .unwrap_or_else(|_| "http://localhost:3000,http://127.0.0.1:3000".into());
 
     // Get port from environment (Railway sets this)
-    let port = env::var("PORT").unwrap_or_else(|_| "8080".to_string());
+    let port: u16 = match env::var("PORT") {
+        Ok(raw) => match raw.trim().parse() {
+            Ok(port) => port,
+            Err(_) => {
+                error!("PORT must be a number between 0 and 65535, got {:?}", raw);
+                std::process::exit(1);
+            }
+        },
+        Err(_) => 8080,
+    };
     let bind_address = format!("0.0.0.0:{}", port);
     
     info!("Starting server on {}", bind_address);
 
-        HttpServer::new(move || {
+        let server = HttpServer::new(move || {
             let mut cors = Cors::default()
-                .allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
+                .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"])
                 .allowed_headers(vec![
                     "authorization", 
                     "content-type", 
                     "accept",
                     "x-requested-with"
                 ])
+                .expose_headers(vec![
+                    "retry-after",
+                    X_REQUEST_ID,
+                    X_RATELIMIT_LIMIT,
+                    X_RATELIMIT_REMAINING,
+                    X_RATELIMIT_RESET,
+                ])
                 .supports_credentials()
                 .max_age(3600);
 
@@ -106,32 +240,100 @@ async fn main() -> std::io::Result<()> {
             }
 
             App::new()
+                // Throttle post/barter/report writes per user, or per IP when anonymous
+                .wrap(from_fn(limit_writes))
+                // Throttle login/signup per IP (AUTH_RATE_LIMIT per minute), before anything else runs
+                .wrap(from_fn(limit_auth_attempts))
                 .wrap(cors)
-                .wrap(Logger::default())
+                // Request id + one structured log line per request (outermost, so 429/503 are logged too)
+                .wrap(from_fn(assign_request_id))
                 .app_data(state.clone())
                 .app_data(auth_data.clone())
+                .app_data(profile_repo.clone())
+                .app_data(upload_locks.clone())
+                .app_data(
+                    web::JsonConfig::default()
+                        .limit(json_limit)
+                        .error_handler(json_payload_error),
+                )
                 // Auth routes (no /api prefix)
                 .service(signup)
                 .service(complete_profile)
                 .service(login)
+                .service(refresh)               // POST /auth/refresh
+                .service(forgot_password)       // POST /auth/forgot-password
+                .service(reset_password)        // POST /auth/reset-password
+                .service(resend_confirmation)   // POST /auth/resend-confirmation
                 .service(get_skills)
                 .service(test_supabase)
+                .service(health)                // GET /health
+                .service(list_skill_users)      // GET /api/skills/{skill}/users
+                .service(get_related_skills)    // GET /api/skills/{skill}/related
+                .service(get_skill_recommendations) // GET /api/me/recommendations/skills
+                .service(search)                // GET /api/search?q=
+                .service(get_matches)           // GET /api/matches
+                .service(search_matches)        // GET /api/matches/search?skill=
+                // Barter routes
+                .service(create_barter_request) // POST /api/barter/request
+                .service(respond_barter_request) // PUT /api/barter/request/{id}
+                .service(list_incoming_barters) // GET /api/barters/incoming
+                .service(list_outgoing_barters) // GET /api/barters/outgoing
+                .service(list_connections)      // GET /api/me/connections
+                // Admin routes (AdminUser)
+                .service(import_skills)         // POST /api/admin/skills
+                .service(disable_skill)         // DELETE /api/admin/skills/{name}
+                .service(list_users)            // GET /api/admin/users
+                .service(suspend_user)          // PUT /api/admin/users/{id}/suspend
+                .service(unsuspend_user)        // PUT /api/admin/users/{id}/reactivate
+                .service(list_audit_log)        // GET /api/admin/audit?actor_id=&action=
+                .service(list_deleted_posts)    // GET /api/admin/posts/deleted
+                .service(list_reports)          // GET /api/admin/reports
                 // Profile management routes
                 .service(get_user_profile)      // GET /api/profile
                 .service(update_user_profile)   // PUT /api/profile
+                .service(patch_user_profile)    // PATCH /api/profile
+                .service(deactivate_account)    // POST /api/me/deactivate
+                .service(reactivate_account)    // POST /api/me/reactivate
+                .service(delete_account)        // DELETE /api/account
+                .service(swap_skills)           // POST /api/me/skills/swap
+                .service(get_profile_history)   // GET /api/me/profile/history
+                .service(get_public_profile)    // GET /api/profiles/{user_id}, /api/profile/{user_id}
                 // Profile routes
                 .service(upload_profile_picture)
+                .service(upload_profile_picture_multipart) // POST /api/profile-picture/upload-multipart
+                .service(crop_profile_picture)  // POST /api/profile-picture/crop
+                .service(delete_profile_picture) // DELETE /api/profile-picture
                 .service(skip_profile_picture)
                 .service(serve_profile_picture)
-                .service(get_current_profile)
+                .service(get_current_profile)   // GET /api/me/profile (completed profile only)
+                .service(get_current_email)     // GET /api/me/email
                 // Posts routes
                 .service(
                     web::scope("/api")
                         .service(create_post)  // This becomes /api/posts
                         .service(list_posts)   // This becomes /api/posts
+                        .service(get_post)     // GET /api/posts/{id}
+                        .service(get_post_context) // GET /api/posts/{id}/context
+                        .service(update_post)  // PUT /api/posts/{id}
+                        .service(delete_post)  // DELETE /api/posts/{id}
+                        .service(list_user_posts) // GET /api/users/{user_id}/posts
+                        .service(list_scheduled_posts) // GET /api/me/posts/scheduled
+                        .service(list_drafts)  // GET /api/me/drafts
+                        .service(publish_draft) // POST /api/posts/{id}/publish
+                        .service(report_post)  // POST /api/posts/{id}/report
                 )
+                // Unmatched routes (404) and wrong methods (405) keep the JSON envelope
+                .default_service(web::route().to(route_not_found))
         })
-        .bind(&bind_address)?  // FIXED: Proper binding to 0.0.0.0 with dynamic port
-        .run()
-        .await
+        .bind(&bind_address)
+        .map_err(|e| {
+            error!("Failed to bind {}: {}", bind_address, e);
+            e
+        })?;
+
+    for addr in server.addrs() {
+        info!("Listening on {}", addr);
+    }
+
+    server.run().await
 }
\ No newline at end of file
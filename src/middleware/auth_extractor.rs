@@ -1,57 +1,287 @@
-// src/middleware/auth_extractor.rs - SUPER SIMPLE untuk projek sekolah
-use actix_web::{dev::Payload, Error, FromRequest, HttpRequest};
-use actix_web::error::ErrorUnauthorized;
-use futures::future::{ready, Ready};
+// src/middleware/auth_extractor.rs - verifikasi JWT Supabase (HS256)
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use actix_web::{dev::Payload, web, Error, FromRequest, HttpRequest, HttpResponse};
+use actix_web::error::{ErrorForbidden, ErrorInternalServerError, ErrorUnauthorized, InternalError};
+use futures::future::LocalBoxFuture;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use uuid::Uuid;
 use base64::Engine; // Add this import to bring the Engine trait into scope
+use crate::dtos::response::ApiResponse;
+use crate::error::ResultExt;
+use crate::models::user::JwtClaims;
+use crate::services::auth_services::AuthService;
+
+/// Konfigurasi verifikasi token, dibaca sekali dari env
+struct JwtConfig {
+    /// SUPABASE_JWT_SECRET - secret HS256 dari project Supabase
+    secret: Option<String>,
+    /// INSECURE_JWT=1 - izinkan token tanpa verifikasi signature (dev/sekolah saja)
+    insecure: bool,
+}
+
+fn jwt_config() -> &'static JwtConfig {
+    static CONFIG: OnceLock<JwtConfig> = OnceLock::new();
+    CONFIG.get_or_init(|| {
+        let secret = env::var("SUPABASE_JWT_SECRET")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        let insecure = env::var("INSECURE_JWT").map(|v| v.trim() == "1").unwrap_or(false);
+
+        match (&secret, insecure) {
+            (Some(_), _) => log::info!("JWT signature verification enabled (HS256)"),
+            (None, true) => log::warn!("INSECURE_JWT=1: JWT signatures are NOT verified"),
+            (None, false) => log::error!("SUPABASE_JWT_SECRET not set: all authenticated requests will be rejected"),
+        }
+
+        JwtConfig { secret, insecure }
+    })
+}
 
 /// Hasil extractor - user yang sudah terautentikasi
+/// Semua kegagalan yang berasal dari token (header hilang, format salah, `sub` hilang
+/// atau bukan UUID) berhenti di sini sebagai 401, jadi handler selalu menerima UUID valid.
+/// Akun yang di-suspend admin ditolak dengan 403 (ACCOUNT_SUSPENDED).
 pub struct AuthenticatedUser {
     pub user_id: Uuid,
+    /// Klaim `email` dari token (bisa kosong)
+    pub email: Option<String>,
+    /// Token mentah, untuk memanggil endpoint Supabase atas nama user
+    pub access_token: String,
 }
 
 impl FromRequest for AuthenticatedUser {
     type Error = Error;
-    type Future = Ready<Result<AuthenticatedUser, Error>>;
+    type Future = LocalBoxFuture<'static, Result<AuthenticatedUser, Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let auth = authenticate(req);
+        let svc = req.app_data::<web::Data<AuthService>>().cloned();
+
+        Box::pin(async move {
+            let (user, _) = auth?;
+            if let Some(svc) = svc {
+                ensure_not_suspended(&svc, user.user_id).await?;
+            }
+            Ok(user)
+        })
+    }
+}
+
+/// User yang terautentikasi dan ber-role "admin"
+/// 401 kalau token tidak valid, 403 kalau user bukan admin atau di-suspend.
+pub struct AdminUser {
+    pub user_id: Uuid,
+}
+
+impl FromRequest for AdminUser {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<AdminUser, Error>>;
 
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-        // Ambil header Authorization
-        let auth_header = match req.headers().get("Authorization") {
-            Some(header) => match header.to_str() {
-                Ok(h) => h,
-                Err(_) => return ready(Err(ErrorUnauthorized("Invalid header format"))),
-            },
-            None => return ready(Err(ErrorUnauthorized("Missing Authorization header"))),
-        };
-
-        // Cek format Bearer token
-        if !auth_header.starts_with("Bearer ") {
-            return ready(Err(ErrorUnauthorized("Invalid auth header format")));
+        let auth = authenticate(req);
+        let svc = req.app_data::<web::Data<AuthService>>().cloned();
+
+        Box::pin(async move {
+            let (user, trusted_role) = auth?;
+            let admin = AdminUser { user_id: user.user_id };
+
+            let svc = svc.ok_or_else(|| ErrorInternalServerError("AuthService not configured"))?;
+            ensure_not_suspended(&svc, admin.user_id).await?;
+
+            // Klaim admin dari token yang signature-nya terverifikasi: tidak perlu ke DB
+            if trusted_role.as_deref() == Some("admin") {
+                return Ok(admin);
+            }
+
+            // Lewat AppError supaya Supabase yang down tetap jadi 503, bukan 500
+            let role = svc
+                .get_role(admin.user_id)
+                .await
+                .context("Failed to verify admin role")?;
+            match role {
+                Some(role) if role == "admin" => Ok(admin),
+                _ => Err(ErrorForbidden("Admin access required")),
+            }
+        })
+    }
+}
+
+/// Status suspend per user, di-cache sebentar supaya tidak ada query ke DB di setiap request
+struct SuspensionCache {
+    /// SUSPENSION_CACHE_SECS (default 30); 0 mematikan cache
+    ttl: Duration,
+    entries: Mutex<HashMap<Uuid, (bool, Instant)>>,
+}
+
+fn suspension_cache() -> &'static SuspensionCache {
+    static CACHE: OnceLock<SuspensionCache> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        let secs = env::var("SUSPENSION_CACHE_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(30);
+        SuspensionCache {
+            ttl: Duration::from_secs(secs),
+            entries: Mutex::new(HashMap::new()),
         }
+    })
+}
 
-        let token = auth_header.trim_start_matches("Bearer ").trim();
-        
-        println!("=== AUTH DEBUG ===");
-        println!("Token received (first 50 chars): {}", &token[..std::cmp::min(token.len(), 50)]);
+impl SuspensionCache {
+    fn get(&self, user_id: Uuid) -> Option<bool> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(&user_id)
+            .filter(|(_, at)| at.elapsed() < self.ttl)
+            .map(|(suspended, _)| *suspended)
+    }
 
-        // SUPER SIMPLE: Extract user_id from JWT payload tanpa validasi signature
-        // HANYA UNTUK PROJEK SEKOLAH - TIDAK AMAN!
-        match extract_user_id_from_jwt(token) {
-            Ok(user_id) => {
-                println!("Auth successful for user: {}", user_id);
-                ready(Ok(AuthenticatedUser { user_id }))
+    fn insert(&self, user_id: Uuid, suspended: bool) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        // Buang entry kadaluarsa sesekali supaya map tidak tumbuh tanpa batas
+        if entries.len() >= 10_000 {
+            entries.retain(|_, (_, at)| now.duration_since(*at) < self.ttl);
+        }
+        entries.insert(user_id, (suspended, now));
+    }
+}
+
+/// Drop the cached suspension status, so a suspend/reactivate by an admin applies to
+/// the user's next request instead of after SUSPENSION_CACHE_SECS.
+pub fn forget_suspension_status(user_id: Uuid) {
+    suspension_cache()
+        .entries
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&user_id);
+}
+
+/// 403 for a request or login from an account an admin suspended
+pub fn account_suspended_response() -> HttpResponse {
+    HttpResponse::Forbidden().json(ApiResponse::error_with_code(
+        "ACCOUNT_SUSPENDED",
+        "Your account has been suspended. Please contact support.",
+    ))
+}
+
+/// Reject suspended users with 403. When the lookup itself fails the request is let
+/// through (and logged), so a Supabase hiccup doesn't lock everybody out.
+async fn ensure_not_suspended(svc: &AuthService, user_id: Uuid) -> Result<(), Error> {
+    let cache = suspension_cache();
+    let suspended = match cache.get(user_id) {
+        Some(suspended) => suspended,
+        None => match svc.is_suspended(user_id).await {
+            Ok(suspended) => {
+                cache.insert(user_id, suspended);
+                suspended
             }
             Err(e) => {
-                println!("Auth failed: {}", e);
-                ready(Err(ErrorUnauthorized("Invalid token")))
+                log::warn!("Failed to check suspension for user {}: {}", user_id, e);
+                false
             }
+        },
+    };
+
+    if suspended {
+        return Err(InternalError::from_response("Account suspended", account_suspended_response()).into());
+    }
+    Ok(())
+}
+
+/// User id of a valid Bearer token on `req`, None without one. Only the token is
+/// checked (no suspension lookup), so middleware that runs before the extractors,
+/// like the write rate limiter, can key on the user without calling Supabase.
+pub fn token_user_id(req: &HttpRequest) -> Option<Uuid> {
+    if !req.headers().contains_key("Authorization") {
+        return None;
+    }
+    authenticate(req).ok().map(|(user, _)| user.user_id)
+}
+
+/// Validate the Bearer token and build the authenticated user.
+/// The second value is the app role claim (`app_metadata.role`), returned ONLY when
+/// the signature was verified with SUPABASE_JWT_SECRET. With INSECURE_JWT=1 anyone can
+/// forge claims, so it is always None there and role checks must go to the DB.
+/// A trusted admin claim stays valid until the token expires, even if the role is
+/// revoked in the meantime.
+fn authenticate(req: &HttpRequest) -> Result<(AuthenticatedUser, Option<String>), Error> {
+    authenticate_with(req, jwt_config())
+}
+
+fn authenticate_with(req: &HttpRequest, config: &JwtConfig) -> Result<(AuthenticatedUser, Option<String>), Error> {
+    // Ambil header Authorization
+    let auth_header = match req.headers().get("Authorization") {
+        Some(header) => header
+            .to_str()
+            .map_err(|_| ErrorUnauthorized("Invalid header format"))?,
+        None => return Err(ErrorUnauthorized("Missing Authorization header")),
+    };
+
+    // Cek format Bearer token
+    if !auth_header.starts_with("Bearer ") {
+        return Err(ErrorUnauthorized("Invalid auth header format"));
+    }
+
+    let token = auth_header.trim_start_matches("Bearer ").trim();
+
+
+    match verify_and_extract_claims(token, config).and_then(|claims| {
+        Uuid::parse_str(&claims.sub)
+            .map(|user_id| (user_id, claims))
+            .map_err(|e| format!("Invalid UUID: {}", e))
+    }) {
+        Ok((user_id, claims)) => {
+            log::debug!("Auth successful for user: {}", user_id);
+            let trusted_role = if config.secret.is_some() {
+                claims.app_role().map(|r| r.to_string())
+            } else {
+                None
+            };
+            Ok((
+                AuthenticatedUser {
+                    user_id,
+                    email: claims.email,
+                    access_token: token.to_string(),
+                },
+                trusted_role,
+            ))
+        }
+        Err(e) => {
+            log::warn!("Auth failed: {}", e);
+            Err(ErrorUnauthorized("Invalid token"))
         }
     }
 }
 
-// SUPER SIMPLE JWT parser - hanya ambil user ID dari payload
+/// Verify the token's HS256 signature with SUPABASE_JWT_SECRET and return its claims.
+/// Without a secret, the unverified parser is only used when INSECURE_JWT=1.
+fn verify_and_extract_claims(token: &str, config: &JwtConfig) -> Result<JwtClaims, String> {
+    match config.secret {
+        Some(ref secret) => {
+            let mut validation = Validation::new(Algorithm::HS256);
+            validation.set_required_spec_claims(&["exp", "sub"]);
+
+            decode::<JwtClaims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation)
+                .map(|data| data.claims)
+                .map_err(|e| format!("JWT verification failed: {}", e))
+        }
+        // HANYA UNTUK PROJEK SEKOLAH - TIDAK AMAN!
+        None if config.insecure => extract_claims_from_jwt(token),
+        None => Err("JWT secret not configured".to_string()),
+    }
+}
+
+// SUPER SIMPLE JWT parser - hanya ambil claims dari payload
 // TIDAK VALIDASI SIGNATURE - HANYA UNTUK DEVELOPMENT/SEKOLAH!
-fn extract_user_id_from_jwt(token: &str) -> Result<Uuid, String> {
+fn extract_claims_from_jwt(token: &str) -> Result<JwtClaims, String> {
     // JWT format: header.payload.signature
     let parts: Vec<&str> = token.split('.').collect();
     if parts.len() != 3 {
@@ -61,43 +291,130 @@ fn extract_user_id_from_jwt(token: &str) -> Result<Uuid, String> {
     // Decode payload (bagian ke-2) - JWT menggunakan base64url tanpa padding
     let payload = parts[1];
     
-    println!("Raw payload part: {}", payload);
     
     // Gunakan URL_SAFE_NO_PAD dan JANGAN tambahkan padding manual
-    match base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(payload) {
-        Ok(decoded) => {
-            let payload_str = String::from_utf8(decoded).map_err(|e| format!("UTF8 error: {}", e))?;
-            println!("Decoded payload: {}", payload_str);
-            
-            // Parse JSON untuk ambil 'sub' field (user ID)
-            let json: serde_json::Value = serde_json::from_str(&payload_str)
-                .map_err(|e| format!("JSON parse error: {}", e))?;
-            
-            let user_id_str = json["sub"].as_str()
-                .ok_or("Missing 'sub' field in token")?;
-            
-            Uuid::parse_str(user_id_str)
-                .map_err(|e| format!("Invalid UUID: {}", e))
-        }
+    let decoded = match base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(payload) {
+        Ok(decoded) => decoded,
         Err(e) => {
-            println!("Base64 decode failed, trying with standard decoder...");
+            log::debug!("Base64 decode failed, trying with standard decoder...");
             // Fallback: coba dengan standard base64 jika URL_SAFE_NO_PAD gagal
-            match base64::engine::general_purpose::STANDARD.decode(payload) {
-                Ok(decoded) => {
-                    let payload_str = String::from_utf8(decoded).map_err(|e| format!("UTF8 error: {}", e))?;
-                    println!("Decoded payload (standard): {}", payload_str);
-                    
-                    let json: serde_json::Value = serde_json::from_str(&payload_str)
-                        .map_err(|e| format!("JSON parse error: {}", e))?;
-                    
-                    let user_id_str = json["sub"].as_str()
-                        .ok_or("Missing 'sub' field in token")?;
-                    
-                    Uuid::parse_str(user_id_str)
-                        .map_err(|e| format!("Invalid UUID: {}", e))
-                }
-                Err(e2) => Err(format!("Both base64 decoders failed: {} and {}", e, e2))
-            }
+            base64::engine::general_purpose::STANDARD
+                .decode(payload)
+                .map_err(|e2| format!("Both base64 decoders failed: {} and {}", e, e2))?
         }
+    };
+
+    let payload_str = String::from_utf8(decoded).map_err(|e| format!("UTF8 error: {}", e))?;
+
+    // Parse JSON untuk ambil claims ('sub' = user ID, 'email', dll)
+    let json: serde_json::Value = serde_json::from_str(&payload_str)
+        .map_err(|e| format!("JSON parse error: {}", e))?;
+
+    if json["sub"].as_str().is_none() {
+        return Err("Missing 'sub' field in token".to_string());
     }
-}
\ No newline at end of file
+
+    serde_json::from_value(json).map_err(|e| format!("Invalid claims: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+
+    /// Unsigned token (`alg: none` header) with the given payload
+    fn token(payload: serde_json::Value) -> String {
+        let encode = |v: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(v);
+        format!("{}.{}.sig", encode(br#"{"alg":"none"}"#), encode(payload.to_string().as_bytes()))
+    }
+
+    const SECRET: &str = "test-jwt-secret";
+
+    fn verified() -> JwtConfig {
+        JwtConfig { secret: Some(SECRET.to_string()), insecure: false }
+    }
+
+    fn insecure() -> JwtConfig {
+        JwtConfig { secret: None, insecure: true }
+    }
+
+    /// HS256 token signed with `secret`
+    fn signed(payload: serde_json::Value, secret: &str) -> String {
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(Algorithm::HS256),
+            &payload,
+            &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn bearer(token: &str) -> HttpRequest {
+        TestRequest::default()
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_http_request()
+    }
+
+    fn status_of(req: &HttpRequest, config: &JwtConfig) -> StatusCode {
+        match authenticate_with(req, config) {
+            Ok(_) => StatusCode::OK,
+            Err(e) => e.as_response_error().status_code(),
+        }
+    }
+
+    #[test]
+    fn missing_sub_is_rejected_by_the_claims_parser() {
+        let raw = token(serde_json::json!({ "email": "a@x.com", "exp": 9999999999u64 }));
+        assert!(extract_claims_from_jwt(&raw).is_err());
+    }
+
+    #[test]
+    fn verified_token_gives_the_user() {
+        let user_id = Uuid::new_v4();
+        let raw = signed(serde_json::json!({ "sub": user_id, "email": "a@x.com", "exp": 9999999999u64 }), SECRET);
+
+        let (user, role) = authenticate_with(&bearer(&raw), &verified()).unwrap();
+        assert_eq!(user.user_id, user_id);
+        assert_eq!(user.email.as_deref(), Some("a@x.com"));
+        assert_eq!(role, None);
+    }
+
+    #[test]
+    fn missing_header_or_sub_is_401_not_500() {
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(status_of(&req, &verified()), StatusCode::UNAUTHORIZED);
+
+        let payload = serde_json::json!({ "exp": 9999999999u64 });
+        assert_eq!(status_of(&bearer(&signed(payload.clone(), SECRET)), &verified()), StatusCode::UNAUTHORIZED);
+        assert_eq!(status_of(&bearer(&token(payload)), &insecure()), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn non_uuid_sub_is_401() {
+        let payload = serde_json::json!({ "sub": "not-a-uuid", "exp": 9999999999u64 });
+        assert_eq!(status_of(&bearer(&signed(payload.clone(), SECRET)), &verified()), StatusCode::UNAUTHORIZED);
+        assert_eq!(status_of(&bearer(&token(payload)), &insecure()), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn wrong_signature_is_401() {
+        let raw = signed(serde_json::json!({ "sub": Uuid::new_v4(), "exp": 9999999999u64 }), "other-secret");
+        assert_eq!(status_of(&bearer(&raw), &verified()), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn app_role_is_trusted_only_when_verified() {
+        let payload = serde_json::json!({
+            "sub": Uuid::new_v4(),
+            "exp": 9999999999u64,
+            "app_metadata": { "role": "admin" },
+        });
+
+        let (_, role) = authenticate_with(&bearer(&signed(payload.clone(), SECRET)), &verified()).unwrap();
+        assert_eq!(role.as_deref(), Some("admin"));
+
+        // INSECURE_JWT: klaim bisa dipalsukan, role harus dicek ke DB
+        let (_, role) = authenticate_with(&bearer(&token(payload)), &insecure()).unwrap();
+        assert_eq!(role, None);
+    }
+}
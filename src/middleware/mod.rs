@@ -1 +1,4 @@
-pub mod auth_extractor;
\ No newline at end of file
+pub mod auth_extractor;
+pub mod upstream_guard;
+pub mod rate_limit;
+pub mod request_id;
\ No newline at end of file
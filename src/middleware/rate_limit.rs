@@ -0,0 +1,488 @@
+// src/middleware/rate_limit.rs - batasi percobaan login/signup per IP dan write per user
+use std::collections::HashMap;
+use std::env;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue, RETRY_AFTER};
+use actix_web::middleware::Next;
+use actix_web::http::Method;
+use actix_web::{Error, HttpRequest, HttpResponse};
+use uuid::Uuid;
+use crate::dtos::response::ApiResponse;
+use crate::middleware::auth_extractor::token_user_id;
+
+const DEFAULT_AUTH_RATE_LIMIT: u32 = 10;
+const DEFAULT_RESEND_CONFIRMATION_LIMIT: u32 = 1;
+const DEFAULT_POST_WRITE_LIMIT: u32 = 30;
+const DEFAULT_BARTER_WRITE_LIMIT: u32 = 20;
+const DEFAULT_REPORT_WRITE_LIMIT: u32 = 10;
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Routes that create a session or an account, check a password, or send email
+const LIMITED_PATHS: &[&str] = &[
+    "/auth/login",
+    "/auth/signup",
+    "/auth/complete-profile",
+    "/auth/resend-confirmation",
+    "/auth/forgot-password",
+    // DELETE: konfirmasi password dengan login ulang
+    "/api/account",
+];
+
+pub const X_RATELIMIT_LIMIT: &str = "x-ratelimit-limit";
+pub const X_RATELIMIT_REMAINING: &str = "x-ratelimit-remaining";
+pub const X_RATELIMIT_RESET: &str = "x-ratelimit-reset";
+
+/// Outcome of one counted request
+pub struct RateDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// seconds until the current window ends
+    pub reset_secs: u64,
+}
+
+/// Fixed one-minute window per key (the client IP by default), kept in memory (per process)
+pub struct RateLimiter<K = IpAddr> {
+    limit: u32,
+    state: Mutex<LimiterState<K>>,
+}
+
+struct LimiterState<K> {
+    windows: HashMap<K, (u32, Instant)>,
+    last_prune: Instant,
+}
+
+impl<K: Hash + Eq> RateLimiter<K> {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            state: Mutex::new(LimiterState {
+                windows: HashMap::new(),
+                last_prune: Instant::now(),
+            }),
+        }
+    }
+
+    /// Count a request for `key` and tell whether it may go through
+    pub fn check(&self, key: K) -> RateDecision {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: K, now: Instant) -> RateDecision {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Buang window yang sudah lewat supaya map tidak terus membesar
+        if now.duration_since(state.last_prune) >= WINDOW {
+            state.windows.retain(|_, (_, start)| now.duration_since(*start) < WINDOW);
+            state.last_prune = now;
+        }
+
+        let (count, start) = state.windows.entry(key).or_insert((0, now));
+        if now.duration_since(*start) >= WINDOW {
+            *count = 0;
+            *start = now;
+        }
+
+        let allowed = *count < self.limit;
+        if allowed {
+            *count += 1;
+        }
+
+        let elapsed = now.duration_since(*start);
+        RateDecision {
+            allowed,
+            limit: self.limit,
+            remaining: self.limit - *count,
+            reset_secs: WINDOW.saturating_sub(elapsed).as_secs().max(1),
+        }
+    }
+}
+
+/// Requests per minute from env `key`; unset, invalid or 0 gives `default`
+fn limit_from_env(key: &str, default: u32) -> u32 {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(default)
+}
+
+/// Shared limiter for the auth routes (AUTH_RATE_LIMIT requests per IP per minute, default 10)
+pub fn auth_rate_limiter() -> &'static RateLimiter {
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    LIMITER.get_or_init(|| RateLimiter::new(limit_from_env("AUTH_RATE_LIMIT", DEFAULT_AUTH_RATE_LIMIT)))
+}
+
+/// Proxies whose X-Forwarded-For entries are believed (TRUSTED_PROXIES).
+/// Comma-separated IPs, or "*" to trust every socket peer as a single proxy hop (only when
+/// the app is reachable solely through the platform proxy). Default: none, the socket peer
+/// is the client.
+enum TrustedProxies {
+    None,
+    All,
+    List(Vec<IpAddr>),
+}
+
+impl TrustedProxies {
+    fn parse(raw: &str) -> Self {
+        let raw = raw.trim();
+        if raw == "*" {
+            return TrustedProxies::All;
+        }
+        let ips: Vec<IpAddr> = raw.split(',').filter_map(|ip| parse_ip(ip.trim())).collect();
+        if ips.is_empty() { TrustedProxies::None } else { TrustedProxies::List(ips) }
+    }
+
+    /// Whether the socket peer is a proxy whose X-Forwarded-For we read
+    fn trusts(&self, peer: IpAddr) -> bool {
+        match self {
+            TrustedProxies::None => false,
+            TrustedProxies::All => true,
+            TrustedProxies::List(ips) => ips.contains(&peer),
+        }
+    }
+
+    /// Whether an X-Forwarded-For entry is another proxy in the chain. With "*" only the
+    /// peer is a proxy, so the rightmost entry is already the client.
+    fn trusts_hop(&self, hop: IpAddr) -> bool {
+        match self {
+            TrustedProxies::List(ips) => ips.contains(&hop),
+            TrustedProxies::None | TrustedProxies::All => false,
+        }
+    }
+}
+
+fn trusted_proxies() -> &'static TrustedProxies {
+    static PROXIES: OnceLock<TrustedProxies> = OnceLock::new();
+    PROXIES.get_or_init(|| TrustedProxies::parse(&env::var("TRUSTED_PROXIES").unwrap_or_default()))
+}
+
+/// Client IP of a request, for rate limiting and the audit log. X-Forwarded-For is
+/// client-supplied, so it is only read when the socket peer is in TRUSTED_PROXIES.
+/// See [`client_ip_with`].
+pub fn client_ip(req: &HttpRequest) -> Option<IpAddr> {
+    let forwarded_for = req
+        .headers()
+        .get_all("x-forwarded-for")
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(",");
+    client_ip_with(req.peer_addr().map(|addr| addr.ip()), Some(&forwarded_for), trusted_proxies())
+}
+
+/// X-Forwarded-For is walked from the right: each trusted proxy appends the address it
+/// received from, so the first entry that is not one of our proxies is the real client.
+/// Entries left of it were written by the client and are ignored, otherwise a new fake
+/// leftmost entry would give a fresh rate-limit budget on every request.
+fn client_ip_with(peer: Option<IpAddr>, forwarded_for: Option<&str>, trusted: &TrustedProxies) -> Option<IpAddr> {
+    let peer = peer?;
+    if !trusted.trusts(peer) {
+        return Some(peer);
+    }
+
+    let mut client = peer;
+    for hop in forwarded_for.unwrap_or_default().rsplit(',') {
+        // Entry yang rusak: berhenti di hop terakhir yang valid
+        let Some(ip) = parse_ip(hop.trim()) else { break };
+        client = ip;
+        if !trusted.trusts_hop(ip) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+/// Confirmation emails per address per minute (RESEND_CONFIRMATION_LIMIT, default 1), so
+/// resend-confirmation can't be used to flood one inbox from many IPs
+pub fn resend_confirmation_limiter() -> &'static RateLimiter<String> {
+    static LIMITER: OnceLock<RateLimiter<String>> = OnceLock::new();
+    LIMITER.get_or_init(|| {
+        RateLimiter::new(limit_from_env("RESEND_CONFIRMATION_LIMIT", DEFAULT_RESEND_CONFIRMATION_LIMIT))
+    })
+}
+
+/// Write routes with their own per-minute budget
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteRoute {
+    /// create/edit/delete/publish a post (POST_WRITE_LIMIT, default 30)
+    Posts,
+    /// send or answer barter requests (BARTER_WRITE_LIMIT, default 20)
+    Barters,
+    /// report a post (REPORT_WRITE_LIMIT, default 10)
+    Reports,
+}
+
+impl WriteRoute {
+    /// Budget group of a request; None for reads and routes without a write budget
+    pub fn of(method: &Method, path: &str) -> Option<Self> {
+        if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+            return None;
+        }
+        if path.starts_with("/api/posts") {
+            Some(if path.ends_with("/report") { WriteRoute::Reports } else { WriteRoute::Posts })
+        } else if path.starts_with("/api/barter/") || path.starts_with("/api/barters") {
+            Some(WriteRoute::Barters)
+        } else {
+            None
+        }
+    }
+
+    fn limiter(self) -> &'static RateLimiter<RateKey> {
+        static LIMITERS: OnceLock<[RateLimiter<RateKey>; 3]> = OnceLock::new();
+        let limiters = LIMITERS.get_or_init(|| {
+            [
+                RateLimiter::new(limit_from_env("POST_WRITE_LIMIT", DEFAULT_POST_WRITE_LIMIT)),
+                RateLimiter::new(limit_from_env("BARTER_WRITE_LIMIT", DEFAULT_BARTER_WRITE_LIMIT)),
+                RateLimiter::new(limit_from_env("REPORT_WRITE_LIMIT", DEFAULT_REPORT_WRITE_LIMIT)),
+            ]
+        });
+        &limiters[self as usize]
+    }
+}
+
+/// Who a write budget belongs to: the user when the token is valid, else the client IP.
+/// A user behind a shared NAT gets their own budget, and changing IP doesn't reset it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RateKey {
+    User(Uuid),
+    Ip(IpAddr),
+}
+
+/// 429 RATE_LIMITED with Retry-After and the X-RateLimit-* headers
+pub fn rate_limited_response(decision: &RateDecision) -> HttpResponse {
+    let mut response = HttpResponse::TooManyRequests().json(ApiResponse::error_with_code(
+        "RATE_LIMITED",
+        "Too many attempts. Please try again later.",
+    ));
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(decision.reset_secs));
+    insert_rate_headers(response.headers_mut(), decision);
+    response
+}
+
+/// Throttle login/signup per client IP (see `client_ip`). Over the limit the request gets 429 with
+/// Retry-After; every response on these routes carries the X-RateLimit-* headers.
+pub async fn limit_auth_attempts(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    if !LIMITED_PATHS.contains(&req.path()) {
+        return next.call(req).await.map(|res| res.map_into_left_body());
+    }
+
+    // Di belakang proxy (Railway) IP asli ada di X-Forwarded-For, tapi header itu
+    // hanya dipercaya kalau datang dari proxy di TRUSTED_PROXIES
+    let Some(ip) = client_ip(req.request()) else {
+        return next.call(req).await.map(|res| res.map_into_left_body());
+    };
+
+    let decision = auth_rate_limiter().check(ip);
+
+    if !decision.allowed {
+        log::warn!("Rate limit exceeded for {} on {}", ip, req.path());
+        let response = rate_limited_response(&decision);
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    let mut res = next.call(req).await?;
+    insert_rate_headers(res.headers_mut(), &decision);
+    Ok(res.map_into_left_body())
+}
+
+/// Throttle writes per route group (see [`WriteRoute`]), keyed on the authenticated user
+/// and falling back to the client IP for anonymous requests. Same 429 and X-RateLimit-*
+/// headers as the auth limiter.
+pub async fn limit_writes(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let Some(route) = WriteRoute::of(req.method(), req.path()) else {
+        return next.call(req).await.map(|res| res.map_into_left_body());
+    };
+
+    let key = token_user_id(req.request())
+        .map(RateKey::User)
+        .or_else(|| client_ip(req.request()).map(RateKey::Ip));
+
+    let Some(key) = key else {
+        return next.call(req).await.map(|res| res.map_into_left_body());
+    };
+
+    let decision = route.limiter().check(key);
+
+    if !decision.allowed {
+        log::warn!("Write rate limit ({:?}) exceeded for {:?} on {}", route, key, req.path());
+        let response = rate_limited_response(&decision);
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    let mut res = next.call(req).await?;
+    insert_rate_headers(res.headers_mut(), &decision);
+    Ok(res.map_into_left_body())
+}
+
+fn insert_rate_headers(headers: &mut actix_web::http::header::HeaderMap, decision: &RateDecision) {
+    headers.insert(HeaderName::from_static(X_RATELIMIT_LIMIT), HeaderValue::from(decision.limit));
+    headers.insert(HeaderName::from_static(X_RATELIMIT_REMAINING), HeaderValue::from(decision.remaining));
+    headers.insert(HeaderName::from_static(X_RATELIMIT_RESET), HeaderValue::from(decision.reset_secs));
+}
+
+/// "1.2.3.4", "1.2.3.4:5678", "[::1]:5678" atau "::1"
+fn parse_ip(addr: &str) -> Option<IpAddr> {
+    addr.parse::<IpAddr>()
+        .ok()
+        .or_else(|| addr.parse::<std::net::SocketAddr>().ok().map(|a| a.ip()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_down_remaining_and_blocks_over_limit() {
+        let limiter = RateLimiter::new(3);
+        let now = Instant::now();
+
+        let remaining: Vec<u32> = (0..3).map(|_| limiter.check_at("a", now).remaining).collect();
+        assert_eq!(remaining, vec![2, 1, 0]);
+
+        let blocked = limiter.check_at("a", now);
+        assert!(!blocked.allowed);
+        assert_eq!(blocked.remaining, 0);
+        assert_eq!(blocked.limit, 3);
+
+        // Key lain punya budget sendiri
+        assert!(limiter.check_at("b", now).allowed);
+    }
+
+    #[test]
+    fn window_resets_after_a_minute() {
+        let limiter = RateLimiter::new(1);
+        let start = Instant::now();
+
+        assert!(limiter.check_at("a", start).allowed);
+        let blocked = limiter.check_at("a", start + Duration::from_secs(59));
+        assert!(!blocked.allowed);
+        assert_eq!(blocked.reset_secs, 1);
+
+        let reset = limiter.check_at("a", start + WINDOW);
+        assert!(reset.allowed);
+        assert_eq!(reset.remaining, 0);
+        assert_eq!(reset.reset_secs, 60);
+    }
+
+    #[test]
+    fn expired_windows_are_pruned() {
+        let limiter = RateLimiter::new(5);
+        let start = Instant::now();
+        limiter.check_at("a", start);
+        limiter.check_at("b", start);
+
+        limiter.check_at("c", start + WINDOW);
+        let state = limiter.state.lock().unwrap();
+        assert_eq!(state.windows.len(), 1);
+        assert!(state.windows.contains_key("c"));
+    }
+
+    #[test]
+    fn forwarded_header_only_counts_from_trusted_proxy() {
+        let peer: IpAddr = "10.0.0.1".parse().unwrap();
+        let spoofed = Some("203.0.113.9");
+
+        assert_eq!(client_ip_with(Some(peer), spoofed, &TrustedProxies::None), Some(peer));
+        assert_eq!(
+            client_ip_with(Some(peer), spoofed, &TrustedProxies::parse("10.0.0.1, 10.0.0.2")),
+            Some("203.0.113.9".parse().unwrap())
+        );
+        assert_eq!(
+            client_ip_with(Some(peer), spoofed, &TrustedProxies::parse("10.0.0.2")),
+            Some(peer)
+        );
+        assert_eq!(
+            client_ip_with(Some(peer), Some("[::1]:5678"), &TrustedProxies::parse("*")),
+            Some("::1".parse().unwrap())
+        );
+        assert_eq!(client_ip_with(Some(peer), Some("garbage"), &TrustedProxies::All), Some(peer));
+    }
+
+    #[test]
+    fn forwarded_for_is_read_from_the_right() {
+        let peer: IpAddr = "10.0.0.1".parse().unwrap();
+        let proxies = TrustedProxies::parse("10.0.0.1, 10.0.0.2");
+        let client: IpAddr = "198.51.100.7".parse().unwrap();
+
+        // Client menulis entry kiri sendiri; proxy kita menambahkan IP asli di kanan
+        let xff = "203.0.113.9, 198.51.100.7, 10.0.0.2";
+        assert_eq!(client_ip_with(Some(peer), Some(xff), &proxies), Some(client));
+        // "*": hanya peer yang proxy, entry paling kanan adalah client
+        assert_eq!(
+            client_ip_with(Some(peer), Some("203.0.113.9, 198.51.100.7"), &TrustedProxies::All),
+            Some(client)
+        );
+        // Semua hop proxy kita sendiri
+        assert_eq!(client_ip_with(Some(peer), Some("10.0.0.2"), &proxies), Some("10.0.0.2".parse().unwrap()));
+    }
+
+    #[test]
+    fn spoofed_leftmost_entry_does_not_reset_the_budget() {
+        let limiter = RateLimiter::new(3);
+        let proxies = TrustedProxies::parse("10.0.0.1");
+        let now = Instant::now();
+
+        let decisions: Vec<bool> = (0..4)
+            .map(|i| {
+                let xff = format!("203.0.113.{}, 198.51.100.7", i);
+                let ip = client_ip_with(Some("10.0.0.1".parse().unwrap()), Some(&xff), &proxies).unwrap();
+                limiter.check_at(ip, now).allowed
+            })
+            .collect();
+        assert_eq!(decisions, vec![true, true, true, false]);
+    }
+
+    #[test]
+    fn writes_are_grouped_by_route() {
+        let route = |method: Method, path: &str| WriteRoute::of(&method, path);
+        assert_eq!(route(Method::POST, "/api/posts"), Some(WriteRoute::Posts));
+        assert_eq!(route(Method::DELETE, "/api/posts/1"), Some(WriteRoute::Posts));
+        assert_eq!(route(Method::POST, "/api/posts/1/publish"), Some(WriteRoute::Posts));
+        assert_eq!(route(Method::POST, "/api/posts/1/report"), Some(WriteRoute::Reports));
+        assert_eq!(route(Method::PUT, "/api/barter/request/1"), Some(WriteRoute::Barters));
+        assert_eq!(route(Method::GET, "/api/posts"), None);
+        assert_eq!(route(Method::PUT, "/api/profile"), None);
+    }
+
+    #[actix_web::test]
+    async fn anonymous_writes_fall_back_to_the_client_ip() {
+        use actix_web::middleware::from_fn;
+        use actix_web::{test, web, App};
+
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn(limit_writes))
+                .route("/api/posts/{id}/report", web::post().to(HttpResponse::Ok)),
+        )
+        .await;
+        let send = |peer: &str| {
+            test::TestRequest::post()
+                .uri("/api/posts/1/report")
+                .peer_addr(peer.parse().unwrap())
+                .to_request()
+        };
+
+        for _ in 0..DEFAULT_REPORT_WRITE_LIMIT {
+            assert!(test::call_service(&app, send("192.0.2.10:1000")).await.status().is_success());
+        }
+        let blocked = test::call_service(&app, send("192.0.2.10:1000")).await;
+        assert_eq!(blocked.status(), actix_web::http::StatusCode::TOO_MANY_REQUESTS);
+
+        // Budget per IP: IP lain masih boleh
+        assert!(test::call_service(&app, send("192.0.2.11:1000")).await.status().is_success());
+    }
+}
@@ -0,0 +1,92 @@
+// src/middleware/request_id.rs - request id per request + satu log line terstruktur per request
+use std::fmt;
+use std::future::{ready, Ready};
+use std::time::Instant;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use uuid::Uuid;
+
+/// Response header carrying the request id, so a client report can be matched to the logs
+pub const X_REQUEST_ID: &str = "x-request-id";
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: RequestId;
+}
+
+/// Id unik per request (UUID v4), disimpan di request extensions oleh `assign_request_id`.
+/// Bisa dipakai langsung sebagai parameter handler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestId(pub Uuid);
+
+impl RequestId {
+    /// Id of the request being handled on this task, for code that has no `HttpRequest`
+    /// (services, repositories, the log formatter). None outside a request, e.g. in the
+    /// post scheduler or in tasks spawned by a handler.
+    pub fn current() -> Option<RequestId> {
+        CURRENT_REQUEST_ID.try_with(|id| *id).ok()
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromRequest for RequestId {
+    type Error = Error;
+    type Future = Ready<Result<RequestId, Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Ok(request_id(req)))
+    }
+}
+
+/// Request id of `req`. Falls back to a fresh id when the middleware isn't mounted.
+pub fn request_id(req: &HttpRequest) -> RequestId {
+    req.extensions()
+        .get::<RequestId>()
+        .copied()
+        .unwrap_or_else(|| RequestId(Uuid::new_v4()))
+}
+
+/// Give every request a UUID: stored in the request extensions, returned as `X-Request-Id`
+/// and attached to every log line written while the request runs. When the request is
+/// done one line is logged with method, path, status, latency_ms and request_id.
+pub async fn assign_request_id(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let id = RequestId(Uuid::new_v4());
+    req.extensions_mut().insert(id);
+
+    let method = req.method().to_string();
+    let path = req.path().to_string();
+    let started = Instant::now();
+
+    let result = CURRENT_REQUEST_ID.scope(id, next.call(req)).await;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let status = match &result {
+        Ok(res) => res.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+    log::info!(
+        target: "barterup::request",
+        request_id:% = id,
+        method = method.as_str(),
+        path = path.as_str(),
+        status = status.as_u16(),
+        latency_ms = (latency_ms * 100.0).round() / 100.0;
+        "{} {} -> {} ({:.2} ms)", method, path, status.as_u16(), latency_ms
+    );
+
+    let mut res = result?;
+    if let Ok(value) = HeaderValue::from_str(&id.to_string()) {
+        res.headers_mut().insert(HeaderName::from_static(X_REQUEST_ID), value);
+    }
+    Ok(res)
+}
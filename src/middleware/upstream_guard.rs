@@ -0,0 +1,12 @@
+// src/middleware/upstream_guard.rs - response 503 saat Supabase down
+use actix_web::HttpResponse;
+use crate::dtos::response::ApiResponse;
+
+/// 503 UPSTREAM_UNAVAILABLE, rendered by AppError when a Supabase call couldn't connect
+/// or wasn't sent because the circuit breaker is open (see `SendTracked`)
+pub fn upstream_unavailable_response() -> HttpResponse {
+    HttpResponse::ServiceUnavailable().json(ApiResponse::error_with_code(
+        "UPSTREAM_UNAVAILABLE",
+        "Service temporarily unavailable, please try again shortly",
+    ))
+}
@@ -1,3 +1,4 @@
 pub mod personal;
 pub mod user;
-pub mod post;
\ No newline at end of file
+pub mod post;
+pub mod skill_graph;
\ No newline at end of file
@@ -1,10 +1,23 @@
 // src/models/personal.rs - Update validation
 
-use serde::{Serialize, Deserialize};
+use std::sync::RwLock;
+use serde::{Deserialize, Deserializer, Serialize};
 use uuid::Uuid;
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::NaiveDate;
+use crate::dtos::personal::{PersonalDataOut, ProfileWarning};
+
+// Bio di bawah panjang ini lolos validasi, tapi masih terlalu singkat untuk matching yang bagus
+const SHORT_BIO_CHARS: usize = 50;
+
+// Rentang umur yang diizinkan saat signup
+pub const MIN_AGE_YEARS: u32 = 13;
+pub const MAX_AGE_YEARS: u32 = 120;
+
+// Panjang nama tampilan (full_name), dalam karakter
+pub const MAX_FULL_NAME_CHARS: usize = 80;
 
 // Valid skill options (matching your frontend)
+// Hanya fallback: selama tabel `skills` sudah terbaca, daftar skill aktif di sana yang dipakai
 const VALID_SKILLS: &[&str] = &[
     "Music",
     "Art", 
@@ -17,17 +30,39 @@ const VALID_SKILLS: &[&str] = &[
     "Gardening"
 ];
 
+/// Active skill names from the `skills` table (see `set_active_skills`).
+/// None until the table has been read, or when it has no active skill.
+static ACTIVE_SKILLS: RwLock<Option<Vec<String>>> = RwLock::new(None);
+
+/// `null` dari PostgREST jadi nilai default (mis. skill kosong di row stub signup)
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Row of the `profiles` table, as ProfileSupabaseRepo returns it. `id` is the auth
+/// user id; the repo selects it a second time as `user_id`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Personal {
     pub id: Uuid,
     pub user_id: Uuid,
-    pub date_of_birth: NaiveDate,
+    pub date_of_birth: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub primary_skill: String,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub skill_to_learn: String,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub bio: String,
     pub profile_picture_url: Option<String>, // ADDED: URL ke gambar profile    
-    pub created_at: Option<NaiveDateTime>,
-    pub updated_at: Option<NaiveDateTime>,
+    pub full_name: Option<String>,
+    pub is_discoverable: Option<bool>,
+    pub is_active: Option<bool>,
+    /// Timestamp apa adanya dari PostgREST
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,20 +78,16 @@ pub struct NewPersonal {
 impl Personal {
     pub fn validate(&self) -> Result<(), String> {
         // Age validation (13-120 years)
-        let today = chrono::Utc::now().naive_utc().date();
-        let min_date = today - chrono::Duration::days(365 * 120); // 120 years ago
-        let max_date = today - chrono::Duration::days(365 * 13);  // 13 years ago
-
-        if self.date_of_birth < min_date || self.date_of_birth > max_date {
+        if !self.date_of_birth.is_some_and(is_allowed_age) {
             return Err("Age must be between 13-120 years".to_string());
         }
 
         // Skill validation
-        if !VALID_SKILLS.contains(&self.primary_skill.as_str()) {
+        if !is_valid_skill(&self.primary_skill) {
             return Err("Invalid primary skill. Please select from available options.".to_string());
         }
 
-        if !VALID_SKILLS.contains(&self.skill_to_learn.as_str()) {
+        if !is_valid_skill(&self.skill_to_learn) {
             return Err("Invalid skill to learn. Please select from available options.".to_string());
         }
 
@@ -70,11 +101,11 @@ impl Personal {
             return Err("Bio cannot be empty".to_string());
         }
 
-        if self.bio.len() < 10 {
+        if self.bio.chars().count() < 10 {
             return Err("Bio must be at least 10 characters long".to_string());
         }
 
-        if self.bio.len() > 1000 {
+        if self.bio.chars().count() > 1000 {
             return Err("Bio must be less than 1000 characters".to_string());
         }
 
@@ -82,9 +113,7 @@ impl Personal {
     }
 
     pub fn age_years(&self) -> i32 {
-        let today = chrono::Utc::now().naive_utc().date();
-        let age = today.signed_duration_since(self.date_of_birth);
-        (age.num_days() / 365) as i32
+        self.date_of_birth.and_then(age_in_years).map_or(0, |age| age as i32)
     }
     // ADDED: Method untuk update profile picture
     pub fn update_profile_picture(&mut self, picture_url: Option<String>) {
@@ -95,20 +124,16 @@ impl Personal {
 impl NewPersonal {
     pub fn validate(&self) -> Result<(), String> {
         // Age validation
-        let today = chrono::Utc::now().naive_utc().date();
-        let min_date = today - chrono::Duration::days(365 * 120); // 120 years ago
-        let max_date = today - chrono::Duration::days(365 * 13);  // 13 years ago
-
-        if self.date_of_birth < min_date || self.date_of_birth > max_date {
+        if !is_allowed_age(self.date_of_birth) {
             return Err("Invalid date of birth. Age must be between 13-120 years.".to_string());
         }
 
         // Skill validation
-        if !VALID_SKILLS.contains(&self.primary_skill.as_str()) {
+        if !is_valid_skill(&self.primary_skill) {
             return Err("Invalid primary skill. Please select from available options.".to_string());
         }
 
-        if !VALID_SKILLS.contains(&self.skill_to_learn.as_str()) {
+        if !is_valid_skill(&self.skill_to_learn) {
             return Err("Invalid skill to learn. Please select from available options.".to_string());
         }
 
@@ -122,11 +147,11 @@ impl NewPersonal {
             return Err("Bio cannot be empty".to_string());
         }
 
-        if self.bio.len() < 10 {
+        if self.bio.chars().count() < 10 {
             return Err("Bio must be at least 10 characters long".to_string());
         }
 
-        if self.bio.len() > 1000 {
+        if self.bio.chars().count() > 1000 {
             return Err("Bio must be less than 1000 characters".to_string());
         }
 
@@ -134,12 +159,173 @@ impl NewPersonal {
     }
 }
 
+/// Umur dalam tahun penuh (kalender, UTC hari ini); None kalau tanggal lahir di masa depan
+pub fn age_in_years(date_of_birth: NaiveDate) -> Option<u32> {
+    chrono::Utc::now().date_naive().years_since(date_of_birth)
+}
+
+/// Umur dari date_of_birth "YYYY-MM-DD" seperti disimpan di profiles; None kalau kosong atau tidak valid
+pub fn age_from_iso_date(date_of_birth: Option<&str>) -> Option<u32> {
+    let date = NaiveDate::parse_from_str(date_of_birth?.trim(), "%Y-%m-%d").ok()?;
+    age_in_years(date)
+}
+
+/// Umur MIN_AGE_YEARS..=MAX_AGE_YEARS, dipakai validasi signup
+pub fn is_allowed_age(date_of_birth: NaiveDate) -> bool {
+    age_in_years(date_of_birth).is_some_and(|age| (MIN_AGE_YEARS..=MAX_AGE_YEARS).contains(&age))
+}
+
+/// Display name, trimmed; 1..=MAX_FULL_NAME_CHARS characters. The error is the
+/// message for the client.
+pub fn validate_full_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    let chars = name.chars().count();
+    if chars == 0 || chars > MAX_FULL_NAME_CHARS {
+        return Err(format!(
+            "Full name must be between 1 and {} characters",
+            MAX_FULL_NAME_CHARS
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Date of birth as sent by the FE, for signup and profile updates alike.
+/// Accepts YYYY-MM-DD, then DD/MM/YYYY (never MM/DD, so "03/04/2000" is always
+/// 3 April) and requires an allowed age. The error is the message for the client.
+pub fn parse_and_validate_dob(input: &str) -> Result<NaiveDate, String> {
+    let input = input.trim();
+    let date = NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(input, "%d/%m/%Y"))
+        .map_err(|_| format!("Invalid date format: '{}'. Use YYYY-MM-DD or DD/MM/YYYY", input))?;
+
+    if !is_allowed_age(date) {
+        return Err(format!(
+            "Age must be between {} and {} years",
+            MIN_AGE_YEARS, MAX_AGE_YEARS
+        ));
+    }
+    Ok(date)
+}
+
+/// Replace the skill list used for validation with the active rows of the `skills`
+/// table. An empty list falls back to VALID_SKILLS again.
+pub fn set_active_skills(skills: Vec<String>) {
+    let skills = Some(skills).filter(|s| !s.is_empty());
+    *ACTIVE_SKILLS.write().unwrap_or_else(|e| e.into_inner()) = skills;
+}
+
 // Helper function to get valid skills (for API endpoints)
-pub fn get_valid_skills() -> Vec<&'static str> {
-    VALID_SKILLS.to_vec()
+pub fn get_valid_skills() -> Vec<String> {
+    match ACTIVE_SKILLS.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(skills) => skills.clone(),
+        None => VALID_SKILLS.iter().map(|s| s.to_string()).collect(),
+    }
 }
 
 // Helper function to validate skill
 pub fn is_valid_skill(skill: &str) -> bool {
-    VALID_SKILLS.contains(&skill)
-}
\ No newline at end of file
+    match ACTIVE_SKILLS.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(skills) => skills.iter().any(|s| s == skill),
+        None => VALID_SKILLS.contains(&skill),
+    }
+}
+
+fn warning(field: &str, code: &str, message: &str) -> ProfileWarning {
+    ProfileWarning {
+        field: field.to_string(),
+        code: code.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Soft checks on a saved profile. These never block the write; they only
+/// nudge the user towards a profile that matches better.
+pub fn profile_warnings(profile: &PersonalDataOut) -> Vec<ProfileWarning> {
+    let mut warnings = Vec::new();
+
+    if profile.bio.trim().chars().count() < SHORT_BIO_CHARS {
+        warnings.push(warning(
+            "bio",
+            "BIO_SHORT",
+            "Your bio is quite short. Tell others a bit more about what you can teach.",
+        ));
+    }
+
+    if profile.date_of_birth.as_deref().is_none_or(|d| d.trim().is_empty()) {
+        warnings.push(warning(
+            "date_of_birth",
+            "DATE_OF_BIRTH_MISSING",
+            "Add your date of birth so we can suggest better matches.",
+        ));
+    }
+
+    if !profile.is_discoverable {
+        warnings.push(warning(
+            "is_discoverable",
+            "NOT_DISCOVERABLE",
+            "Your profile is hidden, so other members won't find you for a barter.",
+        ));
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    fn new_personal(bio: &str) -> NewPersonal {
+        NewPersonal {
+            user_id: Uuid::new_v4(),
+            date_of_birth: NaiveDate::from_ymd_opt(2000, 4, 3).unwrap(),
+            primary_skill: "Music".to_string(),
+            skill_to_learn: "Cooking".to_string(),
+            bio: bio.to_string(),
+            profile_picture_url: None,
+        }
+    }
+
+    #[test]
+    fn bio_length_counts_characters_not_bytes() {
+        // 1000 emoji = 4000 byte, tapi tetap 1000 karakter
+        assert!(new_personal(&"🎸".repeat(1000)).validate().is_ok());
+        assert!(new_personal(&"🎸".repeat(1001)).validate().is_err());
+        assert!(new_personal("ééééééééé").validate().is_err());
+        assert!(new_personal("éééééééééé").validate().is_ok());
+    }
+
+    #[test]
+    fn full_name_is_trimmed_and_limited_in_characters() {
+        assert_eq!(validate_full_name("  Siti  ").unwrap(), "Siti");
+        assert!(validate_full_name(&"é".repeat(MAX_FULL_NAME_CHARS)).is_ok());
+        assert!(validate_full_name(&"é".repeat(MAX_FULL_NAME_CHARS + 1)).is_err());
+        assert!(validate_full_name("   ").is_err());
+    }
+
+    #[test]
+    fn dob_accepts_iso_and_day_first_dates() {
+        let april_3 = NaiveDate::from_ymd_opt(2000, 4, 3).unwrap();
+        assert_eq!(parse_and_validate_dob("2000-04-03"), Ok(april_3));
+        assert_eq!(parse_and_validate_dob(" 03/04/2000 "), Ok(april_3));
+        // MM/DD tidak pernah dipakai
+        assert!(parse_and_validate_dob("04/13/2000").unwrap_err().starts_with("Invalid date format"));
+    }
+
+    #[test]
+    fn dob_outside_the_allowed_age_is_rejected() {
+        let year = chrono::Utc::now().year() - MIN_AGE_YEARS as i32 + 1;
+        let too_young = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+        let err = parse_and_validate_dob(&too_young.to_string()).unwrap_err();
+        assert!(err.starts_with("Age must be between"), "{}", err);
+        assert!(parse_and_validate_dob("1800-01-01").is_err());
+    }
+
+    #[test]
+    fn age_from_iso_date_ignores_empty_or_invalid_dates() {
+        assert_eq!(age_from_iso_date(None), None);
+        assert_eq!(age_from_iso_date(Some("")), None);
+        assert_eq!(age_from_iso_date(Some("03/04/2000")), None);
+        assert!(age_from_iso_date(Some("2000-04-03")).is_some_and(|age| age >= 26));
+    }
+}
@@ -0,0 +1,100 @@
+// src/models/skill_graph.rs - skill yang saling berdekatan (untuk rekomendasi & pencarian terkait)
+use std::collections::HashMap;
+use serde::Serialize;
+use crate::models::personal::get_valid_skills;
+
+/// Pasangan skill yang dianggap berdekatan. Relasinya dua arah,
+/// jadi cukup ditulis sekali per pasangan.
+const SKILL_EDGES: &[(&str, &str)] = &[
+    ("Art", "Design"),
+    ("Art", "Photography"),
+    ("Art", "Music"),
+    ("Design", "Photography"),
+    ("Design", "Programming"),
+    ("Programming", "Writing"),   // technical writing
+    ("Music", "Writing"),         // songwriting
+    ("Cooking", "Gardening"),
+    ("Cooking", "Fitness"),       // nutrition
+    ("Fitness", "Gardening"),
+];
+
+/// Skills adjacent to `skill`, in declaration order. Unknown skills have none.
+pub fn related_skills(skill: &str) -> Vec<&'static str> {
+    SKILL_EDGES
+        .iter()
+        .filter_map(|&(a, b)| {
+            if a == skill {
+                Some(b)
+            } else if b == skill {
+                Some(a)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Skill yang disarankan untuk dipelajari
+#[derive(Debug, Serialize)]
+pub struct SkillSuggestion {
+    pub skill: String,
+    /// Skill milik user yang berdekatan dengan skill ini di graph
+    pub related_to: Vec<&'static str>,
+    /// Jumlah user yang mengajarkan skill ini (None kalau tidak bisa dihitung)
+    pub teachers: Option<i64>,
+}
+
+/// Every valid skill except `current`, ranked by how many of the user's skills it is
+/// adjacent to, then by how many users teach it, then by name.
+/// `teachers` maps a skill to the number of users offering it (missing = unknown).
+pub fn rank_skill_suggestions(
+    current: &[&str],
+    teachers: &HashMap<&str, i64>,
+) -> Vec<SkillSuggestion> {
+    let mut suggestions: Vec<SkillSuggestion> = get_valid_skills()
+        .into_iter()
+        .filter(|skill| !current.contains(&skill.as_str()))
+        .map(|skill| SkillSuggestion {
+            related_to: related_skills(&skill)
+                .into_iter()
+                .filter(|related| current.contains(related))
+                .collect(),
+            teachers: teachers.get(skill.as_str()).copied(),
+            skill,
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| {
+        b.related_to
+            .len()
+            .cmp(&a.related_to.len())
+            .then_with(|| b.teachers.unwrap_or(0).cmp(&a.teachers.unwrap_or(0)))
+            .then_with(|| a.skill.cmp(&b.skill))
+    });
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::personal::is_valid_skill;
+
+    #[test]
+    fn edges_are_two_way() {
+        assert!(related_skills("Art").contains(&"Design"));
+        assert!(related_skills("Design").contains(&"Art"));
+    }
+
+    #[test]
+    fn unknown_skill_has_no_related_skills() {
+        assert!(related_skills("Juggling").is_empty());
+    }
+
+    #[test]
+    fn every_edge_uses_a_known_skill() {
+        for (a, b) in SKILL_EDGES {
+            assert!(is_valid_skill(a), "{} is not a valid skill", a);
+            assert!(is_valid_skill(b), "{} is not a valid skill", b);
+        }
+    }
+}
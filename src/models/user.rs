@@ -45,4 +45,15 @@ pub struct JwtClaims {
     pub iat: Option<u64>,
     pub role: Option<String>,    // kadang Supabase menyertakan role di klaim
     pub email: Option<String>,
+    /// Metadata yang hanya bisa diubah lewat service role (mis. `{"role": "admin"}`)
+    #[serde(default)]
+    pub app_metadata: Option<serde_json::Value>,
+}
+
+impl JwtClaims {
+    /// App-level role from `app_metadata.role`. The top-level `role` claim is the
+    /// Postgres role PostgREST runs as ("authenticated"), not the app role.
+    pub fn app_role(&self) -> Option<&str> {
+        self.app_metadata.as_ref()?.get("role")?.as_str()
+    }
 }
@@ -0,0 +1,155 @@
+// src/repositories/audit_log_repository.rs - audit trail untuk operasi auth yang sensitif
+
+use actix_web::HttpRequest;
+use chrono::{NaiveDateTime, Utc};
+use reqwest::Client;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::dtos::audit_dtos::AuditLogOut;
+use crate::middleware::rate_limit::client_ip;
+use crate::repositories::pagination::{clamp_limit, parse_total_count};
+use crate::services::http_util::SendTracked;
+
+pub struct AuditLogRepository;
+
+/// Satu baris tabel `audit_log`
+#[derive(Serialize, Debug)]
+pub struct AuditEntry {
+    pub actor_id: Option<Uuid>,
+    pub action: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub timestamp: NaiveDateTime,
+    pub detail: Option<serde_json::Value>,
+}
+
+impl AuditEntry {
+    /// Build an entry, capturing IP and user-agent from the request
+    pub fn from_request(
+        req: &HttpRequest,
+        actor_id: Option<Uuid>,
+        action: &str,
+        detail: Option<serde_json::Value>,
+    ) -> Self {
+        let ip = client_ip(req).map(|ip| ip.to_string());
+        let user_agent = req
+            .headers()
+            .get("User-Agent")
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+
+        Self {
+            actor_id,
+            action: action.to_string(),
+            ip,
+            user_agent,
+            timestamp: Utc::now().naive_utc(),
+            detail,
+        }
+    }
+}
+
+impl AuditLogRepository {
+    /// Insert an audit row
+    pub async fn insert(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        entry: &AuditEntry,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}/rest/v1/audit_log", supabase_url.trim_end_matches('/'));
+
+        let response = client
+            .post(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=minimal")
+            .json(entry)
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Failed to write audit log: {} - {}", status, body).into());
+        }
+
+        Ok(())
+    }
+
+    /// Audit rows newest first, optionally only those of one actor and/or one action,
+    /// plus the total count (None if PostgREST didn't report it)
+    pub async fn list_entries(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        actor_id: Option<Uuid>,
+        action: Option<&str>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<(Vec<AuditLogOut>, Option<i64>), Box<dyn std::error::Error>> {
+        let limit = clamp_limit(limit, "AuditLogRepository::list_entries");
+        let mut url = format!(
+            "{}/rest/v1/audit_log?select=*&order=timestamp.desc&limit={}&offset={}",
+            supabase_url.trim_end_matches('/'),
+            limit,
+            offset
+        );
+        if let Some(actor_id) = actor_id {
+            url.push_str(&format!("&actor_id=eq.{}", actor_id));
+        }
+        if let Some(action) = action {
+            url.push_str(&format!("&action=eq.{}", urlencoding::encode(action)));
+        }
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Prefer", "count=exact")
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let total = parse_total_count(response.headers());
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch audit log: {} - {}", status, body).into());
+        }
+
+        Ok((serde_json::from_str(&body)?, total))
+    }
+
+    /// Best-effort write in the background: never delays or fails the caller,
+    /// errors are only logged.
+    pub fn record(supabase_url: &str, service_key: &str, client: &Client, entry: AuditEntry) {
+        let supabase_url = supabase_url.to_string();
+        let service_key = service_key.to_string();
+        let client = client.clone();
+
+        actix_web::rt::spawn(async move {
+            if let Err(e) = Self::insert(&supabase_url, &service_key, &client, &entry).await {
+                log::error!("Audit log write failed for action {}: {}", entry.action, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+    use super::*;
+
+    #[test]
+    fn audit_ip_ignores_a_forwarded_header_from_an_untrusted_peer() {
+        let req = TestRequest::default()
+            .peer_addr("192.0.2.10:4000".parse().unwrap())
+            .insert_header(("X-Forwarded-For", "203.0.113.9"))
+            .to_http_request();
+
+        let entry = AuditEntry::from_request(&req, None, "login", None);
+        assert_eq!(entry.ip.as_deref(), Some("192.0.2.10"));
+    }
+}
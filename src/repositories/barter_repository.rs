@@ -0,0 +1,362 @@
+// src/repositories/barter_repository.rs - tabel `barter_requests` via Supabase (PostgREST)
+
+use reqwest::Client;
+use serde_json::json;
+use uuid::Uuid;
+use crate::dtos::barter_dtos::{BarterRequestOut, CreateBarterRequestDTO};
+use crate::repositories::pagination::clamp_limit;
+use crate::services::http_util::{
+    postgrest_error_code, SendTracked, FOREIGN_KEY_VIOLATION, UNIQUE_VIOLATION,
+};
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_ACCEPTED: &str = "accepted";
+pub const STATUS_REJECTED: &str = "rejected";
+
+pub struct BarterRepository;
+
+/// Hasil create_request
+#[derive(Debug)]
+pub enum CreateOutcome {
+    Created(BarterRequestOut),
+    /// The one-pending-per-pair unique index was hit: a concurrent request for the same
+    /// pair won; look it up with `find_pending_between`
+    DuplicatePending,
+    /// recipient_id doesn't reference an existing user
+    UnknownRecipient,
+}
+
+/// Hasil update_request_status
+#[derive(Debug)]
+pub enum StatusUpdate {
+    Updated(BarterRequestOut),
+    NotFound,
+    /// User bukan recipient dari request ini
+    Forbidden,
+    /// Request sudah tidak pending (berisi status saat ini)
+    NotPending(String),
+}
+
+impl BarterRepository {
+    /// Insert a new pending request from `requester_id`.
+    /// The unique index behind `CreateOutcome::DuplicatePending` (both directions count as the same pair):
+    ///
+    /// ```sql
+    /// create unique index barter_requests_one_pending_per_pair on barter_requests
+    ///     (least(requester_id, recipient_id), greatest(requester_id, recipient_id))
+    ///     where status = 'pending';
+    /// ```
+    pub async fn create_request(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        requester_id: Uuid,
+        request: &CreateBarterRequestDTO,
+    ) -> Result<CreateOutcome, Box<dyn std::error::Error>> {
+        let url = format!("{}/rest/v1/barter_requests", supabase_url);
+
+        let payload = json!({
+            "requester_id": requester_id,
+            "recipient_id": request.recipient_id,
+            "offered_skill": request.offered_skill,
+            "requested_skill": request.requested_skill,
+            "status": STATUS_PENDING
+        });
+
+        log::debug!("Creating barter request with payload: {}", payload);
+
+        let response = client
+            .post(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&payload)
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        // PostgREST menjawab 409 untuk unique violation (23505) dan foreign key violation (23503)
+        if status == reqwest::StatusCode::CONFLICT {
+            match postgrest_error_code(&body).as_deref() {
+                Some(UNIQUE_VIOLATION) => {
+                    log::debug!("Pending barter request already exists for this pair: {}", body);
+                    return Ok(CreateOutcome::DuplicatePending);
+                }
+                Some(FOREIGN_KEY_VIOLATION) => return Ok(CreateOutcome::UnknownRecipient),
+                _ => {}
+            }
+        }
+
+        if !status.is_success() {
+            return Err(format!("Failed to create barter request: {} - {}", status, body).into());
+        }
+
+        let requests: Vec<BarterRequestOut> = serde_json::from_str(&body)?;
+        requests.into_iter().next()
+            .map(CreateOutcome::Created)
+            .ok_or_else(|| "No barter request returned from creation".into())
+    }
+
+    /// Pending request between the two users, in either direction
+    pub async fn find_pending_between(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        user_a: Uuid,
+        user_b: Uuid,
+    ) -> Result<Option<BarterRequestOut>, Box<dyn std::error::Error>> {
+        let url = format!(
+            "{}/rest/v1/barter_requests?status=eq.{}&or=(and(requester_id.eq.{a},recipient_id.eq.{b}),and(requester_id.eq.{b},recipient_id.eq.{a}))&limit=1",
+            supabase_url, STATUS_PENDING, a = user_a, b = user_b
+        );
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch barter requests: {} - {}", status, body).into());
+        }
+
+        let requests: Vec<BarterRequestOut> = serde_json::from_str(&body)?;
+        Ok(requests.into_iter().next())
+    }
+
+    /// Newest requests where the user is the recipient (`incoming`) or the requester
+    pub async fn list_for_user(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        user_id: Uuid,
+        incoming: bool,
+        limit: u32,
+    ) -> Result<Vec<BarterRequestOut>, Box<dyn std::error::Error>> {
+        let limit = clamp_limit(limit, "BarterRepository::list_for_user");
+        let column = if incoming { "recipient_id" } else { "requester_id" };
+        let url = format!(
+            "{}/rest/v1/barter_requests?{}=eq.{}&order=created_at.desc&limit={}",
+            supabase_url, column, user_id, limit
+        );
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to list barter requests: {} - {}", status, body).into());
+        }
+
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub async fn get_request(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        request_id: Uuid,
+    ) -> Result<Option<BarterRequestOut>, Box<dyn std::error::Error>> {
+        let url = format!("{}/rest/v1/barter_requests?id=eq.{}", supabase_url, request_id);
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch barter request: {} - {}", status, body).into());
+        }
+
+        let requests: Vec<BarterRequestOut> = serde_json::from_str(&body)?;
+        Ok(requests.into_iter().next())
+    }
+
+    /// Set a pending request to `new_status`, but only when `user_id` is its recipient.
+    /// The PATCH is also filtered on status=pending, so two concurrent answers
+    /// can't both win.
+    pub async fn update_request_status(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        request_id: Uuid,
+        user_id: Uuid,
+        new_status: &str,
+    ) -> Result<StatusUpdate, Box<dyn std::error::Error>> {
+        let Some(current) = Self::get_request(supabase_url, service_key, client, request_id).await? else {
+            return Ok(StatusUpdate::NotFound);
+        };
+
+        if current.recipient_id != user_id {
+            return Ok(StatusUpdate::Forbidden);
+        }
+
+        if current.status != STATUS_PENDING {
+            return Ok(StatusUpdate::NotPending(current.status));
+        }
+
+        let url = format!(
+            "{}/rest/v1/barter_requests?id=eq.{}&recipient_id=eq.{}&status=eq.{}",
+            supabase_url, request_id, user_id, STATUS_PENDING
+        );
+
+        let response = client
+            .patch(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&json!({ "status": new_status }))
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to update barter request: {} - {}", status, body).into());
+        }
+
+        let requests: Vec<BarterRequestOut> = serde_json::from_str(&body)?;
+        Ok(match requests.into_iter().next() {
+            Some(updated) => StatusUpdate::Updated(updated),
+            // Dijawab oleh request lain di antara GET dan PATCH
+            None => StatusUpdate::NotPending("answered".to_string()),
+        })
+    }
+
+    /// Delete every request the user sent or received (account deletion).
+    /// Returns how many rows were removed.
+    pub async fn delete_requests_for_user(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let url = format!(
+            "{}/rest/v1/barter_requests?or=(requester_id.eq.{u},recipient_id.eq.{u})&select=id",
+            supabase_url, u = user_id
+        );
+
+        let response = client
+            .delete(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Prefer", "return=representation")
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to delete barter requests: {} - {}", status, body).into());
+        }
+
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&body)?;
+        Ok(rows.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use actix_web::HttpResponse;
+    use super::*;
+    use crate::test_support::mock_supabase;
+
+    /// `barter_requests` with the one-pending-per-pair unique index and a foreign key
+    /// on recipient_id (only `known_users` exist)
+    fn barter_table(known_users: Vec<Uuid>) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        let rows: Arc<Mutex<Vec<serde_json::Value>>> = Arc::new(Mutex::new(Vec::new()));
+        let table = rows.clone();
+        let url = mock_supabase(move |req, body| {
+            assert_eq!(req.method(), "POST");
+            let mut row: serde_json::Value = serde_json::from_slice(body).unwrap();
+            let recipient: Uuid = serde_json::from_value(row["recipient_id"].clone()).unwrap();
+            if !known_users.contains(&recipient) {
+                return HttpResponse::Conflict().json(json!({ "code": "23503", "message": "fk" }));
+            }
+
+            let mut rows = table.lock().unwrap();
+            let same_pair = |r: &serde_json::Value| {
+                let pair = [&r["requester_id"], &r["recipient_id"]];
+                pair.contains(&&row["requester_id"]) && pair.contains(&&row["recipient_id"])
+            };
+            if rows.iter().any(|r| r["status"] == STATUS_PENDING && same_pair(r)) {
+                return HttpResponse::Conflict().json(json!({ "code": "23505", "message": "duplicate" }));
+            }
+            row["id"] = json!(Uuid::new_v4());
+            row["created_at"] = json!("2026-01-01T00:00:00Z");
+            rows.push(row.clone());
+            HttpResponse::Created().json(vec![row])
+        });
+        (url, rows)
+    }
+
+    fn request_to(recipient_id: Uuid) -> CreateBarterRequestDTO {
+        CreateBarterRequestDTO {
+            recipient_id,
+            offered_skill: "Music".to_string(),
+            requested_skill: "Cooking".to_string(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn concurrent_identical_requests_leave_one_pending_row() {
+        let (requester, recipient) = (Uuid::new_v4(), Uuid::new_v4());
+        let (url, rows) = barter_table(vec![requester, recipient]);
+        let client = Client::new();
+        let request = request_to(recipient);
+
+        let (first, second) = futures::join!(
+            BarterRepository::create_request(&url, "key", &client, requester, &request),
+            BarterRepository::create_request(&url, "key", &client, requester, &request),
+        );
+        let outcomes = [first.unwrap(), second.unwrap()];
+
+        assert_eq!(outcomes.iter().filter(|o| matches!(o, CreateOutcome::Created(_))).count(), 1);
+        assert_eq!(
+            outcomes.iter().filter(|o| matches!(o, CreateOutcome::DuplicatePending)).count(),
+            1
+        );
+        assert_eq!(rows.lock().unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn foreign_key_conflict_is_not_a_duplicate() {
+        let requester = Uuid::new_v4();
+        let (url, rows) = barter_table(vec![requester]);
+
+        let outcome = BarterRepository::create_request(
+            &url,
+            "key",
+            &Client::new(),
+            requester,
+            &request_to(Uuid::new_v4()),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(outcome, CreateOutcome::UnknownRecipient));
+        assert!(rows.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+// src/repositories/connection_repository.rs - tabel `connections` via Supabase (PostgREST)
+
+use reqwest::Client;
+use serde_json::json;
+use uuid::Uuid;
+use crate::repositories::pagination::{clamp_limit, parse_total_count};
+use crate::services::http_util::SendTracked;
+
+pub struct ConnectionRepository;
+
+#[derive(serde::Deserialize)]
+struct ConnectionRow {
+    user_low: Uuid,
+    user_high: Uuid,
+}
+
+impl ConnectionRepository {
+    /// Record that two users are connected (a barter between them was accepted).
+    /// The pair is stored sorted, so a reverse barter between the same users, or
+    /// accepting a second barter, hits the primary key and is ignored. The table:
+    ///
+    /// ```sql
+    /// create table connections (
+    ///     user_low uuid not null references auth.users(id) on delete cascade,
+    ///     user_high uuid not null references auth.users(id) on delete cascade,
+    ///     created_at timestamptz not null default now(),
+    ///     primary key (user_low, user_high),
+    ///     check (user_low < user_high)
+    /// );
+    /// create index connections_user_high on connections (user_high);
+    /// ```
+    ///
+    /// Barters accepted before the table existed, or whose insert failed after the
+    /// accept, are filled in with this backfill. It is safe to run again at any time:
+    ///
+    /// ```sql
+    /// insert into connections (user_low, user_high)
+    /// select distinct least(requester_id, recipient_id), greatest(requester_id, recipient_id)
+    /// from barter_requests
+    /// where status = 'accepted' and requester_id <> recipient_id
+    /// on conflict do nothing;
+    /// ```
+    ///
+    /// The insert ignores duplicates, so it is sent with the same retries as a read.
+    pub async fn create(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        user_a: Uuid,
+        user_b: Uuid,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (user_low, user_high) = if user_a < user_b { (user_a, user_b) } else { (user_b, user_a) };
+        let url = format!(
+            "{}/rest/v1/connections?on_conflict=user_low,user_high",
+            supabase_url
+        );
+
+        let response = client
+            .post(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=ignore-duplicates,return=minimal")
+            .json(&json!({ "user_low": user_low, "user_high": user_high }))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Failed to create connection: {} - {}", status, body).into());
+        }
+        Ok(())
+    }
+
+    /// The user's connections, newest first, as the other user's id, plus the total count
+    pub async fn list_partner_ids(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        user_id: Uuid,
+        limit: u32,
+        offset: u32,
+    ) -> Result<(Vec<Uuid>, Option<i64>), Box<dyn std::error::Error>> {
+        let limit = clamp_limit(limit, "ConnectionRepository::list_partner_ids");
+        let url = format!(
+            "{}/rest/v1/connections?or=(user_low.eq.{u},user_high.eq.{u})&select=user_low,user_high&order=created_at.desc&limit={}&offset={}",
+            supabase_url, limit, offset, u = user_id
+        );
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Prefer", "count=exact")
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let total = parse_total_count(response.headers());
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to list connections: {} - {}", status, body).into());
+        }
+
+        let rows: Vec<ConnectionRow> = serde_json::from_str(&body)?;
+        let partners = rows
+            .into_iter()
+            .map(|row| if row.user_low == user_id { row.user_high } else { row.user_low })
+            .collect();
+        Ok((partners, total))
+    }
+
+    /// Delete every connection of the user (account deletion).
+    /// Returns how many rows were removed.
+    pub async fn delete_for_user(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let url = format!(
+            "{}/rest/v1/connections?or=(user_low.eq.{u},user_high.eq.{u})&select=user_low",
+            supabase_url, u = user_id
+        );
+
+        let response = client
+            .delete(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Prefer", "return=representation")
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to delete connections: {} - {}", status, body).into());
+        }
+
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&body)?;
+        Ok(rows.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use actix_web::HttpResponse;
+    use super::*;
+    use crate::test_support::mock_supabase;
+
+    type Pairs = Arc<Mutex<Vec<(Uuid, Uuid)>>>;
+
+    /// `connections` with its (user_low, user_high) primary key; duplicates are ignored
+    /// like PostgREST does with `resolution=ignore-duplicates`
+    fn connections_table() -> (String, Pairs) {
+        let rows: Pairs = Arc::default();
+        let table = rows.clone();
+        let url = mock_supabase(move |req, body| {
+            assert!(req.headers().get("Prefer").unwrap().to_str().unwrap().contains("ignore-duplicates"));
+            let row: serde_json::Value = serde_json::from_slice(body).unwrap();
+            let pair: (Uuid, Uuid) = (
+                serde_json::from_value(row["user_low"].clone()).unwrap(),
+                serde_json::from_value(row["user_high"].clone()).unwrap(),
+            );
+            let mut rows = table.lock().unwrap();
+            if !rows.contains(&pair) {
+                rows.push(pair);
+            }
+            HttpResponse::Created().finish()
+        });
+        (url, rows)
+    }
+
+    #[actix_web::test]
+    async fn reverse_barter_does_not_duplicate_the_connection() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let (url, rows) = connections_table();
+        let client = Client::new();
+
+        ConnectionRepository::create(&url, "key", &client, a, b).await.unwrap();
+        ConnectionRepository::create(&url, "key", &client, b, a).await.unwrap();
+
+        assert_eq!(*rows.lock().unwrap(), vec![(a.min(b), a.max(b))]);
+    }
+
+    #[actix_web::test]
+    async fn partner_is_the_other_side_of_each_row() {
+        let (me, lower, higher) = (
+            Uuid::from_u128(5),
+            Uuid::from_u128(1),
+            Uuid::from_u128(9),
+        );
+        let url = mock_supabase(move |_, _| {
+            HttpResponse::Ok()
+                .insert_header(("Content-Range", "0-1/7"))
+                .json(json!([
+                    { "user_low": me, "user_high": higher },
+                    { "user_low": lower, "user_high": me },
+                ]))
+        });
+
+        let (partners, total) =
+            ConnectionRepository::list_partner_ids(&url, "key", &Client::new(), me, 2, 0).await.unwrap();
+
+        assert_eq!(partners, vec![higher, lower]);
+        assert_eq!(total, Some(7));
+    }
+}
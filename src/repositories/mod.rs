@@ -1,2 +1,10 @@
 pub mod profile_supabase_repo;
-pub mod post_repository;    
\ No newline at end of file
+pub mod post_repository;
+pub mod audit_log_repository;
+pub mod pagination;
+pub mod search_filter;
+pub mod profile_history_repository;
+pub mod barter_repository;
+pub mod skill_repository;
+pub mod report_repository;
+pub mod connection_repository;
\ No newline at end of file
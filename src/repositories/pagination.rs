@@ -0,0 +1,69 @@
+// src/repositories/pagination.rs - helper untuk list endpoint yang dipaginasi
+use reqwest::header::{HeaderMap, CONTENT_RANGE};
+
+/// Total row count from a PostgREST `Content-Range` header (request sent with
+/// `Prefer: count=exact`). `0-19/134` -> Some(134), `*/0` -> Some(0);
+/// an unknown total (`*/*`), a missing or malformed header -> None.
+pub fn parse_total_count(headers: &HeaderMap) -> Option<i64> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let (_, total) = value.trim().rsplit_once('/')?;
+    if total == "*" {
+        return None;
+    }
+    total.parse::<i64>().ok().filter(|t| *t >= 0)
+}
+
+
+/// Hard upper bound for any list query, whoever the caller is
+/// (MAX_LIST_LIMIT, default 500). Read once at first use.
+pub fn max_list_limit() -> u32 {
+    static MAX: std::sync::OnceLock<u32> = std::sync::OnceLock::new();
+    *MAX.get_or_init(|| {
+        std::env::var("MAX_LIST_LIMIT")
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .filter(|max| *max > 0)
+            .unwrap_or(500)
+    })
+}
+
+/// Clamp a requested `limit` to [`max_list_limit`]; `context` names the
+/// repository method in the log line when clamping happens.
+pub fn clamp_limit(limit: u32, context: &str) -> u32 {
+    let max = max_list_limit();
+    if limit > max {
+        log::warn!("{}: requested limit {} clamped to {}", context, limit, max);
+        max
+    } else {
+        limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn total(content_range: Option<&'static str>) -> Option<i64> {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = content_range {
+            headers.insert(CONTENT_RANGE, HeaderValue::from_static(value));
+        }
+        parse_total_count(&headers)
+    }
+
+    #[test]
+    fn total_is_read_after_the_slash() {
+        assert_eq!(total(Some("0-19/134")), Some(134));
+        assert_eq!(total(Some("*/0")), Some(0));
+    }
+
+    #[test]
+    fn unknown_missing_or_malformed_total_is_none() {
+        assert_eq!(total(Some("0-19/*")), None);
+        assert_eq!(total(None), None);
+        assert_eq!(total(Some("0-19")), None);
+        assert_eq!(total(Some("0-19/abc")), None);
+        assert_eq!(total(Some("0-19/-1")), None);
+    }
+}
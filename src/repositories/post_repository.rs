@@ -1,27 +1,59 @@
 // src/repositories/post_repository.rs - Enhanced version with better profile joins
 
+use chrono::{SecondsFormat, Utc};
 use reqwest::Client;
 use serde_json::json;
 use uuid::Uuid;
-use crate::dtos::post_dtos::{CreatePostDTO, PostOut};
+use crate::dtos::post_dtos::{CreatePostDTO, PostOut, UpdatePostDTO};
+use crate::repositories::pagination::clamp_limit;
+use crate::repositories::search_filter::ilike_contains;
+use crate::services::markdown::render_markdown;
+use crate::services::http_util::SendTracked;
 
 pub struct PostRepository;
 
 #[derive(serde::Deserialize, Debug)]
 pub struct PostWithProfile {
-    pub id: String,
-    pub user_id: String,
+    pub id: Uuid,
+    pub user_id: Uuid,
     pub content: Option<String>,
+    pub content_html: Option<String>,
     pub image_url: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    #[serde(default)]
+    pub deleted_at: Option<String>,
     // Profile data joined from profiles table
+    // PostgREST embeds it as an object or a one-element array depending on the inferred FK cardinality
+    #[serde(default, deserialize_with = "deserialize_object_or_first")]
     pub profiles: Option<ProfileData>,
 }
 
+/// Accept `null`, `{...}` or `[{...}]` for an embedded to-one relation
+fn deserialize_object_or_first<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        Many(Vec<T>),
+        One(T),
+    }
+
+    let value: Option<OneOrMany<T>> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(match value {
+        Some(OneOrMany::Many(items)) => items.into_iter().next(),
+        Some(OneOrMany::One(item)) => Some(item),
+        None => None,
+    })
+}
+
 #[derive(serde::Deserialize, Debug)]
 pub struct ProfileData {
     pub full_name: Option<String>,
+    pub username: Option<String>,
     pub primary_skill: Option<String>,
     pub bio: Option<String>,
     pub profile_picture_url: Option<String>,
@@ -29,6 +61,12 @@ pub struct ProfileData {
 }
 
 impl PostRepository {
+    /// Insert a post. Drafts (`is_draft`) and scheduled posts (`publish_at`) stay out of
+    /// every feed until they're published. Drafts need this column:
+    ///
+    /// ```sql
+    /// alter table posts add column is_draft boolean not null default false;
+    /// ```
     pub async fn create_post(
         supabase_url: &str,
         service_key: &str,
@@ -40,11 +78,14 @@ impl PostRepository {
         
         let payload = json!({
             "user_id": user_id,
+            "content_html": render_markdown(&post_data.content),
             "content": post_data.content,
-            "image_url": post_data.image_url
+            "image_url": post_data.image_url,
+            "publish_at": post_data.publish_at,
+            "is_draft": post_data.draft
         });
 
-        println!("Creating post with payload: {}", payload);
+        log::debug!("Creating post with payload: {}", payload);
 
         let response = client
             .post(&url)
@@ -53,14 +94,14 @@ impl PostRepository {
             .header("Content-Type", "application/json")
             .header("Prefer", "return=representation")
             .json(&payload)
-            .send()
+            .send_tracked()
             .await?;
 
         let status = response.status();
         let body = response.text().await?;
         
-        println!("Post creation response status: {}", status);
-        println!("Post creation response body: {}", body);
+        log::debug!("Post creation response status: {}", status);
+        log::debug!("Post creation response body: {}", body);
 
         if !status.is_success() {
             return Err(format!("Failed to create post: {} - {}", status, body).into());
@@ -71,6 +112,221 @@ impl PostRepository {
             .ok_or_else(|| "No post returned from creation".into())
     }
 
+    /// Update a post owned by `user_id`. Only fields present in the DTO are sent,
+    /// so an absent `image_url` keeps the current image and an explicit null removes it.
+    /// Returns Ok(None) when no row matched (post missing or not owned by the user).
+    pub async fn update_post(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        post_id: Uuid,
+        user_id: Uuid,
+        update: UpdatePostDTO,
+    ) -> Result<Option<PostOut>, Box<dyn std::error::Error>> {
+        let url = format!(
+            "{}/rest/v1/posts?id=eq.{}&user_id=eq.{}&deleted_at=is.null",
+            supabase_url, post_id, user_id
+        );
+
+        let mut payload = serde_json::Map::new();
+        payload.insert(
+            "updated_at".to_string(),
+            json!(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)),
+        );
+        if let Some(content) = update.content {
+            payload.insert("content_html".to_string(), json!(render_markdown(&content)));
+            payload.insert("content".to_string(), json!(content));
+        }
+        match update.image_url {
+            None => {}                                   // unchanged
+            Some(None) => {
+                payload.insert("image_url".to_string(), serde_json::Value::Null); // remove
+            }
+            Some(Some(image_url)) => {
+                payload.insert("image_url".to_string(), json!(image_url)); // replace
+            }
+        }
+
+        log::debug!("Updating post {} with payload: {:?}", post_id, payload);
+
+        let response = client
+            .patch(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&payload)
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to update post: {} - {}", status, body).into());
+        }
+
+        let posts: Vec<PostOut> = serde_json::from_str(&body)?;
+        Ok(posts.into_iter().next())
+    }
+
+    /// Soft delete a post owned by `user_id`: sets `deleted_at`, the row stays in the DB
+    /// (replies keep their parent) but every feed/read query filters it out.
+    /// Returns Ok(None) when no row matched (missing, not owned, or already deleted).
+    ///
+    /// Needs the column: `alter table posts add column deleted_at timestamptz;`
+    pub async fn soft_delete_post(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        post_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<PostOut>, Box<dyn std::error::Error>> {
+        let url = format!(
+            "{}/rest/v1/posts?id=eq.{}&user_id=eq.{}&deleted_at=is.null",
+            supabase_url, post_id, user_id
+        );
+
+        let response = client
+            .patch(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&json!({ "deleted_at": Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true) }))
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to delete post: {} - {}", status, body).into());
+        }
+
+        let posts: Vec<PostOut> = serde_json::from_str(&body)?;
+        Ok(posts.into_iter().next())
+    }
+
+    /// Hard delete every post of `user_id`, soft-deleted ones included (account deletion).
+    /// Returns how many rows were removed.
+    pub async fn delete_posts_by_user(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let url = format!("{}/rest/v1/posts?user_id=eq.{}&select=id", supabase_url, user_id);
+
+        let response = client
+            .delete(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Prefer", "return=representation")
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to delete posts: {} - {}", status, body).into());
+        }
+
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&body)?;
+        Ok(rows.len())
+    }
+
+    /// Soft-deleted posts with their author's profile, most recently deleted first (admin view)
+    pub async fn list_deleted_posts(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        limit: u32,
+    ) -> Result<Vec<PostWithProfile>, Box<dyn std::error::Error>> {
+        let limit = clamp_limit(limit, "PostRepository::list_deleted_posts");
+        let url = format!(
+            "{}/rest/v1/posts?deleted_at=not.is.null&select=*,profiles(full_name,username,primary_skill,bio,profile_picture_url,role,is_active)&order=deleted_at.desc&limit={}",
+            supabase_url, limit
+        );
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch deleted posts: {} - {}", status, body).into());
+        }
+
+        let posts: Vec<PostWithProfile> = serde_json::from_str(&body)
+            .map_err(|e| format!("Failed to parse deleted posts response: {} - Body: {}", e, body))?;
+
+        Ok(posts)
+    }
+
+    /// Owner (user_id) of a post, None when the post doesn't exist.
+    /// Drafts and scheduled posts count, so use this only for the author's own actions.
+    pub async fn get_post_owner(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        post_id: Uuid,
+    ) -> Result<Option<Uuid>, Box<dyn std::error::Error>> {
+        Self::fetch_post_owner(supabase_url, service_key, client, post_id, "").await
+    }
+
+    /// Owner of a post other users can see: None for drafts and scheduled posts too,
+    /// like the read endpoints that answer 404 for them
+    pub async fn get_public_post_owner(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        post_id: Uuid,
+    ) -> Result<Option<Uuid>, Box<dyn std::error::Error>> {
+        Self::fetch_post_owner(supabase_url, service_key, client, post_id, "&is_draft=is.false&publish_at=is.null").await
+    }
+
+    async fn fetch_post_owner(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        post_id: Uuid,
+        visibility: &str,
+    ) -> Result<Option<Uuid>, Box<dyn std::error::Error>> {
+        let url = format!(
+            "{}/rest/v1/posts?id=eq.{}&deleted_at=is.null{}&select=user_id",
+            supabase_url, post_id, visibility
+        );
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch post owner: {} - {}", status, body).into());
+        }
+
+        #[derive(serde::Deserialize)]
+        struct OwnerRow {
+            user_id: Uuid,
+        }
+
+        let rows: Vec<OwnerRow> = serde_json::from_str(&body)?;
+        Ok(rows.into_iter().next().map(|row| row.user_id))
+    }
+
     /// List posts with joined profile data
     pub async fn list_posts_with_profiles(
         supabase_url: &str,
@@ -78,52 +334,53 @@ impl PostRepository {
         client: &Client,
         limit: u32,
     ) -> Result<Vec<PostWithProfile>, Box<dyn std::error::Error>> {
+        let limit = clamp_limit(limit, "PostRepository::list_posts_with_profiles");
         // Enhanced query to get profile data including full_name
         // Note: The profiles table uses 'id' as the primary key that references auth.users.id
         let url = format!(
-            "{}/rest/v1/posts?select=*,profiles!posts_user_id_fkey(full_name,primary_skill,bio,profile_picture_url,role)&order=created_at.desc&limit={}",
+            "{}/rest/v1/posts?publish_at=is.null&is_draft=is.false&deleted_at=is.null&profiles.is_active=not.is.false&select=*,profiles!posts_user_id_fkey!inner(full_name,username,primary_skill,bio,profile_picture_url,role,is_active)&order=created_at.desc,id.desc&limit={}",
             supabase_url, limit
         );
 
-        println!("Fetching posts with profiles from: {}", url);
+        log::debug!("Fetching posts with profiles from: {}", url);
 
         let response = client
             .get(&url)
             .header("apikey", service_key)
             .header("Authorization", format!("Bearer {}", service_key))
-            .send()
+            .send_with_retry()
             .await?;
 
         let status = response.status();
         let body = response.text().await?;
         
-        println!("Posts response status: {}", status);
-        println!("Posts response body (first 500 chars): {}", 
+        log::debug!("Posts response status: {}", status);
+        log::debug!("Posts response body (first 500 chars): {}", 
                 if body.len() > 500 { &body[..500] } else { &body });
 
         if !status.is_success() {
-            println!("Profile join failed, trying alternative query...");
+            log::warn!("Profile join failed, trying alternative query...");
             
             // Alternative: Try without explicit foreign key reference
             let alt_url = format!(
-                "{}/rest/v1/posts?select=*,profiles(full_name,primary_skill,bio,profile_picture_url,role)&order=created_at.desc&limit={}",
+                "{}/rest/v1/posts?publish_at=is.null&is_draft=is.false&deleted_at=is.null&profiles.is_active=not.is.false&select=*,profiles!inner(full_name,username,primary_skill,bio,profile_picture_url,role,is_active)&order=created_at.desc,id.desc&limit={}",
                 supabase_url, limit
             );
             
-            println!("Trying alternative URL: {}", alt_url);
+            log::debug!("Trying alternative URL: {}", alt_url);
             
             let alt_response = client
                 .get(&alt_url)
                 .header("apikey", service_key)
                 .header("Authorization", format!("Bearer {}", service_key))
-                .send()
+                .send_with_retry()
                 .await?;
 
             let alt_status = alt_response.status();
             let alt_body = alt_response.text().await?;
             
-            println!("Alternative response status: {}", alt_status);
-            println!("Alternative response body (first 500 chars): {}", 
+            log::debug!("Alternative response status: {}", alt_status);
+            log::debug!("Alternative response body (first 500 chars): {}", 
                     if alt_body.len() > 500 { &alt_body[..500] } else { &alt_body });
 
             if !alt_status.is_success() {
@@ -142,6 +399,91 @@ impl PostRepository {
         Ok(posts)
     }
 
+    /// One published post with its author's profile; None when no post matches
+    /// (or the author deactivated their account, as in the feed)
+    pub async fn get_post_with_profile(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        post_id: Uuid,
+    ) -> Result<Option<PostWithProfile>, Box<dyn std::error::Error>> {
+        let url = format!(
+            "{}/rest/v1/posts?id=eq.{}&publish_at=is.null&is_draft=is.false&deleted_at=is.null&profiles.is_active=not.is.false&select=*,profiles!inner(full_name,username,primary_skill,bio,profile_picture_url,role,is_active)",
+            supabase_url, post_id
+        );
+
+        log::debug!("Fetching post with profile from: {}", url);
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch post: {} - {}", status, body).into());
+        }
+
+        let posts: Vec<PostWithProfile> = serde_json::from_str(&body)
+            .map_err(|e| format!("Failed to parse post response: {} - Body: {}", e, body))?;
+
+        Ok(posts.into_iter().next())
+    }
+
+    /// Id of the published post right before (`older`) or after the post `post_id`
+    /// created at `created_at`, in the feed's (created_at, id) order. Posts with the same
+    /// created_at are told apart by id. None at either end of the feed.
+    pub async fn get_neighbor_post_id(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        post_id: Uuid,
+        created_at: &str,
+        older: bool,
+    ) -> Result<Option<Uuid>, Box<dyn std::error::Error>> {
+        let (op, order) = if older { ("lt", "desc") } else { ("gt", "asc") };
+        // (created_at, id) < / > (X, ID); timestamp di-quote karena berisi ':' dan '+'
+        let after = format!(
+            "(created_at.{op}.\"{ts}\",and(created_at.eq.\"{ts}\",id.{op}.{id}))",
+            op = op,
+            ts = created_at,
+            id = post_id
+        );
+        let url = format!(
+            "{}/rest/v1/posts?publish_at=is.null&is_draft=is.false&deleted_at=is.null&profiles.is_active=not.is.false&or={}&select=id,profiles!inner(is_active)&order=created_at.{},id.{}&limit=1",
+            supabase_url,
+            urlencoding::encode(&after),
+            order,
+            order
+        );
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch neighbor post: {} - {}", status, body).into());
+        }
+
+        #[derive(serde::Deserialize)]
+        struct IdRow {
+            id: Uuid,
+        }
+
+        let rows: Vec<IdRow> = serde_json::from_str(&body)?;
+        Ok(rows.into_iter().next().map(|row| row.id))
+    }
+
     /// Enhanced method to get posts for a specific user with their profile
     pub async fn get_user_posts_with_profile(
         supabase_url: &str,
@@ -150,18 +492,19 @@ impl PostRepository {
         user_id: Uuid,
         limit: u32,
     ) -> Result<Vec<PostWithProfile>, Box<dyn std::error::Error>> {
+        let limit = clamp_limit(limit, "PostRepository::get_user_posts_with_profile");
         let url = format!(
-            "{}/rest/v1/posts?user_id=eq.{}&select=*,profiles(full_name,primary_skill,bio,profile_picture_url,role)&order=created_at.desc&limit={}",
+            "{}/rest/v1/posts?publish_at=is.null&is_draft=is.false&deleted_at=is.null&profiles.is_active=not.is.false&user_id=eq.{}&select=*,profiles!inner(full_name,username,primary_skill,bio,profile_picture_url,role,is_active)&order=created_at.desc,id.desc&limit={}",
             supabase_url, user_id, limit
         );
 
-        println!("Fetching user posts with profile from: {}", url);
+        log::debug!("Fetching user posts with profile from: {}", url);
 
         let response = client
             .get(&url)
             .header("apikey", service_key)
             .header("Authorization", format!("Bearer {}", service_key))
-            .send()
+            .send_with_retry()
             .await?;
 
         let status = response.status();
@@ -177,6 +520,43 @@ impl PostRepository {
         Ok(posts)
     }
 
+    /// Newest posts whose content contains `query`, with the author's profile joined
+    pub async fn search_posts(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        query: &str,
+        limit: u32,
+    ) -> Result<Vec<PostWithProfile>, Box<dyn std::error::Error>> {
+        let limit = clamp_limit(limit, "PostRepository::search_posts");
+        let filter = format!("ilike.{}", ilike_contains(query));
+        let url = format!(
+            "{}/rest/v1/posts?publish_at=is.null&is_draft=is.false&deleted_at=is.null&profiles.is_active=not.is.false&content={}&select=*,profiles!inner(full_name,username,primary_skill,bio,profile_picture_url,role,is_active)&order=created_at.desc,id.desc&limit={}",
+            supabase_url, urlencoding::encode(&filter), limit
+        );
+
+        log::debug!("Searching posts: {}", url);
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to search posts: {} - {}", status, body).into());
+        }
+
+        let posts: Vec<PostWithProfile> = serde_json::from_str(&body)
+            .map_err(|e| format!("Failed to parse post search response: {} - Body: {}", e, body))?;
+
+        Ok(posts)
+    }
+
     /// Fallback method for basic posts (keeping for compatibility)
     pub async fn list_posts(
         supabase_url: &str,
@@ -184,8 +564,9 @@ impl PostRepository {
         client: &Client,
         limit: u32,
     ) -> Result<Vec<PostOut>, Box<dyn std::error::Error>> {
+        let limit = clamp_limit(limit, "PostRepository::list_posts");
         let url = format!(
-            "{}/rest/v1/posts?order=created_at.desc&limit={}",
+            "{}/rest/v1/posts?publish_at=is.null&is_draft=is.false&deleted_at=is.null&profiles.is_active=not.is.false&select=*,profiles!inner(is_active)&order=created_at.desc,id.desc&limit={}",
             supabase_url, limit
         );
 
@@ -193,7 +574,7 @@ impl PostRepository {
             .get(&url)
             .header("apikey", service_key)
             .header("Authorization", format!("Bearer {}", service_key))
-            .send()
+            .send_with_retry()
             .await?;
 
         let status = response.status();
@@ -206,4 +587,398 @@ impl PostRepository {
         let posts: Vec<PostOut> = serde_json::from_str(&body)?;
         Ok(posts)
     }
-}
\ No newline at end of file
+
+    /// The caller's scheduled (not yet published) posts, soonest first
+    pub async fn list_scheduled_posts(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        user_id: Uuid,
+        limit: u32,
+    ) -> Result<Vec<PostOut>, Box<dyn std::error::Error>> {
+        let limit = clamp_limit(limit, "PostRepository::list_scheduled_posts");
+        let url = format!(
+            "{}/rest/v1/posts?user_id=eq.{}&publish_at=not.is.null&deleted_at=is.null&order=publish_at.asc,id.asc&limit={}",
+            supabase_url, user_id, limit
+        );
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch scheduled posts: {} - {}", status, body).into());
+        }
+
+        let posts: Vec<PostOut> = serde_json::from_str(&body)?;
+        Ok(posts)
+    }
+
+    /// The caller's unpublished posts, most recently edited first: drafts and posts still
+    /// waiting for their publish_at. Scheduled ones are told apart by `publish_at`.
+    pub async fn list_drafts(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        user_id: Uuid,
+        limit: u32,
+    ) -> Result<Vec<PostOut>, Box<dyn std::error::Error>> {
+        let limit = clamp_limit(limit, "PostRepository::list_drafts");
+        let url = format!(
+            "{}/rest/v1/posts?user_id=eq.{}&or=(is_draft.is.true,publish_at.not.is.null)&deleted_at=is.null&order=updated_at.desc.nullslast,created_at.desc,id.desc&limit={}",
+            supabase_url, user_id, limit
+        );
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch drafts: {} - {}", status, body).into());
+        }
+
+        let posts: Vec<PostOut> = serde_json::from_str(&body)?;
+        Ok(posts)
+    }
+
+    /// Publish one of the user's drafts: it becomes public with a fresh created_at,
+    /// so it lands at the top of the feed. Ok(None) when no draft of this user matched
+    /// (missing, someone else's, deleted or already published).
+    pub async fn publish_draft(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        post_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<PostOut>, Box<dyn std::error::Error>> {
+        let url = format!(
+            "{}/rest/v1/posts?id=eq.{}&user_id=eq.{}&is_draft=is.true&deleted_at=is.null",
+            supabase_url, post_id, user_id
+        );
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+
+        let response = client
+            .patch(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&json!({ "is_draft": false, "created_at": now }))
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to publish draft: {} - {}", status, body).into());
+        }
+
+        let posts: Vec<PostOut> = serde_json::from_str(&body)?;
+        Ok(posts.into_iter().next())
+    }
+
+    /// Publish scheduled posts whose publish_at has passed: created_at becomes the
+    /// post's own publish_at (so it lands in the feed where it was scheduled, not
+    /// together with everything else published in the same run) and publish_at is
+    /// cleared. Returns how many posts were published.
+    pub async fn publish_due_posts(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let url = format!(
+            "{}/rest/v1/posts?publish_at=lte.{}&deleted_at=is.null&select=id,publish_at",
+            supabase_url, urlencoding::encode(&now)
+        );
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch due scheduled posts: {} - {}", status, body).into());
+        }
+
+        #[derive(serde::Deserialize)]
+        struct DueRow {
+            id: Uuid,
+            publish_at: String,
+        }
+
+        // PostgREST tidak bisa `SET created_at = publish_at`, jadi satu PATCH per waktu publish
+        let mut by_publish_at: std::collections::BTreeMap<String, Vec<Uuid>> = Default::default();
+        for row in serde_json::from_str::<Vec<DueRow>>(&body)? {
+            by_publish_at.entry(row.publish_at).or_default().push(row.id);
+        }
+
+        let mut published = 0;
+        for (publish_at, ids) in by_publish_at {
+            let ids: Vec<String> = ids.iter().map(Uuid::to_string).collect();
+            // publish_at=eq. lagi: post yang dijadwal ulang sejak GET tadi tidak ikut ter-publish
+            let url = format!(
+                "{}/rest/v1/posts?id=in.({})&publish_at=eq.{}&deleted_at=is.null&select=id",
+                supabase_url,
+                ids.join(","),
+                urlencoding::encode(&publish_at)
+            );
+
+            let response = client
+                .patch(&url)
+                .header("apikey", service_key)
+                .header("Authorization", format!("Bearer {}", service_key))
+                .header("Content-Type", "application/json")
+                .header("Prefer", "return=representation")
+                .json(&json!({ "created_at": publish_at, "publish_at": null }))
+                .send_tracked()
+                .await?;
+
+            let status = response.status();
+            let body = response.text().await?;
+
+            if !status.is_success() {
+                return Err(format!("Failed to publish scheduled posts: {} - {}", status, body).into());
+            }
+
+            let rows: Vec<serde_json::Value> = serde_json::from_str(&body)?;
+            published += rows.len();
+        }
+
+        Ok(published)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use actix_web::HttpResponse;
+    use super::*;
+    use crate::test_support::mock_supabase;
+
+    #[actix_web::test]
+    async fn each_due_post_is_published_at_its_own_publish_time() {
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let patches: Arc<Mutex<Vec<(String, serde_json::Value)>>> = Arc::default();
+        let seen = patches.clone();
+
+        let url = mock_supabase(move |req, body| {
+            if req.method() == "GET" {
+                return HttpResponse::Ok().json(json!([
+                    { "id": first, "publish_at": "2026-01-01T10:00:00+00:00" },
+                    { "id": second, "publish_at": "2026-01-01T10:00:30+00:00" },
+                ]));
+            }
+            let query = req.query_string().to_string();
+            let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+            seen.lock().unwrap().push((query, payload));
+            HttpResponse::Ok().json(json!([{ "id": Uuid::new_v4() }]))
+        });
+
+        let published = PostRepository::publish_due_posts(&url, "key", &Client::new()).await.unwrap();
+
+        assert_eq!(published, 2);
+        let patches = patches.lock().unwrap();
+        assert_eq!(patches.len(), 2);
+        for ((query, payload), (id, publish_at)) in patches.iter().zip([
+            (first, "2026-01-01T10:00:00+00:00"),
+            (second, "2026-01-01T10:00:30+00:00"),
+        ]) {
+            assert!(query.contains(&format!("id=in.({})", id)));
+            assert_eq!(payload["created_at"], publish_at);
+            assert!(payload["publish_at"].is_null());
+        }
+    }
+
+    #[actix_web::test]
+    async fn drafts_include_scheduled_posts() {
+        let (user_id, draft, scheduled) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let url = mock_supabase(move |req, _| {
+            let query = req.query_string();
+            assert!(query.contains("or=(is_draft.is.true,publish_at.not.is.null)"), "{}", query);
+            assert!(query.contains(&format!("user_id=eq.{}", user_id)), "{}", query);
+            HttpResponse::Ok().json(json!([
+                { "id": draft, "user_id": user_id, "is_draft": true },
+                { "id": scheduled, "user_id": user_id, "publish_at": "2026-11-01T09:00:00Z" },
+            ]))
+        });
+
+        let posts = PostRepository::list_drafts(&url, "key", &Client::new(), user_id, 50).await.unwrap();
+
+        let out = serde_json::to_value(&posts).unwrap();
+        assert_eq!(out[0]["id"], json!(draft));
+        assert!(out[0]["publish_at"].is_null());
+        assert_eq!(out[1]["id"], json!(scheduled));
+        assert_eq!(out[1]["publish_at"], "2026-11-01T09:00:00Z");
+    }
+
+    #[actix_web::test]
+    async fn neighbor_lookup_breaks_created_at_ties_by_id() {
+        let (post_id, neighbor) = (Uuid::new_v4(), Uuid::new_v4());
+        let queries: Arc<Mutex<Vec<String>>> = Arc::default();
+        let seen = queries.clone();
+        let url = mock_supabase(move |req, _| {
+            seen.lock().unwrap().push(urlencoding::decode(req.query_string()).unwrap().into_owned());
+            HttpResponse::Ok().json(json!([{ "id": neighbor }]))
+        });
+        let created_at = "2026-01-01T10:00:00+00:00";
+
+        let older = PostRepository::get_neighbor_post_id(&url, "key", &Client::new(), post_id, created_at, true)
+            .await
+            .unwrap();
+
+        assert_eq!(older, Some(neighbor));
+        let query = queries.lock().unwrap()[0].clone();
+        assert!(query.contains(&format!(
+            "or=(created_at.lt.\"{0}\",and(created_at.eq.\"{0}\",id.lt.{1}))",
+            created_at, post_id
+        )));
+        assert!(query.contains("order=created_at.desc,id.desc"));
+        assert!(query.contains("profiles.is_active=not.is.false"));
+        assert!(query.contains("select=id,profiles!inner(is_active)"));
+    }
+
+    #[actix_web::test]
+    async fn fallback_feed_skips_inactive_authors() {
+        let url = mock_supabase(move |req, _| {
+            let query = urlencoding::decode(req.query_string()).unwrap().into_owned();
+            assert!(query.contains("profiles.is_active=not.is.false"), "{}", query);
+            assert!(query.contains("select=*,profiles!inner(is_active)"), "{}", query);
+            HttpResponse::Ok().json(json!([
+                { "id": Uuid::new_v4(), "content": "hi", "profiles": { "is_active": true } },
+            ]))
+        });
+
+        let posts = PostRepository::list_posts(&url, "key", &Client::new(), 20).await.unwrap();
+        assert_eq!(posts.len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn update_sends_image_url_only_when_present() {
+        let payloads: Arc<Mutex<Vec<serde_json::Value>>> = Arc::default();
+        let seen = payloads.clone();
+        let url = mock_supabase(move |_, body| {
+            seen.lock().unwrap().push(serde_json::from_slice(body).unwrap());
+            HttpResponse::Ok().json(json!([]))
+        });
+        let (post_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        for image_url in [None, Some(None)] {
+            let update = UpdatePostDTO { content: Some("hi".into()), image_url };
+            PostRepository::update_post(&url, "key", &Client::new(), post_id, user_id, update)
+                .await
+                .unwrap();
+        }
+
+        let payloads = payloads.lock().unwrap();
+        assert!(payloads[0].get("image_url").is_none());
+        assert!(payloads[1]["image_url"].is_null() && payloads[1].get("image_url").is_some());
+    }
+
+    #[actix_web::test]
+    async fn post_owner_tells_someone_elses_post_from_a_missing_one() {
+        let (owned, missing, owner) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let url = mock_supabase(move |req, _| {
+            if req.query_string().contains(&format!("id=eq.{}", owned)) {
+                HttpResponse::Ok().json(json!([{ "user_id": owner }]))
+            } else {
+                HttpResponse::Ok().json(json!([]))
+            }
+        });
+        let client = Client::new();
+
+        let found = PostRepository::get_post_owner(&url, "key", &client, owned).await.unwrap();
+        assert_eq!(found, Some(owner));
+        let none = PostRepository::get_post_owner(&url, "key", &client, missing).await.unwrap();
+        assert_eq!(none, None);
+    }
+
+    #[actix_web::test]
+    async fn public_owner_lookup_skips_drafts_and_scheduled_posts() {
+        let queries: Arc<Mutex<Vec<String>>> = Arc::default();
+        let seen = queries.clone();
+        let url = mock_supabase(move |req, _| {
+            seen.lock().unwrap().push(req.query_string().to_string());
+            HttpResponse::Ok().json(json!([]))
+        });
+        let (client, post_id) = (Client::new(), Uuid::new_v4());
+
+        PostRepository::get_public_post_owner(&url, "key", &client, post_id).await.unwrap();
+        PostRepository::get_post_owner(&url, "key", &client, post_id).await.unwrap();
+
+        let queries = queries.lock().unwrap();
+        assert!(queries[0].contains("is_draft=is.false&publish_at=is.null"), "{}", queries[0]);
+        assert!(!queries[1].contains("is_draft"), "{}", queries[1]);
+    }
+
+    #[actix_web::test]
+    async fn publishing_a_draft_makes_it_public_with_a_fresh_created_at() {
+        let (post_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let patches: Arc<Mutex<Vec<(String, serde_json::Value)>>> = Arc::default();
+        let seen = patches.clone();
+        let url = mock_supabase(move |req, body| {
+            let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+            seen.lock().unwrap().push((req.query_string().to_string(), payload));
+            HttpResponse::Ok().json(json!([]))
+        });
+        let before = Utc::now() - chrono::Duration::seconds(1);
+
+        let published = PostRepository::publish_draft(&url, "key", &Client::new(), post_id, user_id)
+            .await
+            .unwrap();
+
+        assert!(published.is_none());
+        let (query, payload) = patches.lock().unwrap()[0].clone();
+        assert!(query.contains(&format!("id=eq.{}&user_id=eq.{}&is_draft=is.true", post_id, user_id)));
+        assert_eq!(payload["is_draft"], false);
+        let created_at: chrono::DateTime<Utc> = payload["created_at"].as_str().unwrap().parse().unwrap();
+        assert!(created_at >= before);
+    }
+
+    fn post_with(profiles: serde_json::Value) -> PostWithProfile {
+        serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "user_id": Uuid::new_v4(),
+            "content": "hi",
+            "content_html": null,
+            "image_url": null,
+            "created_at": null,
+            "updated_at": null,
+            "profiles": profiles,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn embedded_profile_is_read_as_object_or_first_array_element() {
+        let object = post_with(json!({ "full_name": "Ayu" }));
+        assert_eq!(object.profiles.unwrap().full_name.as_deref(), Some("Ayu"));
+
+        let array = post_with(json!([{ "full_name": "Budi" }]));
+        assert_eq!(array.profiles.unwrap().full_name.as_deref(), Some("Budi"));
+    }
+
+    #[test]
+    fn null_or_empty_embedded_profile_is_none() {
+        assert!(post_with(json!(null)).profiles.is_none());
+        assert!(post_with(json!([])).profiles.is_none());
+    }
+}
@@ -0,0 +1,126 @@
+// src/repositories/profile_history_repository.rs - riwayat perubahan field profile
+
+use chrono::{NaiveDateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::repositories::pagination::clamp_limit;
+use crate::services::http_util::SendTracked;
+
+pub struct ProfileHistoryRepository;
+
+/// Satu baris tabel `profile_field_changes`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProfileFieldChange {
+    pub user_id: Uuid,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: NaiveDateTime,
+}
+
+impl ProfileFieldChange {
+    /// A change of `field` made now, or None when the value didn't change
+    pub fn if_changed(
+        user_id: Uuid,
+        field: &str,
+        old_value: Option<String>,
+        new_value: Option<String>,
+    ) -> Option<Self> {
+        if old_value == new_value {
+            return None;
+        }
+
+        Some(Self {
+            user_id,
+            field: field.to_string(),
+            old_value,
+            new_value,
+            changed_at: Utc::now().naive_utc(),
+        })
+    }
+}
+
+impl ProfileHistoryRepository {
+    /// Insert a batch of field changes
+    pub async fn insert(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        changes: &[ProfileFieldChange],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}/rest/v1/profile_field_changes", supabase_url.trim_end_matches('/'));
+
+        let response = client
+            .post(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=minimal")
+            .json(changes)
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Failed to write profile history: {} - {}", status, body).into());
+        }
+
+        Ok(())
+    }
+
+    /// Best-effort write in the background, like AuditLogRepository::record
+    pub fn record(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        changes: Vec<ProfileFieldChange>,
+    ) {
+        if changes.is_empty() {
+            return;
+        }
+
+        let supabase_url = supabase_url.to_string();
+        let service_key = service_key.to_string();
+        let client = client.clone();
+
+        actix_web::rt::spawn(async move {
+            if let Err(e) = Self::insert(&supabase_url, &service_key, &client, &changes).await {
+                log::error!("Profile history write failed for user {}: {}", changes[0].user_id, e);
+            }
+        });
+    }
+
+    /// Most recent changes of the user's profile, newest first
+    pub async fn list_recent(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        user_id: Uuid,
+        limit: u32,
+    ) -> Result<Vec<ProfileFieldChange>, Box<dyn std::error::Error + Send + Sync>> {
+        let limit = clamp_limit(limit, "ProfileHistoryRepository::list_recent");
+        let url = format!(
+            "{}/rest/v1/profile_field_changes?user_id=eq.{}&select=user_id,field,old_value,new_value,changed_at&order=changed_at.desc&limit={}",
+            supabase_url.trim_end_matches('/'),
+            user_id,
+            limit
+        );
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(format!("Failed to fetch profile history: {} - {}", status, body).into());
+        }
+
+        Ok(serde_json::from_str(&body)?)
+    }
+}
@@ -1,18 +1,25 @@
 // src/repositories/profile_supabase_repo.rs
 use crate::models::personal::{NewPersonal, Personal}; // sesuaikan path
-use crate::dtos::personal::CreatePersonalDTO;
+use crate::dtos::personal::{AdminProfileOut, CreatePersonalDTO, PublicProfileOut};
+use crate::repositories::pagination::{clamp_limit, parse_total_count};
+use crate::repositories::search_filter::ilike_contains;
+use crate::services::http_util::{build_supabase_client, is_unreachable, SendError, SendTracked};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
 use uuid::Uuid;
+use std::collections::HashMap;
 use std::env;
 use urlencoding::encode;
 
 #[derive(Debug, Error)]
 pub enum RepoError {
     #[error("http error: {0}")]
-    Http(#[from] reqwest::Error),
+    Http(reqwest::Error),
+    /// Supabase couldn't be reached (connect error or timeout)
+    #[error("supabase unavailable: {0}")]
+    Unavailable(String),
     #[error("serde error: {0}")]
     Serde(#[from] serde_json::Error),
     #[error("supabase error: {0}")]
@@ -23,6 +30,25 @@ pub enum RepoError {
     Other(String),
 }
 
+impl From<SendError> for RepoError {
+    fn from(e: SendError) -> Self {
+        match e {
+            SendError::Http(e) => e.into(),
+            SendError::CircuitOpen => RepoError::Unavailable(e.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for RepoError {
+    fn from(e: reqwest::Error) -> Self {
+        if is_unreachable(&e) {
+            RepoError::Unavailable(e.to_string())
+        } else {
+            RepoError::Http(e)
+        }
+    }
+}
+
 /// Repository untuk table `profiles` via Supabase (PostgREST)
 #[derive(Clone)]
 pub struct ProfileSupabaseRepo {
@@ -33,27 +59,47 @@ pub struct ProfileSupabaseRepo {
 }
 
 impl ProfileSupabaseRepo {
-    /// create from env vars (helper). Panik kalau service role key tidak ada.
-    pub fn new_from_env() -> Self {
-        let supabase_url = env::var("SUPABASE_URL").expect("SUPABASE_URL required");
+    /// Repo for `supabase_url` (with or without the `/rest/v1` suffix) using `client`
+    pub fn new(client: Client, supabase_url: &str, service_role_key: impl Into<String>) -> Self {
         let rest = if supabase_url.ends_with("/rest/v1") {
             supabase_url.trim_end_matches('/').to_string()
         } else {
             format!("{}/rest/v1", supabase_url.trim_end_matches('/'))
         };
 
+        Self {
+            client,
+            base_rest_url: rest,
+            service_role_key: service_role_key.into(),
+            anon_key: None,
+        }
+    }
+
+    /// create from env vars (helper). Panik kalau service role key tidak ada.
+    pub fn new_from_env() -> Self {
+        let supabase_url = env::var("SUPABASE_URL").expect("SUPABASE_URL required");
         let service_role_key =
             env::var("SUPABASE_SERVICE_ROLE_KEY").expect("SUPABASE_SERVICE_ROLE_KEY required");
-        let anon_key = env::var("SUPABASE_ANON_KEY").ok();
 
         Self {
-            client: Client::new(),
-            base_rest_url: rest,
-            service_role_key,
-            anon_key,
+            anon_key: env::var("SUPABASE_ANON_KEY").ok(),
+            ..Self::new(build_supabase_client(), &supabase_url, service_role_key)
         }
     }
 
+    /// Pakai `client` yang sudah ada (mis. milik AppState) daripada client sendiri
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Kolom untuk Personal (`user_id` = alias dari `id`)
+    const PERSONAL_SELECT: &'static str = "*,user_id:id";
+
+    /// Kolom untuk PublicProfileOut (`id` di-alias jadi `user_id`)
+    const PUBLIC_PROFILE_SELECT: &'static str =
+        "user_id:id,full_name,primary_skill,skill_to_learn,bio,profile_picture_url";
+
     fn profiles_url(&self) -> String {
         format!("{}/profiles", self.base_rest_url.trim_end_matches('/'))
     }
@@ -84,30 +130,42 @@ impl ProfileSupabaseRepo {
         #[derive(Serialize)]
         struct Payload<'a> {
             id: &'a str,
-            date_of_birth: &'a str,
+            /// null menghapus tanggal lahir ("" dari DTO)
+            date_of_birth: Option<&'a str>,
             primary_skill: &'a str,
             skill_to_learn: &'a str,
             bio: &'a str,
-            role: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            role: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            is_discoverable: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            full_name: Option<&'a str>,
         }
 
+        // Role hanya di-set untuk row baru: upsert merge-duplicates akan menimpa
+        // role yang sudah ada (mis. "admin") kalau field ini selalu dikirim
+        let existing_role = self.get_role_by_user_id(user_id).await?;
+
         let payload = Payload {
             id: &user_id.to_string(),
-            date_of_birth: &dto.date_of_birth,
+            date_of_birth: Some(dto.date_of_birth.as_str()).filter(|d| !d.is_empty()),
             primary_skill: &dto.primary_skill,
             skill_to_learn: &dto.skill_to_learn,
             bio: &dto.bio,
-            role: "user",
+            role: if existing_role.is_none() { Some("user") } else { None },
+            is_discoverable: dto.is_discoverable,
+            full_name: dto.full_name.as_deref(),
         };
 
-        let url = self.profiles_url();
+        let url = format!("{}?select={}", self.profiles_url(), Self::PERSONAL_SELECT);
         let resp = self
             .client
             .post(&url)
             .headers(self.headers())
             .header("Prefer", "resolution=merge-duplicates,return=representation")
             .json(&payload)
-            .send()
+            .send_tracked()
             .await?;
 
         let status = resp.status();
@@ -133,19 +191,20 @@ impl ProfileSupabaseRepo {
 
     /// Get profile by user id (id = primary key referencing auth.users.id)
     pub async fn get_by_user_id(&self, user_id: Uuid) -> Result<Personal, RepoError> {
-        // PostgREST filter: ?id=eq.<uuid>&select=*
+        // PostgREST filter: ?id=eq.<uuid>&select=*,user_id:id
         // url encode user_id just in case
         let url = format!(
-            "{}?id=eq.{}&select=*",
+            "{}?id=eq.{}&select={}",
             self.profiles_url(),
-            encode(&user_id.to_string())
+            encode(&user_id.to_string()),
+            Self::PERSONAL_SELECT
         );
 
         let resp = self
             .client
             .get(&url)
             .headers(self.headers())
-            .send()
+            .send_with_retry()
             .await?;
 
         let status = resp.status();
@@ -162,6 +221,246 @@ impl ProfileSupabaseRepo {
         arr.into_iter().next().ok_or(RepoError::NotFound)
     }
 
+    /// Public subset of one user's profile; None when there is no (active) profile
+    /// Public profiles for several users in one request (`id=in.(...)`).
+    /// Users without a profile, or who deactivated their account, are simply absent.
+    pub async fn get_by_user_ids(&self, user_ids: &[Uuid]) -> Result<Vec<PublicProfileOut>, RepoError> {
+        if user_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids = user_ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let url = format!(
+            "{}?id=in.({})&is_active=not.is.false&select={}",
+            self.profiles_url(),
+            ids,
+            Self::PUBLIC_PROFILE_SELECT
+        );
+
+        let resp = self
+            .client
+            .get(&url)
+            .headers(self.headers())
+            .send_with_retry()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            return Err(RepoError::Supabase(format!(
+                "{} -> {}",
+                status.as_u16(),
+                text
+            )));
+        }
+
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    pub async fn get_public_profile(&self, user_id: Uuid) -> Result<Option<PublicProfileOut>, RepoError> {
+        let url = format!(
+            "{}?id=eq.{}&is_active=not.is.false&select={}",
+            self.profiles_url(),
+            encode(&user_id.to_string()),
+            Self::PUBLIC_PROFILE_SELECT
+        );
+
+        let resp = self
+            .client
+            .get(&url)
+            .headers(self.headers())
+            .send_with_retry()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            return Err(RepoError::Supabase(format!(
+                "{} -> {}",
+                status.as_u16(),
+                text
+            )));
+        }
+
+        let profiles: Vec<PublicProfileOut> = serde_json::from_str(&text)?;
+        Ok(profiles.into_iter().next())
+    }
+
+    /// Discoverable public profiles whose primary_skill equals `skill`, most recently updated first,
+    /// plus the total match count (None if PostgREST didn't report it).
+    /// `exclude_user_id` (biasanya user yang sedang login) tidak ikut dikembalikan.
+    pub async fn list_by_primary_skill(
+        &self,
+        skill: &str,
+        exclude_user_id: Option<Uuid>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<(Vec<PublicProfileOut>, Option<i64>), RepoError> {
+        let limit = clamp_limit(limit, "ProfileSupabaseRepo::list_by_primary_skill");
+        let mut url = format!(
+            "{}?primary_skill=eq.{}&is_discoverable=not.is.false&is_active=not.is.false&select={}&order=updated_at.desc.nullslast&limit={}&offset={}",
+            self.profiles_url(),
+            encode(skill),
+            Self::PUBLIC_PROFILE_SELECT,
+            limit,
+            offset
+        );
+        if let Some(exclude) = exclude_user_id {
+            url.push_str(&format!("&id=neq.{}", encode(&exclude.to_string())));
+        }
+
+        let resp = self
+            .client
+            .get(&url)
+            .headers(self.headers())
+            .header("Prefer", "count=exact")
+            .send_with_retry()
+            .await?;
+
+        let status = resp.status();
+        let total = parse_total_count(resp.headers());
+        let text = resp.text().await?;
+        if !status.is_success() {
+            return Err(RepoError::Supabase(format!(
+                "{} -> {}",
+                status.as_u16(),
+                text
+            )));
+        }
+
+        let profiles: Vec<PublicProfileOut> = serde_json::from_str(&text)?;
+        Ok((profiles, total))
+    }
+
+    /// Number of discoverable profiles teaching each skill (their primary_skill), in one
+    /// grouped query. Needs PostgREST aggregates:
+    ///
+    /// ```sql
+    /// alter role authenticator set pgrst.db_aggregates_enabled = 'true';
+    /// notify pgrst, 'reload config';
+    /// ```
+    pub async fn count_by_primary_skill(
+        &self,
+        exclude_user_id: Option<Uuid>,
+    ) -> Result<HashMap<String, i64>, RepoError> {
+        let mut url = format!(
+            "{}?primary_skill=not.is.null&is_discoverable=not.is.false&is_active=not.is.false&select=primary_skill,count()",
+            self.profiles_url()
+        );
+        if let Some(exclude) = exclude_user_id {
+            url.push_str(&format!("&id=neq.{}", encode(&exclude.to_string())));
+        }
+
+        let resp = self
+            .client
+            .get(&url)
+            .headers(self.headers())
+            .send_with_retry()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            return Err(RepoError::Supabase(format!(
+                "{} -> {}",
+                status.as_u16(),
+                text
+            )));
+        }
+
+        #[derive(Deserialize)]
+        struct SkillCount {
+            primary_skill: String,
+            count: i64,
+        }
+
+        let rows: Vec<SkillCount> = serde_json::from_str(&text)?;
+        Ok(rows.into_iter().map(|row| (row.primary_skill, row.count)).collect())
+    }
+
+    /// Discoverable profiles whose username, full name, skills or bio contain `query`
+    pub async fn search_profiles(
+        &self,
+        query: &str,
+        limit: u32,
+    ) -> Result<Vec<PublicProfileOut>, RepoError> {
+        let limit = clamp_limit(limit, "ProfileSupabaseRepo::search_profiles");
+        let pattern = ilike_contains(query);
+        let filter = format!(
+            "(username.ilike.{p},full_name.ilike.{p},primary_skill.ilike.{p},skill_to_learn.ilike.{p},bio.ilike.{p})",
+            p = pattern
+        );
+        let url = format!(
+            "{}?or={}&is_discoverable=not.is.false&is_active=not.is.false&select={}&order=updated_at.desc.nullslast&limit={}",
+            self.profiles_url(),
+            encode(&filter),
+            Self::PUBLIC_PROFILE_SELECT,
+            limit
+        );
+
+        let resp = self
+            .client
+            .get(&url)
+            .headers(self.headers())
+            .send_with_retry()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            return Err(RepoError::Supabase(format!(
+                "{} -> {}",
+                status.as_u16(),
+                text
+            )));
+        }
+
+        let profiles: Vec<PublicProfileOut> = serde_json::from_str(&text)?;
+        Ok(profiles)
+    }
+
+    /// All profiles, newest first, including hidden and deactivated ones (admin only),
+    /// plus the total count (None if PostgREST didn't report it).
+    pub async fn list_all(
+        &self,
+        limit: u32,
+        offset: u32,
+    ) -> Result<(Vec<AdminProfileOut>, Option<i64>), RepoError> {
+        let limit = clamp_limit(limit, "ProfileSupabaseRepo::list_all");
+        let url = format!(
+            "{}?select=user_id:id,username,full_name,role,primary_skill,skill_to_learn,is_discoverable,is_active,is_suspended,created_at&order=created_at.desc.nullslast&limit={}&offset={}",
+            self.profiles_url(),
+            limit,
+            offset
+        );
+
+        let resp = self
+            .client
+            .get(&url)
+            .headers(self.headers())
+            .header("Prefer", "count=exact")
+            .send_with_retry()
+            .await?;
+
+        let status = resp.status();
+        let total = parse_total_count(resp.headers());
+        let text = resp.text().await?;
+        if !status.is_success() {
+            return Err(RepoError::Supabase(format!(
+                "{} -> {}",
+                status.as_u16(),
+                text
+            )));
+        }
+
+        let profiles: Vec<AdminProfileOut> = serde_json::from_str(&text)?;
+        Ok((profiles, total))
+    }
+
     /// Get role value for user (returns Ok(Some(role)) or Ok(None) if not exist)
     pub async fn get_role_by_user_id(&self, user_id: Uuid) -> Result<Option<String>, RepoError> {
         let url = format!(
@@ -174,7 +473,7 @@ impl ProfileSupabaseRepo {
             .client
             .get(&url)
             .headers(self.headers())
-            .send()
+            .send_with_retry()
             .await?;
 
         let status = resp.status();
@@ -197,6 +496,66 @@ impl ProfileSupabaseRepo {
         Ok(None)
     }
 
+    /// PATCH only the given columns of the user's profile row.
+    /// Returns None when the user has no profile row yet.
+    pub async fn patch_profile(
+        &self,
+        user_id: Uuid,
+        fields: &serde_json::Map<String, Value>,
+    ) -> Result<Option<Personal>, RepoError> {
+        let url = format!(
+            "{}?id=eq.{}&select={}",
+            self.profiles_url(),
+            encode(&user_id.to_string()),
+            Self::PERSONAL_SELECT
+        );
+        self.patch_returning(&url, &Value::Object(fields.clone())).await
+    }
+
+    /// Exchange primary_skill and skill_to_learn in one PATCH. The PATCH is filtered
+    /// on the values in `current`, so a concurrent edit makes it match nothing:
+    /// Ok(None) then, and the caller should reload and retry.
+    pub async fn swap_skills(&self, current: &Personal) -> Result<Option<Personal>, RepoError> {
+        let url = format!(
+            "{}?id=eq.{}&primary_skill=eq.{}&skill_to_learn=eq.{}&select={}",
+            self.profiles_url(),
+            encode(&current.user_id.to_string()),
+            encode(&current.primary_skill),
+            encode(&current.skill_to_learn),
+            Self::PERSONAL_SELECT
+        );
+        let body = serde_json::json!({
+            "primary_skill": current.skill_to_learn,
+            "skill_to_learn": current.primary_skill,
+        });
+        self.patch_returning(&url, &body).await
+    }
+
+    /// PATCH `url` with `body` and return the first updated row, None when nothing matched
+    async fn patch_returning(&self, url: &str, body: &Value) -> Result<Option<Personal>, RepoError> {
+        let resp = self
+            .client
+            .patch(url)
+            .headers(self.headers())
+            .header("Prefer", "return=representation")
+            .json(body)
+            .send_tracked()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            return Err(RepoError::Supabase(format!(
+                "{} -> {}",
+                status.as_u16(),
+                text
+            )));
+        }
+
+        let rows: Vec<Personal> = serde_json::from_str(&text)?;
+        Ok(rows.into_iter().next())
+    }
+
     /// Delete profile by user id. Returns true when deleted (i.e. success & not 404)
     pub async fn delete_by_user_id(&self, user_id: Uuid) -> Result<bool, RepoError> {
         let url = format!("{}?id=eq.{}", self.profiles_url(), encode(&user_id.to_string()));
@@ -205,7 +564,7 @@ impl ProfileSupabaseRepo {
             .delete(&url)
             .headers(self.headers())
             .header("Prefer", "return=representation")
-            .send()
+            .send_tracked()
             .await?;
 
         let status = resp.status();
@@ -223,3 +582,35 @@ impl ProfileSupabaseRepo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use actix_web::HttpResponse;
+    use super::*;
+    use crate::test_support::mock_supabase;
+
+    #[actix_web::test]
+    async fn teacher_counts_come_from_one_grouped_query() {
+        let me = Uuid::new_v4();
+        let queries: Arc<Mutex<Vec<String>>> = Arc::default();
+        let seen = queries.clone();
+        let url = mock_supabase(move |req, _| {
+            seen.lock().unwrap().push(req.query_string().to_string());
+            HttpResponse::Ok().json(serde_json::json!([
+                { "primary_skill": "Music", "count": 4 },
+                { "primary_skill": "Cooking", "count": 1 },
+            ]))
+        });
+        let repo = ProfileSupabaseRepo::new(Client::new(), &url, "key");
+
+        let counts = repo.count_by_primary_skill(Some(me)).await.unwrap();
+
+        assert_eq!(counts.get("Music"), Some(&4));
+        assert_eq!(counts.get("Cooking"), Some(&1));
+        let queries = queries.lock().unwrap();
+        assert_eq!(queries.len(), 1);
+        assert!(queries[0].contains("select=primary_skill,count()"));
+        assert!(queries[0].contains(&format!("id=neq.{}", me)));
+    }
+}
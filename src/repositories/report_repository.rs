@@ -0,0 +1,205 @@
+// src/repositories/report_repository.rs - tabel `post_reports` via Supabase (PostgREST)
+
+use std::collections::BTreeMap;
+use reqwest::Client;
+use serde_json::json;
+use uuid::Uuid;
+use crate::dtos::report_dtos::{CreateReportDTO, PostReportOut, ReportedPostOut};
+use crate::repositories::pagination::clamp_limit;
+use crate::services::http_util::{postgrest_error_code, SendTracked, UNIQUE_VIOLATION};
+
+/// Reason yang boleh dipakai saat report post
+pub const REPORT_REASONS: &[&str] = &["spam", "harassment", "other"];
+
+/// Post id per `in.(...)` filter, supaya URL tetap pendek
+const POST_IDS_PER_DELETE: usize = 100;
+
+pub struct ReportRepository;
+
+impl ReportRepository {
+    /// Insert a report of `post_id` by `reporter_id`.
+    /// Ok(None) when this user already reported the post. The table:
+    ///
+    /// ```sql
+    /// create table post_reports (
+    ///     id uuid primary key default gen_random_uuid(),
+    ///     post_id uuid not null references posts(id) on delete cascade,
+    ///     reporter_id uuid not null references auth.users(id) on delete cascade,
+    ///     reason text not null check (reason in ('spam', 'harassment', 'other')),
+    ///     detail text,
+    ///     created_at timestamptz not null default now(),
+    ///     unique (post_id, reporter_id)
+    /// );
+    /// ```
+    pub async fn create_report(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        post_id: Uuid,
+        reporter_id: Uuid,
+        report: &CreateReportDTO,
+    ) -> Result<Option<PostReportOut>, Box<dyn std::error::Error>> {
+        let url = format!("{}/rest/v1/post_reports", supabase_url);
+
+        let payload = json!({
+            "post_id": post_id,
+            "reporter_id": reporter_id,
+            "reason": report.reason,
+            "detail": report.detail
+        });
+
+        let response = client
+            .post(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&payload)
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        // 409 + 23505 = unique (post_id, reporter_id) dilanggar: user sudah pernah report post ini.
+        // 409 lain (mis. 23503, post sudah dihapus) adalah error biasa.
+        if status == reqwest::StatusCode::CONFLICT
+            && postgrest_error_code(&body).as_deref() == Some(UNIQUE_VIOLATION)
+        {
+            return Ok(None);
+        }
+
+        if !status.is_success() {
+            return Err(format!("Failed to create post report: {} - {}", status, body).into());
+        }
+
+        let reports: Vec<PostReportOut> = serde_json::from_str(&body)?;
+        reports.into_iter().next()
+            .map(Some)
+            .ok_or_else(|| "No report returned from creation".into())
+    }
+
+    /// Delete the reports filed by `user_id` and the reports on their posts (account
+    /// deletion). Tables created before the foreign keys cascaded would otherwise
+    /// block deleting the posts and the auth user. Returns how many rows were removed.
+    pub async fn delete_reports_for_user(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        // Semua post milik user, termasuk yang soft-deleted
+        let url = format!("{}/rest/v1/posts?user_id=eq.{}&select=id", supabase_url, user_id);
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(format!("Failed to fetch posts for report cleanup: {} - {}", status, body).into());
+        }
+
+        #[derive(serde::Deserialize)]
+        struct PostId {
+            id: Uuid,
+        }
+        let post_ids: Vec<PostId> = serde_json::from_str(&body)?;
+
+        let mut filters: Vec<String> = post_ids
+            .chunks(POST_IDS_PER_DELETE)
+            .map(|chunk| {
+                let ids: Vec<String> = chunk.iter().map(|p| p.id.to_string()).collect();
+                format!("post_id=in.({})", ids.join(","))
+            })
+            .collect();
+        filters.push(format!("reporter_id=eq.{}", user_id));
+
+        let mut removed = 0;
+        for filter in filters {
+            let url = format!("{}/rest/v1/post_reports?{}&select=id", supabase_url, filter);
+            let response = client
+                .delete(&url)
+                .header("apikey", service_key)
+                .header("Authorization", format!("Bearer {}", service_key))
+                .header("Prefer", "return=representation")
+                .send_tracked()
+                .await?;
+
+            let status = response.status();
+            let body = response.text().await?;
+            if !status.is_success() {
+                return Err(format!("Failed to delete post reports: {} - {}", status, body).into());
+            }
+
+            let rows: Vec<serde_json::Value> = serde_json::from_str(&body)?;
+            removed += rows.len();
+        }
+
+        Ok(removed)
+    }
+
+    /// The most recent `limit` reports grouped by post, posts with the most reports first
+    pub async fn list_reports_by_post(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        limit: u32,
+    ) -> Result<Vec<ReportedPostOut>, Box<dyn std::error::Error>> {
+        let limit = clamp_limit(limit, "ReportRepository::list_reports_by_post");
+        let url = format!(
+            "{}/rest/v1/post_reports?select=*&order=created_at.desc&limit={}",
+            supabase_url, limit
+        );
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch post reports: {} - {}", status, body).into());
+        }
+
+        let reports: Vec<PostReportOut> = serde_json::from_str(&body)?;
+        Ok(group_by_post(reports))
+    }
+}
+
+/// Group reports (newest first) per post; ties on count go to the most recently reported post
+fn group_by_post(reports: Vec<PostReportOut>) -> Vec<ReportedPostOut> {
+    let mut groups: Vec<ReportedPostOut> = Vec::new();
+
+    for report in reports {
+        let index = match groups.iter().position(|g| g.post_id == report.post_id) {
+            Some(index) => index,
+            None => {
+                groups.push(ReportedPostOut {
+                    post_id: report.post_id,
+                    report_count: 0,
+                    reasons: BTreeMap::new(),
+                    latest_report_at: report.created_at.clone(),
+                    reports: Vec::new(),
+                });
+                groups.len() - 1
+            }
+        };
+
+        let group = &mut groups[index];
+        group.report_count += 1;
+        *group.reasons.entry(report.reason.clone()).or_insert(0) += 1;
+        group.reports.push(report);
+    }
+
+    // sort stabil: urutan "terbaru dulu" tetap untuk jumlah yang sama
+    groups.sort_by_key(|g| std::cmp::Reverse(g.report_count));
+    groups
+}
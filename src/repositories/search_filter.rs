@@ -0,0 +1,34 @@
+// src/repositories/search_filter.rs - helper untuk filter pencarian PostgREST
+/// Free text as it can safely go into a filter: characters that mean something to
+/// LIKE (`%`, `_`, `*`) or to the PostgREST filter syntax (`,`, `(`, `)`, `"`, `\`)
+/// are dropped and whitespace is collapsed. Validate the length of this, not of
+/// the raw input, since `%%` cleans to nothing.
+pub fn clean_search_term(query: &str) -> String {
+    let cleaned: String = query
+        .chars()
+        .filter(|c| !matches!(c, '%' | '_' | '*' | ',' | '(' | ')' | '"' | '\\'))
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Turn free text into a PostgREST `ilike` "contains" pattern (`*term*`), see [`clean_search_term`]
+pub fn ilike_contains(query: &str) -> String {
+    format!("*{}*", clean_search_term(query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards_and_filter_syntax_are_dropped() {
+        assert_eq!(clean_search_term("  50%_off (now),  \"deal\" "), "50off now deal");
+        assert_eq!(ilike_contains("gui tar"), "*gui tar*");
+    }
+
+    #[test]
+    fn wildcard_only_query_cleans_to_nothing() {
+        assert_eq!(clean_search_term("%%"), "");
+        assert_eq!(clean_search_term("*_ ()"), "");
+    }
+}
@@ -0,0 +1,134 @@
+// src/repositories/skill_repository.rs - tabel `skills` (taksonomi skill) via Supabase (PostgREST)
+
+use chrono::{SecondsFormat, Utc};
+use reqwest::Client;
+use serde_json::json;
+use urlencoding::encode;
+use crate::dtos::skill_dtos::{SkillIn, SkillOut};
+use crate::models::personal::set_active_skills;
+use crate::services::http_util::SendTracked;
+
+pub struct SkillRepository;
+
+impl SkillRepository {
+    /// All rows of the `skills` table ordered by name; only active ones with `active_only`
+    pub async fn list_skills(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        active_only: bool,
+    ) -> Result<Vec<SkillOut>, Box<dyn std::error::Error>> {
+        let mut url = format!(
+            "{}/rest/v1/skills?select=name,active,updated_at&order=name.asc",
+            supabase_url
+        );
+        if active_only {
+            url.push_str("&active=eq.true");
+        }
+
+        let response = client
+            .get(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .send_with_retry()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to list skills: {} - {}", status, body).into());
+        }
+
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Read the active skills and make them the list that skill validation uses.
+    /// Returns the names; with an empty table validation falls back to VALID_SKILLS.
+    pub async fn refresh_active_skills(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let names: Vec<String> = Self::list_skills(supabase_url, service_key, client, true)
+            .await?
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        set_active_skills(names.clone());
+        Ok(names)
+    }
+
+    /// Insert the skills, or update `active` for names that already exist
+    pub async fn upsert_skills(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        skills: &[SkillIn],
+    ) -> Result<Vec<SkillOut>, Box<dyn std::error::Error>> {
+        let url = format!("{}/rest/v1/skills?on_conflict=name", supabase_url);
+
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let payload: Vec<serde_json::Value> = skills
+            .iter()
+            .map(|s| json!({ "name": s.name, "active": s.active, "updated_at": now }))
+            .collect();
+
+        log::debug!("Upserting {} skills", payload.len());
+
+        let response = client
+            .post(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=merge-duplicates,return=representation")
+            .json(&payload)
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to upsert skills: {} - {}", status, body).into());
+        }
+
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Mark a skill inactive (rows are never deleted, profiles may still use the name).
+    /// Ok(None) when no skill has that name.
+    pub async fn disable_skill(
+        supabase_url: &str,
+        service_key: &str,
+        client: &Client,
+        name: &str,
+    ) -> Result<Option<SkillOut>, Box<dyn std::error::Error>> {
+        let url = format!("{}/rest/v1/skills?name=eq.{}", supabase_url, encode(name));
+
+        let payload = json!({
+            "active": false,
+            "updated_at": Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+        });
+
+        let response = client
+            .patch(&url)
+            .header("apikey", service_key)
+            .header("Authorization", format!("Bearer {}", service_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&payload)
+            .send_tracked()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("Failed to disable skill: {} - {}", status, body).into());
+        }
+
+        let skills: Vec<SkillOut> = serde_json::from_str(&body)?;
+        Ok(skills.into_iter().next())
+    }
+}
@@ -1,4 +1,5 @@
 // src/services/auth_services.rs - Fixed version
+use std::collections::HashSet;
 use std::env;
 use chrono::NaiveDate;
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
@@ -9,11 +10,18 @@ use uuid::Uuid;
 
 use crate::dtos::auth::{SignupIn, LoginIn, SessionOut};
 use crate::dtos::personal::{CreatePersonalDTO, PersonalDataOut};
+use crate::models::personal::age_from_iso_date;
+use crate::repositories::pagination::parse_total_count;
+use crate::services::http_util::{build_supabase_client, is_unreachable, SendError, SendTracked};
+use crate::services::match_index::{MatchIndex, MatchIndexCache};
 
 #[derive(Debug, Error)]
 pub enum AuthError {
     #[error("http error: {0}")]
-    Http(#[from] reqwest::Error),
+    Http(reqwest::Error),
+    /// Supabase couldn't be reached (connect error or timeout, after retries for GETs)
+    #[error("supabase unavailable: {0}")]
+    Unavailable(String),
     #[error("supabase error: {0}")]
     Supabase(String),
     #[error("invalid token")]
@@ -26,6 +34,111 @@ pub enum AuthError {
     ProfileNotFound,
     #[error("other: {0}")]
     Other(String),
+    #[error("configuration error: {0}")]
+    ConfigurationError(String),
+    #[error("email not confirmed")]
+    EmailNotConfirmed,
+}
+
+impl From<SendError> for AuthError {
+    fn from(e: SendError) -> Self {
+        match e {
+            SendError::Http(e) => e.into(),
+            SendError::CircuitOpen => AuthError::Unavailable(e.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for AuthError {
+    fn from(e: reqwest::Error) -> Self {
+        if is_unreachable(&e) {
+            AuthError::Unavailable(e.to_string())
+        } else {
+            AuthError::Http(e)
+        }
+    }
+}
+
+/// True when a Supabase auth error body says the email isn't confirmed yet.
+/// Newer GoTrue sends `error_code: "email_not_confirmed"`, older versions only
+/// `error_description: "Email not confirmed"`.
+fn is_email_not_confirmed(body: &str) -> bool {
+    let Ok(v) = serde_json::from_str::<serde_json::Value>(body) else {
+        return false;
+    };
+    if v.get("error_code").and_then(|c| c.as_str()) == Some("email_not_confirmed") {
+        return true;
+    }
+    ["msg", "error_description"].iter().any(|key| {
+        v.get(*key)
+            .and_then(|m| m.as_str())
+            .is_some_and(|m| m.to_lowercase().contains("email not confirmed"))
+    })
+}
+
+/// Quote a value for a PostgREST logic tree (`or=(...)`, `and=(...)`), where commas,
+/// dots and parentheses would otherwise be read as syntax
+fn postgrest_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Auth user id of a profile-like row. `personals` has its own `user_id` column;
+/// in `profiles` the primary key `id` is the auth user id, so fall back to it.
+fn row_user_id(row: &serde_json::Value) -> Result<Uuid, AuthError> {
+    let raw = row
+        .get("user_id")
+        .and_then(|v| v.as_str())
+        .or_else(|| row.get("id").and_then(|v| v.as_str()))
+        .ok_or_else(|| AuthError::Supabase("missing user id in profile row".into()))?;
+    Ok(Uuid::parse_str(raw)?)
+}
+
+/// Build PersonalDataOut from a `profiles` row (select=*)
+fn personal_from_row(row: &serde_json::Value) -> Result<PersonalDataOut, AuthError> {
+    let id = Uuid::parse_str(
+        row.get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| AuthError::Supabase("missing id in profile row".into()))?,
+    )?;
+    let text = |key: &str| row.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+
+    Ok(PersonalDataOut {
+        id,
+        user_id: row_user_id(row)?,
+        full_name: row.get("full_name").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        date_of_birth: row.get("date_of_birth").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        age: age_from_iso_date(row.get("date_of_birth").and_then(|v| v.as_str())),
+        primary_skill: text("primary_skill"),
+        skill_to_learn: text("skill_to_learn"),
+        bio: text("bio"),
+        profile_picture_url: row
+            .get("profile_picture_url")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        is_discoverable: row.get("is_discoverable").and_then(|v| v.as_bool()).unwrap_or(true),
+        is_active: row.get("is_active").and_then(|v| v.as_bool()).unwrap_or(true),
+        created_at: row.get("created_at").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        updated_at: row.get("updated_at").and_then(|v| v.as_str()).map(|s| s.to_string()),
+    })
+}
+
+/// Error for a failed request made with the service role key. 401/403 there almost
+/// always means SUPABASE_SERVICE_ROLE_KEY holds the wrong key (e.g. the anon key),
+/// so it's reported as ConfigurationError instead of a generic Supabase error.
+fn service_role_error(operation: &str, status: StatusCode, body: &str) -> AuthError {
+    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+        log::error!(
+            "{} rejected with {} using the service role key: check SUPABASE_SERVICE_ROLE_KEY",
+            operation,
+            status
+        );
+        return AuthError::ConfigurationError(format!(
+            "{} was rejected with {}. SUPABASE_SERVICE_ROLE_KEY may be wrong (is it the anon key?)",
+            operation, status
+        ));
+    }
+
+    AuthError::Supabase(format!("{} failed: {} {}", operation, status, body))
 }
 
 #[derive(Clone)]
@@ -34,30 +147,113 @@ pub struct AuthService {
     pub supabase_url: String,
     pub supabase_anon_key: String,
     pub supabase_service_role_key: String,
+    /// BOOTSTRAP_ADMIN_EMAIL: akun dengan email ini dipromosikan jadi "admin" saat melengkapi profile
+    pub bootstrap_admin_email: Option<String>,
+    /// Domain email sekali pakai yang ditolak saat signup (None = fitur mati)
+    pub disposable_email_domains: Option<HashSet<String>>,
+    /// Indeks skill untuk kandidat match (MATCH_INDEX_REFRESH_SECS), kosong = query langsung
+    pub match_index: MatchIndexCache,
+}
+
+/// Load the disposable-domain blocklist when BLOCK_DISPOSABLE_EMAILS is enabled.
+/// Domains come from DISPOSABLE_EMAIL_DOMAINS_FILE (one per line, `#` comments)
+/// and/or DISPOSABLE_EMAIL_DOMAINS (comma separated).
+fn load_disposable_email_domains() -> Option<HashSet<String>> {
+    let enabled = env::var("BLOCK_DISPOSABLE_EMAILS")
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    if !enabled {
+        return None;
+    }
+
+    let mut domains = HashSet::new();
+    let mut add = |raw: &str| {
+        let domain = raw.trim().trim_end_matches('.').to_lowercase();
+        if !domain.is_empty() && !domain.starts_with('#') {
+            domains.insert(domain);
+        }
+    };
+
+    if let Ok(path) = env::var("DISPOSABLE_EMAIL_DOMAINS_FILE") {
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => contents.lines().for_each(&mut add),
+            Err(e) => log::warn!("Failed to read DISPOSABLE_EMAIL_DOMAINS_FILE {}: {}", path, e),
+        }
+    }
+    if let Ok(list) = env::var("DISPOSABLE_EMAIL_DOMAINS") {
+        list.split(',').for_each(&mut add);
+    }
+
+    if domains.is_empty() {
+        log::warn!("BLOCK_DISPOSABLE_EMAILS is enabled but no disposable domains were loaded");
+    } else {
+        log::info!("Loaded {} disposable email domains", domains.len());
+    }
+    Some(domains)
 }
 
 impl AuthService {
-    pub fn new_from_env() -> Self {
-        let supabase_url = env::var("SUPABASE_URL")
-            .expect("SUPABASE_URL is required")
-            .trim()
-            .to_string();
+    /// Service using `client` (main.rs passes the one shared with AppState).
+    /// Optional settings (BOOTSTRAP_ADMIN_EMAIL, disposable-email blocking) still come from env.
+    pub fn new(
+        client: reqwest::Client,
+        supabase_url: impl Into<String>,
+        supabase_anon_key: impl Into<String>,
+        supabase_service_role_key: impl Into<String>,
+    ) -> Self {
+        let bootstrap_admin_email = env::var("BOOTSTRAP_ADMIN_EMAIL")
+            .ok()
+            .map(|e| e.trim().to_lowercase())
+            .filter(|e| !e.is_empty());
 
-        let supabase_anon_key = env::var("SUPABASE_ANON_KEY")
-            .unwrap_or_default()
-            .trim()
-            .to_string();
+        Self {
+            client,
+            supabase_url: supabase_url.into().trim().to_string(),
+            supabase_anon_key: supabase_anon_key.into().trim().to_string(),
+            supabase_service_role_key: supabase_service_role_key.into().trim().to_string(),
+            bootstrap_admin_email,
+            disposable_email_domains: load_disposable_email_domains(),
+            match_index: MatchIndexCache::from_env(),
+        }
+    }
 
+    /// Standalone service (own client) from SUPABASE_URL, SUPABASE_ANON_KEY and
+    /// SUPABASE_SERVICE_ROLE_KEY; the app itself uses [`AuthService::new`]
+    #[allow(dead_code)]
+    pub fn new_from_env() -> Self {
+        let supabase_url = env::var("SUPABASE_URL").expect("SUPABASE_URL is required");
+        let supabase_anon_key = env::var("SUPABASE_ANON_KEY").unwrap_or_default();
         let supabase_service_role_key = env::var("SUPABASE_SERVICE_ROLE_KEY")
-            .expect("SUPABASE_SERVICE_ROLE_KEY required")
-            .trim()
-            .to_string();
+            .expect("SUPABASE_SERVICE_ROLE_KEY required");
 
-        Self {
-            client: reqwest::Client::new(),
+        Self::new(
+            build_supabase_client(),
             supabase_url,
             supabase_anon_key,
             supabase_service_role_key,
+        )
+    }
+
+    /// True when blocking is enabled and the email's domain, or any parent
+    /// domain of it (`x.mailinator.com` -> `mailinator.com`), is blocklisted
+    pub fn is_disposable_email(&self, email: &str) -> bool {
+        let Some(ref blocked) = self.disposable_email_domains else {
+            return false;
+        };
+        let Some((_, domain)) = email.trim().rsplit_once('@') else {
+            return false;
+        };
+
+        let domain = domain.trim_end_matches('.').to_lowercase();
+        let mut candidate = domain.as_str();
+        loop {
+            if blocked.contains(candidate) {
+                return true;
+            }
+            match candidate.split_once('.') {
+                Some((_, parent)) if parent.contains('.') => candidate = parent,
+                _ => return false,
+            }
         }
     }
 
@@ -82,7 +278,7 @@ impl AuthService {
             .header("apikey", &self.supabase_anon_key)
             .header("Content-Type", "application/json")
             .json(&body)
-            .send()
+            .send_tracked()
             .await?;
 
         let status = resp.status();
@@ -111,22 +307,118 @@ impl AuthService {
         Ok(user_id)
     }
 
+    /// Owner of `username`, compared case-insensitively ("Alice" == "alice")
+    pub async fn username_owner(&self, username: &str) -> Result<Option<Uuid>, AuthError> {
+        // ilike tanpa wildcard = sama persis tapi case-insensitive; `%`, `_` dan `\` di-escape
+        let mut pattern = String::with_capacity(username.len());
+        for c in username.trim().chars() {
+            if matches!(c, '%' | '_' | '\\') {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+
+        let url = format!(
+            "{}/rest/v1/profiles?username=ilike.{}&select=id&limit=1",
+            self.supabase_url.trim_end_matches('/'),
+            urlencoding::encode(&pattern)
+        );
+
+        let resp = self
+            .client
+            .get(&url)
+            .header("apikey", &self.supabase_service_role_key)
+            .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
+            .send_with_retry()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+
+        if !status.is_success() {
+            return Err(service_role_error("username lookup", status, &text));
+        }
+
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&text)
+            .map_err(|e| AuthError::Supabase(format!("invalid json: {}", e)))?;
+
+        match rows.first().and_then(|r| r.get("id")).and_then(|v| v.as_str()) {
+            Some(id) => Ok(Some(Uuid::parse_str(id)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn is_username_taken(&self, username: &str) -> Result<bool, AuthError> {
+        Ok(self.username_owner(username).await?.is_some())
+    }
+
+    /// Create the user's `profiles` row with the username/full_name given at signup.
+    /// An existing row is left untouched (ignore-duplicates), so a repeated signup
+    /// never overwrites a username that is already stored.
+    pub async fn init_profile_username(
+        &self,
+        user_id: Uuid,
+        username: Option<&str>,
+        full_name: Option<&str>,
+    ) -> Result<(), AuthError> {
+        #[derive(Serialize)]
+        struct Payload<'a> {
+            id: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            username: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            full_name: Option<&'a str>,
+            role: &'a str,
+        }
+
+        let username = username.map(str::trim).filter(|s| !s.is_empty());
+        let full_name = full_name.map(str::trim).filter(|s| !s.is_empty());
+        if username.is_none() && full_name.is_none() {
+            return Ok(());
+        }
+
+        let url = format!("{}/rest/v1/profiles", self.supabase_url.trim_end_matches('/'));
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("apikey", &self.supabase_service_role_key)
+            .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=ignore-duplicates,return=minimal")
+            .json(&Payload {
+                id: user_id.to_string(),
+                username,
+                full_name,
+                role: "user",
+            })
+            .send_tracked()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(service_role_error("init_profile_username", status, &text));
+        }
+
+        Ok(())
+    }
+
     /// Update profile picture URL for user - CRITICAL METHOD
     pub async fn update_profile_picture(
         &self,
         user_id: Uuid,
         profile_picture_url: Option<String>,
+        thumbnail_url: Option<String>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let url = format!("{}/rest/v1/profiles", self.supabase_url);
         
         let update_data = serde_json::json!({
-            "profile_picture_url": profile_picture_url
+            "profile_picture_url": profile_picture_url,
+            "thumbnail_url": thumbnail_url
         });
 
-        println!("=== UPDATE PROFILE PICTURE DATABASE ===");
-        println!("User ID: {}", user_id);
-        println!("URL: {}", url);
-        println!("Update data: {}", update_data);
+        log::debug!("Updating profile picture of user {}: {}", user_id, update_data);
 
         let response = self.client
             .patch(&url)
@@ -137,23 +429,23 @@ impl AuthService {
             // CRITICAL: Use 'id' not 'user_id' for profiles table
             .query(&[("id", format!("eq.{}", user_id))])
             .json(&update_data)
-            .send()
+            .send_tracked()
             .await?;
 
         let status = response.status();
-        println!("Database update response status: {}", status);
-
+        
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            println!("Database update error: {}", error_text);
-            return Err(format!("Failed to update profile picture: {} - {}", status, error_text).into());
+            log::debug!("Profile picture update failed: {} {}", status, error_text);
+            return Err(Box::new(service_role_error("update_profile_picture", status, &error_text)));
         }
 
-        println!("Profile picture URL updated in database successfully!");
+        log::debug!("Profile picture of user {} updated", user_id);
         Ok(())
     }
 
-    /// Get user profile with profile picture
+    /// Get user profile with profile picture.
+    /// Reads the legacy `personals` table, where `id` is the row id and `user_id` the auth user.
     pub async fn get_user_profile_with_picture(
         &self,
         user_id: Uuid,
@@ -165,7 +457,7 @@ impl AuthService {
             .header("apikey", &self.supabase_anon_key)
             .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
             .query(&[("user_id", format!("eq.{}", user_id)), ("select", "*".to_string())])  // FIXED: convert to String
-            .send()
+            .send_with_retry()
             .await?;
 
         if !response.status().is_success() {
@@ -178,11 +470,17 @@ impl AuthService {
             let profile_out = crate::dtos::personal::PersonalDataOut {
                 id: serde_json::from_value(profile_data["id"].clone())?,
                 user_id: serde_json::from_value(profile_data["user_id"].clone())?,
-                date_of_birth: profile_data["date_of_birth"].as_str().unwrap_or("").to_string(),
+                full_name: profile_data["full_name"].as_str().map(|s| s.to_string()),
+                date_of_birth: profile_data["date_of_birth"].as_str().map(|s| s.to_string()),
+                age: age_from_iso_date(profile_data["date_of_birth"].as_str()),
                 primary_skill: profile_data["primary_skill"].as_str().unwrap_or("").to_string(),
                 skill_to_learn: profile_data["skill_to_learn"].as_str().unwrap_or("").to_string(),
                 bio: profile_data["bio"].as_str().unwrap_or("").to_string(),
                 profile_picture_url: profile_data["profile_picture_url"].as_str().map(|s| s.to_string()),
+                is_discoverable: profile_data["is_discoverable"].as_bool().unwrap_or(true),
+                is_active: profile_data["is_active"].as_bool().unwrap_or(true),
+                created_at: profile_data["created_at"].as_str().map(|s| s.to_string()),
+                updated_at: profile_data["updated_at"].as_str().map(|s| s.to_string()),
             };
             Ok(Some(profile_out))
         } else {
@@ -228,12 +526,17 @@ impl AuthService {
             .header("apikey", &self.supabase_anon_key)
             .header("Content-Type", "application/json")
             .json(&body)
-            .send()
+            .send_tracked()
             .await?;
 
         let status = resp.status();
         let txt = resp.text().await.unwrap_or_default();
 
+        // Project dengan email confirmation: akun ada tapi belum dikonfirmasi
+        if status == StatusCode::BAD_REQUEST && is_email_not_confirmed(&txt) {
+            return Err(AuthError::EmailNotConfirmed);
+        }
+
         if status != StatusCode::OK {
             return Err(AuthError::Supabase(format!(
                 "login failed: {} {}",
@@ -246,14 +549,20 @@ impl AuthService {
             .map_err(|e| AuthError::Supabase(format!("invalid json in login response: {}", e)))?;
 
         // Extract user_id from login response instead of JWT
+        // Identity yang tidak valid adalah masalah auth (401), bukan error server
         let user_id = if let Some(user) = tr.user {
-            Uuid::parse_str(&user.id)?
+            Uuid::parse_str(&user.id).map_err(|_| AuthError::InvalidToken)?
         } else {
             return Err(AuthError::Supabase("No user info in login response".to_string()));
         };
 
+        if tr.refresh_token.is_none() {
+            log::warn!("Login for {} returned no refresh_token; session cannot be refreshed", user_id);
+        }
+
         let session = SessionOut {
             access_token: tr.access_token,
+            refreshable: tr.refresh_token.is_some(),
             refresh_token: tr.refresh_token,
             expires_in: tr.expires_in,
             token_type: tr.token_type,
@@ -262,12 +571,269 @@ impl AuthService {
         Ok((session, user_id))
     }
 
+    /// Exchange a refresh token for a new session (`grant_type=refresh_token`).
+    /// Supabase answers 400 for an invalid, expired or already used refresh token.
+    pub async fn refresh_session(&self, refresh_token: &str) -> Result<SessionOut, AuthError> {
+        #[derive(Serialize)]
+        struct RefreshBody<'a> {
+            refresh_token: &'a str,
+        }
+
+        #[derive(Deserialize)]
+        struct TokenResp {
+            access_token: String,
+            refresh_token: Option<String>,
+            expires_in: Option<i64>,
+            token_type: Option<String>,
+        }
+
+        let url = format!(
+            "{}/auth/v1/token?grant_type=refresh_token",
+            self.supabase_url.trim_end_matches('/')
+        );
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("apikey", &self.supabase_anon_key)
+            .header("Content-Type", "application/json")
+            .json(&RefreshBody { refresh_token })
+            .send_tracked()
+            .await?;
+
+        let status = resp.status();
+        let txt = resp.text().await.unwrap_or_default();
+
+        if status == StatusCode::BAD_REQUEST || status == StatusCode::UNAUTHORIZED {
+            return Err(AuthError::InvalidToken);
+        }
+
+        if status != StatusCode::OK {
+            return Err(AuthError::Supabase(format!(
+                "refresh failed: {} {}",
+                status,
+                txt
+            )));
+        }
+
+        let tr: TokenResp = serde_json::from_str(&txt)
+            .map_err(|e| AuthError::Supabase(format!("invalid json in refresh response: {}", e)))?;
+
+        Ok(SessionOut {
+            access_token: tr.access_token,
+            refreshable: tr.refresh_token.is_some(),
+            refresh_token: tr.refresh_token,
+            expires_in: tr.expires_in,
+            token_type: tr.token_type,
+        })
+    }
+
+    /// Ask Supabase to email a password recovery link (`/auth/v1/recover`).
+    /// PASSWORD_RESET_REDIRECT_URL, when set, is where the link sends the user.
+    pub async fn send_recovery_email(&self, email: &str) -> Result<(), AuthError> {
+        #[derive(Serialize)]
+        struct RecoverBody<'a> {
+            email: &'a str,
+        }
+
+        let mut url = format!("{}/auth/v1/recover", self.supabase_url.trim_end_matches('/'));
+        if let Ok(redirect) = env::var("PASSWORD_RESET_REDIRECT_URL")
+            && !redirect.trim().is_empty()
+        {
+            url.push_str(&format!("?redirect_to={}", urlencoding::encode(redirect.trim())));
+        }
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("apikey", &self.supabase_anon_key)
+            .header("Content-Type", "application/json")
+            .json(&RecoverBody { email })
+            .send_tracked()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(AuthError::Supabase(format!(
+                "recover failed: {} {}",
+                status,
+                text
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Ask Supabase to send the signup confirmation email again (`/auth/v1/resend`).
+    /// SIGNUP_REDIRECT_URL, when set, is where the confirmation link sends the user.
+    pub async fn resend_confirmation(&self, email: &str) -> Result<(), AuthError> {
+        #[derive(Serialize)]
+        struct ResendBody<'a> {
+            #[serde(rename = "type")]
+            kind: &'a str,
+            email: &'a str,
+        }
+
+        let mut url = format!("{}/auth/v1/resend", self.supabase_url.trim_end_matches('/'));
+        if let Ok(redirect) = env::var("SIGNUP_REDIRECT_URL")
+            && !redirect.trim().is_empty()
+        {
+            url.push_str(&format!("?redirect_to={}", urlencoding::encode(redirect.trim())));
+        }
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("apikey", &self.supabase_anon_key)
+            .header("Content-Type", "application/json")
+            .json(&ResendBody { kind: "signup", email })
+            .send_tracked()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(AuthError::Supabase(format!(
+                "resend confirmation failed: {} {}",
+                status,
+                text
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Set a new password for the user owning the recovery `access_token` (PUT `/auth/v1/user`)
+    /// and return that user's id.
+    /// An invalid or expired token -> InvalidToken; a password Supabase refuses -> Other(message).
+    pub async fn update_password_with_token(
+        &self,
+        access_token: &str,
+        new_password: &str,
+    ) -> Result<Uuid, AuthError> {
+        #[derive(Serialize)]
+        struct PasswordBody<'a> {
+            password: &'a str,
+        }
+
+        let url = format!("{}/auth/v1/user", self.supabase_url.trim_end_matches('/'));
+
+        let resp = self
+            .client
+            .put(&url)
+            .header("apikey", &self.supabase_anon_key)
+            .header("Authorization", format!("Bearer {}", access_token))
+            .header("Content-Type", "application/json")
+            .json(&PasswordBody { password: new_password })
+            .send_tracked()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+            return Err(AuthError::InvalidToken);
+        }
+
+        if status == StatusCode::BAD_REQUEST || status == StatusCode::UNPROCESSABLE_ENTITY {
+            let msg = serde_json::from_str::<serde_json::Value>(&text)
+                .ok()
+                .and_then(|v| {
+                    v.get("msg")
+                        .or_else(|| v.get("error_description"))
+                        .and_then(|m| m.as_str())
+                        .map(|m| m.to_string())
+                })
+                .unwrap_or_else(|| "Password could not be updated".to_string());
+            return Err(AuthError::Other(msg));
+        }
+
+        if !status.is_success() {
+            return Err(AuthError::Supabase(format!(
+                "update password failed: {} {}",
+                status,
+                text
+            )));
+        }
+
+        serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .and_then(|user| user.get("id")?.as_str().and_then(|id| Uuid::parse_str(id).ok()))
+            .ok_or_else(|| AuthError::Supabase("update password: response has no user id".to_string()))
+    }
+
+    /// End every session of the user (`POST /auth/v1/admin/users/{id}/logout`, service role).
+    /// Their refresh tokens stop working at once; access tokens already issued stay valid
+    /// until they expire, so clients must log in again.
+    pub async fn sign_out_all_sessions(&self, user_id: Uuid) -> Result<(), AuthError> {
+        let url = format!(
+            "{}/auth/v1/admin/users/{}/logout",
+            self.supabase_url.trim_end_matches('/'),
+            user_id
+        );
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("apikey", &self.supabase_service_role_key)
+            .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
+            .send_tracked()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(service_role_error("sign out sessions", status, &text));
+        }
+
+        Ok(())
+    }
+
+    /// Fetch the email of the user owning `access_token` from Supabase (`/auth/v1/user`)
+    pub async fn get_auth_user_email(&self, access_token: &str) -> Result<Option<String>, AuthError> {
+        let url = format!("{}/auth/v1/user", self.supabase_url.trim_end_matches('/'));
+
+        let resp = self
+            .client
+            .get(&url)
+            .header("apikey", &self.supabase_anon_key)
+            .header("Authorization", format!("Bearer {}", access_token))
+            .send_with_retry()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+            return Err(AuthError::InvalidToken);
+        }
+
+        if !status.is_success() {
+            return Err(AuthError::Supabase(format!(
+                "get_auth_user_email failed: {} {}",
+                status,
+                text
+            )));
+        }
+
+        let json_val: serde_json::Value = serde_json::from_str(&text)
+            .map_err(|e| AuthError::Supabase(format!("invalid json: {}", e)))?;
+
+        Ok(json_val
+            .get("email")
+            .and_then(|v| v.as_str())
+            .filter(|e| !e.is_empty())
+            .map(|e| e.to_string()))
+    }
+
     // Keep the old method for compatibility, but use the new one internally
     pub async fn login_sb(&self, input: LoginIn) -> Result<SessionOut, AuthError> {
         let (session, _user_id) = self.login_with_user_id(input).await?;
         Ok(session)
     }
 
+    /// Create or update the caller's row in `profiles` (id = auth user id)
     pub async fn add_personal_sb(
         &self,
         user_id: Uuid,
@@ -280,16 +846,27 @@ impl AuthService {
             primary_skill: &'a str,
             skill_to_learn: &'a str,
             bio: &'a str,
-            role: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            role: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            is_discoverable: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            full_name: Option<&'a str>,
         }
 
+        // Role hanya di-set untuk row baru: upsert merge-duplicates akan menimpa
+        // role yang sudah ada (mis. "admin") kalau field ini selalu dikirim
+        let existing_role = self.get_role(user_id).await?;
+
         let payload = Payload {
             id: &user_id.to_string(),
             date_of_birth: &dto.date_of_birth,
             primary_skill: &dto.primary_skill,
             skill_to_learn: &dto.skill_to_learn,
             bio: &dto.bio,
-            role: "user",
+            role: if existing_role.is_none() { Some("user") } else { None },
+            is_discoverable: dto.is_discoverable,
+            full_name: dto.full_name.as_deref(),
         };
 
         let url = format!("{}/rest/v1/profiles", self.supabase_url.trim_end_matches('/'));
@@ -304,18 +881,14 @@ impl AuthService {
         .header("Content-Type", "application/json")
         .header("Prefer", "resolution=merge-duplicates,return=representation")
         .json(&payload)
-        .send()
+        .send_tracked()
         .await?;
 
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
 
         if !status.is_success() {
-            return Err(AuthError::Supabase(format!(
-                "add_personal failed: {} - Response: {}",
-                status,
-                text
-            )));
+            return Err(service_role_error("add_personal", status, &text));
         }
 
         let arr: serde_json::Value = serde_json::from_str(&text)
@@ -326,44 +899,11 @@ impl AuthService {
             .and_then(|a| a.get(0))
             .ok_or_else(|| AuthError::Supabase(format!("invalid response from profiles upsert: {}", text)))?;
 
-        let out = PersonalDataOut {
-            id: Uuid::parse_str(first.get("id").and_then(|v| v.as_str()).ok_or_else(|| {
-                AuthError::Supabase("missing id in upsert response".into())
-            })?)?,
-            user_id: Uuid::parse_str(
-                first.get("id")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| AuthError::Supabase("missing user id".into()))?,
-            )?,
-            date_of_birth: first
-                .get("date_of_birth")
-                .and_then(|v| v.as_str())
-                .unwrap_or_default()
-                .to_string(),
-            primary_skill: first
-                .get("primary_skill")
-                .and_then(|v| v.as_str())
-                .unwrap_or_default()
-                .to_string(),
-            skill_to_learn: first
-                .get("skill_to_learn")
-                .and_then(|v| v.as_str())
-                .unwrap_or_default()
-                .to_string(),
-            bio: first
-                .get("bio")
-                .and_then(|v| v.as_str())
-                .unwrap_or_default()
-                .to_string(),
-            profile_picture_url: first  // FIXED: Add profile_picture_url field
-                .get("profile_picture_url")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string()),
-        };
-
-        Ok(out)
+        personal_from_row(first)
     }
 
+    /// Completed profile of `user_id` from the `profiles` table (id = auth user id).
+    /// None when there is no row or only the signup stub without skills.
     pub async fn get_user_profile(&self, user_id: Uuid) -> Result<Option<PersonalDataOut>, AuthError> {
         let url = format!(
             "{}/rest/v1/profiles?id=eq.{}&select=*",
@@ -376,18 +916,14 @@ impl AuthService {
             .get(&url)
             .header("apikey", &self.supabase_anon_key)
             .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
-            .send()
+            .send_with_retry()
             .await?;
 
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
 
         if !status.is_success() {
-            return Err(AuthError::Supabase(format!(
-                "get_user_profile failed: {} {}",
-                status,
-                text
-            )));
+            return Err(service_role_error("get_user_profile", status, &text));
         }
 
         let arr: serde_json::Value =
@@ -402,46 +938,25 @@ impl AuthService {
         }
 
         let profile = &profiles[0];
-        
-        let out = PersonalDataOut {
-            id: Uuid::parse_str(profile.get("id").and_then(|v| v.as_str()).ok_or_else(|| {
-                AuthError::Supabase("missing id in profile response".into())
-            })?)?,
-            user_id: Uuid::parse_str(
-                profile.get("id")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| AuthError::Supabase("missing user id".into()))?,
-            )?,
-            date_of_birth: profile
-                .get("date_of_birth")
-                .and_then(|v| v.as_str())
-                .unwrap_or_default()
-                .to_string(),
-            primary_skill: profile
-                .get("primary_skill")
-                .and_then(|v| v.as_str())
-                .unwrap_or_default()
-                .to_string(),
-            skill_to_learn: profile
-                .get("skill_to_learn")
-                .and_then(|v| v.as_str())
-                .unwrap_or_default()
-                .to_string(),
-            bio: profile
-                .get("bio")
-                .and_then(|v| v.as_str())
-                .unwrap_or_default()
-                .to_string(),
-            profile_picture_url: profile  // FIXED: Add profile_picture_url field
-                .get("profile_picture_url")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string()),
-        };
 
-        Ok(Some(out))
+        // Row yang dibuat saat signup (hanya username/full_name) belum dihitung sebagai profile
+        if profile
+            .get("primary_skill")
+            .and_then(|v| v.as_str())
+            .is_none_or(|s| s.trim().is_empty())
+        {
+            return Ok(None);
+        }
+        
+        personal_from_row(profile).map(Some)
     }
 
     pub async fn is_role_user(&self, user_id: Uuid) -> Result<bool, AuthError> {
+        Ok(self.get_role(user_id).await?.as_deref() == Some("user"))
+    }
+
+    /// Role of the user's profile row; None when there is no row or no role set
+    pub async fn get_role(&self, user_id: Uuid) -> Result<Option<String>, AuthError> {
         let url = format!(
             "{}/rest/v1/profiles?id=eq.{}&select=role",
             self.supabase_url.trim_end_matches('/'),
@@ -453,18 +968,14 @@ impl AuthService {
             .get(&url)
             .header("apikey", &self.supabase_anon_key)
             .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
-            .send()
+            .send_with_retry()
             .await?;
 
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
 
         if !status.is_success() {
-            return Err(AuthError::Supabase(format!(
-                "is_role_user failed: {} {}",
-                status,
-                text
-            )));
+            return Err(service_role_error("get_role", status, &text));
         }
 
         let arr: serde_json::Value =
@@ -472,11 +983,439 @@ impl AuthService {
 
         let role = arr
             .as_array()
-            .and_then(|a| a.get(0))
+            .and_then(|a| a.first())
             .and_then(|v| v.get("role"))
             .and_then(|r| r.as_str())
-            .unwrap_or("");
+            .map(|r| r.to_string());
+
+        Ok(role)
+    }
+
+    /// Promote the account matching BOOTSTRAP_ADMIN_EMAIL to "admin".
+    /// Only a profile whose role is still "user" is promoted, so this happens once.
+    /// Returns true when the role was changed.
+    pub async fn bootstrap_admin_if_configured(
+        &self,
+        user_id: Uuid,
+        email: &str,
+    ) -> Result<bool, AuthError> {
+        let Some(ref admin_email) = self.bootstrap_admin_email else {
+            return Ok(false);
+        };
+
+        if email.trim().to_lowercase() != *admin_email || !self.is_role_user(user_id).await? {
+            return Ok(false);
+        }
+
+        let url = format!(
+            "{}/rest/v1/profiles?id=eq.{}",
+            self.supabase_url.trim_end_matches('/'),
+            user_id
+        );
+
+        let resp = self
+            .client
+            .patch(&url)
+            .header("apikey", &self.supabase_service_role_key)
+            .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=minimal")
+            .json(&serde_json::json!({ "role": "admin" }))
+            .send_tracked()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(service_role_error("bootstrap_admin", status, &text));
+        }
+
+        log::info!("Bootstrap admin: promoted user {} ({}) to admin", user_id, admin_email);
+        Ok(true)
+    }
+
+    /// One page of discoverable, active profiles other than `exclude_user_id` that
+    /// match the PostgREST `filter`, with the total number of matching rows
+    async fn profiles_page(
+        &self,
+        filter: &str,
+        exclude_user_id: Uuid,
+        limit: u32,
+        offset: u32,
+    ) -> Result<(Vec<PersonalDataOut>, Option<i64>), AuthError> {
+        let url = format!(
+            "{}/rest/v1/profiles?{}&id=neq.{}&is_discoverable=not.is.false&is_active=not.is.false&select=*&order=updated_at.desc.nullslast,id&limit={}&offset={}",
+            self.supabase_url.trim_end_matches('/'),
+            filter,
+            exclude_user_id,
+            limit,
+            offset
+        );
+
+        let resp = self
+            .client
+            .get(&url)
+            .header("apikey", &self.supabase_service_role_key)
+            .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
+            .header("Prefer", "count=exact")
+            .send_with_retry()
+            .await?;
+
+        let status = resp.status();
+        let total = parse_total_count(resp.headers());
+        let text = resp.text().await.unwrap_or_default();
+
+        if !status.is_success() {
+            return Err(service_role_error("profiles_page", status, &text));
+        }
+
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&text)
+            .map_err(|e| AuthError::Supabase(format!("invalid json: {}", e)))?;
+        let profiles = rows.iter().map(personal_from_row).collect::<Result<_, _>>()?;
+        Ok((profiles, total))
+    }
+
+    /// Discoverable, active profiles with these ids, in the order of `ids`. A profile that
+    /// was hidden or deactivated since the match index was built is left out.
+    async fn profiles_by_ids(&self, ids: &[Uuid], exclude_user_id: Uuid) -> Result<Vec<PersonalDataOut>, AuthError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let list = ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+        let filter = format!("id=in.({})", list);
+        let (mut profiles, _) = self.profiles_page(&filter, exclude_user_id, ids.len() as u32, 0).await?;
+        profiles.sort_by_key(|p| ids.iter().position(|id| *id == p.user_id));
+        Ok(profiles)
+    }
+
+    /// Read (id, primary_skill, skill_to_learn) of every discoverable, active profile,
+    /// in the live match query's order, and build the [`MatchIndex`] from it
+    pub async fn build_match_index(&self) -> Result<MatchIndex, AuthError> {
+        const PAGE: usize = 1000;
+
+        #[derive(Deserialize)]
+        struct SkillRow {
+            id: Uuid,
+            primary_skill: Option<String>,
+            skill_to_learn: Option<String>,
+        }
+
+        let mut rows = Vec::new();
+        loop {
+            let url = format!(
+                "{}/rest/v1/profiles?is_discoverable=not.is.false&is_active=not.is.false&select=id,primary_skill,skill_to_learn&order=updated_at.desc.nullslast,id&limit={}&offset={}",
+                self.supabase_url.trim_end_matches('/'),
+                PAGE,
+                rows.len()
+            );
+
+            let resp = self
+                .client
+                .get(&url)
+                .header("apikey", &self.supabase_service_role_key)
+                .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
+                .send_with_retry()
+                .await?;
+
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            if !status.is_success() {
+                return Err(service_role_error("build_match_index", status, &text));
+            }
+
+            let page: Vec<SkillRow> = serde_json::from_str(&text)
+                .map_err(|e| AuthError::Supabase(format!("invalid json: {}", e)))?;
+            let done = page.len() < PAGE;
+            rows.extend(page);
+            if done {
+                break;
+            }
+        }
+
+        Ok(MatchIndex::build(rows.into_iter().map(|row| {
+            (row.id, row.primary_skill.unwrap_or_default(), row.skill_to_learn.unwrap_or_default())
+        })))
+    }
+
+    /// Rebuild the match index and swap it in; the old one stays in use if this fails
+    pub async fn refresh_match_index(&self) -> Result<(), AuthError> {
+        let index = self.build_match_index().await?;
+        self.match_index.set(index);
+        Ok(())
+    }
+
+    /// Barter partners for `user_id`: people who teach what the user wants to learn
+    /// and/or want to learn what the user teaches. Mutual matches (both) come first,
+    /// then one-directional ones. The caller is never included.
+    /// Paged with `limit`/`offset` over that whole ordering; the second value is the
+    /// total number of matches (None if Supabase didn't report a count).
+    /// With a fresh match index the candidates come from it, and only the page is
+    /// read from the profiles table.
+    /// Err(ProfileNotFound) when the caller hasn't completed their profile.
+    pub async fn find_mutual_matches(
+        &self,
+        user_id: Uuid,
+        limit: u32,
+        offset: u32,
+    ) -> Result<(Vec<PersonalDataOut>, Option<i64>), AuthError> {
+        let me = self.get_user_profile(user_id).await?.ok_or(AuthError::ProfileNotFound)?;
+
+        // Kandidat dari indeks kalau ada; hanya halaman yang diminta yang dibaca dari DB
+        if let Some(index) = self.match_index.get() {
+            let candidates = index.candidates(user_id, &me.primary_skill, &me.skill_to_learn);
+            let page: Vec<Uuid> = candidates.iter().skip(offset as usize).take(limit as usize).copied().collect();
+            let matches = self.profiles_by_ids(&page, user_id).await?;
+            return Ok((matches, Some(candidates.len() as i64)));
+        }
+
+        let teaches_wanted = format!("primary_skill.eq.{}", postgrest_quote(&me.skill_to_learn));
+        let wants_taught = format!("skill_to_learn.eq.{}", postgrest_quote(&me.primary_skill));
+        let both = format!("{},{}", teaches_wanted, wants_taught);
+        let mutual_filter = format!("and=({})", urlencoding::encode(&both));
+        let one_way_filter = format!(
+            "or=({})&not.and=({})",
+            urlencoding::encode(&both),
+            urlencoding::encode(&both)
+        );
 
-        Ok(role == "user")
+        // Halaman dihitung di atas urutan gabungan: semua mutual dulu, lalu one-way
+        let (mut matches, mutual_total) = self
+            .profiles_page(&mutual_filter, user_id, limit, offset)
+            .await?;
+        let mutual_count = mutual_total.unwrap_or(offset as i64 + matches.len() as i64);
+        let one_way_offset = (offset as i64 - mutual_count).max(0) as u32;
+        let one_way_limit = limit - matches.len() as u32;
+
+        // limit=0 tetap mengembalikan count, jadi total selalu lengkap
+        let (one_way, one_way_total) = self
+            .profiles_page(&one_way_filter, user_id, one_way_limit, one_way_offset)
+            .await?;
+        matches.extend(one_way);
+
+        let total = mutual_total.zip(one_way_total).map(|(m, o)| m + o);
+        Ok((matches, total))
     }
-}
\ No newline at end of file
+
+    /// Deactivate (`false`) or reactivate (`true`) the user's account. Data is kept;
+    /// an inactive user is left out of discovery, search and the feed.
+    /// Returns false when the user has no profile row yet.
+    pub async fn set_account_active(&self, user_id: Uuid, active: bool) -> Result<bool, AuthError> {
+        let url = format!(
+            "{}/rest/v1/profiles?id=eq.{}&select=id",
+            self.supabase_url.trim_end_matches('/'),
+            user_id
+        );
+
+        let resp = self
+            .client
+            .patch(&url)
+            .header("apikey", &self.supabase_service_role_key)
+            .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&serde_json::json!({ "is_active": active }))
+            .send_tracked()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+
+        if !status.is_success() {
+            return Err(service_role_error("set_account_active", status, &text));
+        }
+
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&text)
+            .map_err(|e| AuthError::Supabase(format!("invalid json: {}", e)))?;
+        Ok(!rows.is_empty())
+    }
+
+    /// True when an admin suspended the account (`profiles.is_suspended`). This is separate
+    /// from `is_active`, which the user can flip back themselves via /api/me/reactivate.
+    /// A user without a profile row is not suspended.
+    ///
+    /// Kolom: `alter table profiles add column is_suspended boolean not null default false;`
+    pub async fn is_suspended(&self, user_id: Uuid) -> Result<bool, AuthError> {
+        let url = format!(
+            "{}/rest/v1/profiles?id=eq.{}&select=is_suspended",
+            self.supabase_url.trim_end_matches('/'),
+            user_id
+        );
+
+        let resp = self
+            .client
+            .get(&url)
+            .header("apikey", &self.supabase_anon_key)
+            .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
+            .send_with_retry()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+
+        if !status.is_success() {
+            return Err(service_role_error("is_suspended", status, &text));
+        }
+
+        let arr: serde_json::Value =
+            serde_json::from_str(&text).map_err(|e| AuthError::Supabase(format!("invalid json: {}", e)))?;
+
+        Ok(arr
+            .as_array()
+            .and_then(|a| a.first())
+            .and_then(|v| v.get("is_suspended"))
+            .and_then(|s| s.as_bool())
+            .unwrap_or(false))
+    }
+
+    /// Suspend (`true`) or lift the suspension (`false`) of an account. Admin only.
+    /// Returns false when the user has no profile row.
+    pub async fn set_suspended(&self, user_id: Uuid, suspended: bool) -> Result<bool, AuthError> {
+        let url = format!(
+            "{}/rest/v1/profiles?id=eq.{}&select=id",
+            self.supabase_url.trim_end_matches('/'),
+            user_id
+        );
+
+        let resp = self
+            .client
+            .patch(&url)
+            .header("apikey", &self.supabase_service_role_key)
+            .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&serde_json::json!({ "is_suspended": suspended }))
+            .send_tracked()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+
+        if !status.is_success() {
+            return Err(service_role_error("set_suspended", status, &text));
+        }
+
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&text)
+            .map_err(|e| AuthError::Supabase(format!("invalid json: {}", e)))?;
+        Ok(!rows.is_empty())
+    }
+
+    /// Delete the Supabase auth user (admin API, `/auth/v1/admin/users/{id}`).
+    /// A user that is already gone counts as deleted.
+    pub async fn delete_auth_user(&self, user_id: Uuid) -> Result<(), AuthError> {
+        let url = format!(
+            "{}/auth/v1/admin/users/{}",
+            self.supabase_url.trim_end_matches('/'),
+            user_id
+        );
+
+        let resp = self
+            .client
+            .delete(&url)
+            .header("apikey", &self.supabase_service_role_key)
+            .header("Authorization", format!("Bearer {}", &self.supabase_service_role_key))
+            .send_tracked()
+            .await?;
+
+        let status = resp.status();
+        if status.is_success() || status == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+
+        let text = resp.text().await.unwrap_or_default();
+        Err(service_role_error("delete_auth_user", status, &text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(blocked: Option<&[&str]>) -> AuthService {
+        let mut svc = AuthService::new(reqwest::Client::new(), "http://127.0.0.1:1", "anon", "service");
+        svc.disposable_email_domains =
+            blocked.map(|domains| domains.iter().map(|d| d.to_string()).collect());
+        svc
+    }
+
+    #[test]
+    fn blocks_listed_domain_and_its_subdomains() {
+        let svc = service(Some(&["mailinator.com"]));
+        assert!(svc.is_disposable_email("a@mailinator.com"));
+        assert!(svc.is_disposable_email("a@x.mailinator.com"));
+        assert!(svc.is_disposable_email(" a@MAILINATOR.COM. "));
+        assert!(!svc.is_disposable_email("a@gmail.com"));
+        assert!(!svc.is_disposable_email("a@notmailinator.com"));
+    }
+
+    #[actix_web::test]
+    async fn username_owner_matches_exactly_ignoring_case() {
+        use std::sync::{Arc, Mutex};
+        let owner = Uuid::new_v4();
+        let seen = Arc::new(Mutex::new(String::new()));
+        let captured = seen.clone();
+        let url = crate::test_support::mock_supabase(move |req, _| {
+            *captured.lock().unwrap() = req.query_string().to_string();
+            actix_web::HttpResponse::Ok().json(serde_json::json!([{ "id": owner }]))
+        });
+
+        let mut svc = service(None);
+        svc.supabase_url = url;
+        assert_eq!(svc.username_owner(" Al_ice% ").await.unwrap(), Some(owner));
+        // `_` dan `%` di-escape supaya ilike tidak jadi wildcard
+        assert!(seen.lock().unwrap().contains("username=ilike.Al%5C_ice%5C%25"));
+    }
+
+    #[test]
+    fn row_user_id_prefers_the_user_id_column() {
+        let (row_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let personals_row = serde_json::json!({ "id": row_id, "user_id": user_id });
+        assert_eq!(row_user_id(&personals_row).unwrap(), user_id);
+
+        // profiles: primary key id = auth user id
+        let profiles_row = serde_json::json!({ "id": row_id });
+        assert_eq!(row_user_id(&profiles_row).unwrap(), row_id);
+        let out = personal_from_row(&profiles_row).unwrap();
+        assert_eq!((out.id, out.user_id), (row_id, row_id));
+
+        assert!(row_user_id(&serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn nothing_is_blocked_when_blocking_is_disabled() {
+        let svc = service(None);
+        assert!(!svc.is_disposable_email("a@mailinator.com"));
+        assert!(!svc.is_disposable_email("not-an-email"));
+    }
+
+    #[actix_web::test]
+    async fn matches_come_from_the_index_when_it_is_fresh() {
+        let (me, teacher, mutual) = (Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3));
+        let row = |id: Uuid, teaches: &str, learns: &str| {
+            serde_json::json!({ "id": id, "primary_skill": teaches, "skill_to_learn": learns, "bio": "" })
+        };
+        let (teacher_row, mutual_row, me_row) =
+            (row(teacher, "Cooking", "Music"), row(mutual, "Cooking", "Coding"), row(me, "Coding", "Cooking"));
+        let url = crate::test_support::mock_supabase(move |req, _| {
+            let query = urlencoding::decode(req.query_string()).unwrap().into_owned();
+            assert!(!query.contains("or="), "live match query used: {}", query);
+            if query.contains("id=in.") {
+                // Urutan DB tidak harus sama dengan urutan indeks
+                return actix_web::HttpResponse::Ok().json(serde_json::json!([teacher_row, mutual_row]));
+            }
+            actix_web::HttpResponse::Ok().json(serde_json::json!([me_row]))
+        });
+        let mut svc = AuthService::new(reqwest::Client::new(), url, "anon", "service");
+        svc.match_index = MatchIndexCache::new(Some(std::time::Duration::from_secs(60)));
+        svc.match_index.set(MatchIndex::build([
+            (teacher, "Cooking", "Music"),
+            (mutual, "Cooking", "Coding"),
+            (me, "Coding", "Cooking"),
+        ]));
+
+        let (matches, total) = svc.find_mutual_matches(me, 20, 0).await.unwrap();
+
+        let ids: Vec<Uuid> = matches.iter().map(|p| p.user_id).collect();
+        assert_eq!(ids, vec![mutual, teacher]);
+        assert_eq!(total, Some(2));
+    }
+}
@@ -0,0 +1,33 @@
+// src/services/content_filter.rs - filter kata terlarang untuk konten post
+use std::collections::HashSet;
+use std::env;
+use std::sync::OnceLock;
+
+/// Daftar bawaan (Inggris + Indonesia); POST_BANNED_WORDS menambah kata lain
+const DEFAULT_BANNED_WORDS: &[&str] = &[
+    "fuck", "fucking", "shit", "bitch", "cunt", "asshole", "bastard", "motherfucker",
+    "bangsat", "kontol", "memek", "ngentot", "bajingan",
+];
+
+/// Banned words, lowercased: the defaults plus the comma-separated POST_BANNED_WORDS env var
+fn banned_words() -> &'static HashSet<String> {
+    static WORDS: OnceLock<HashSet<String>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        let extra = env::var("POST_BANNED_WORDS").unwrap_or_default();
+        DEFAULT_BANNED_WORDS
+            .iter()
+            .map(|w| w.to_string())
+            .chain(extra.split(',').map(|w| w.trim().to_lowercase()))
+            .filter(|w| !w.is_empty())
+            .collect()
+    })
+}
+
+/// True when `text` contains a banned word. Matches whole words only (case-insensitive),
+/// so e.g. "Scunthorpe" or "shitake" in a longer word isn't flagged.
+pub fn contains_banned_word(text: &str) -> bool {
+    let words = banned_words();
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .any(|w| words.contains(&w.to_lowercase()))
+}
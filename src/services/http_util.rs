@@ -0,0 +1,409 @@
+// src/services/http_util.rs - helper untuk semua panggilan HTTP ke Supabase
+use std::env;
+use std::future::Future;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use reqwest::{Client, RequestBuilder, Response};
+use thiserror::Error;
+
+const CLOSED: u8 = 0;
+const OPEN: u8 = 1;
+const HALF_OPEN: u8 = 2;
+
+/// Postgres error codes PostgREST puts in `code` (it answers both with 409)
+pub const UNIQUE_VIOLATION: &str = "23505";
+pub const FOREIGN_KEY_VIOLATION: &str = "23503";
+
+/// Circuit breaker for Supabase. After `failure_threshold` consecutive failures
+/// (network errors or 5xx) within `window_ms` the circuit opens and requests are
+/// rejected for `cooldown_ms`. After that one request is let through (half-open):
+/// success closes the circuit, failure opens it again. A failure is one logical
+/// request: the retries of [`SendTracked::send_with_retry`] count once.
+pub struct CircuitBreaker {
+    state: AtomicU8,
+    failures: AtomicU32,
+    window_started_ms: AtomicU64,
+    /// Saat circuit dibuka, atau saat probe half-open terakhir dilepas
+    opened_at_ms: AtomicU64,
+    failure_threshold: u32,
+    window_ms: u64,
+    cooldown_ms: u64,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(default)
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, window_ms: u64, cooldown_ms: u64) -> Self {
+        Self {
+            state: AtomicU8::new(CLOSED),
+            failures: AtomicU32::new(0),
+            window_started_ms: AtomicU64::new(0),
+            opened_at_ms: AtomicU64::new(0),
+            failure_threshold,
+            window_ms,
+            cooldown_ms,
+        }
+    }
+
+    /// CIRCUIT_FAILURE_THRESHOLD (default 5), CIRCUIT_WINDOW_SECS (default 30),
+    /// CIRCUIT_COOLDOWN_SECS (default 30)
+    pub fn from_env() -> Self {
+        Self::new(
+            env_u64("CIRCUIT_FAILURE_THRESHOLD", 5) as u32,
+            env_u64("CIRCUIT_WINDOW_SECS", 30) * 1000,
+            env_u64("CIRCUIT_COOLDOWN_SECS", 30) * 1000,
+        )
+    }
+
+    /// Whether a request may go ahead. While open this is false until the cooldown
+    /// has passed; then exactly one caller gets true as the half-open probe
+    /// (another one per cooldown if the probe never reports back).
+    pub fn allow_request(&self) -> bool {
+        self.allow_request_at(now_ms())
+    }
+
+    fn allow_request_at(&self, now: u64) -> bool {
+        let state = self.state.load(Ordering::Acquire);
+        if state == CLOSED {
+            return true;
+        }
+
+        let opened_at = self.opened_at_ms.load(Ordering::Acquire);
+        if now.saturating_sub(opened_at) < self.cooldown_ms {
+            return false;
+        }
+
+        // Hanya satu caller yang menang compare_exchange dan jadi probe
+        let won = self
+            .opened_at_ms
+            .compare_exchange(opened_at, now, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok();
+        if won {
+            self.state.store(HALF_OPEN, Ordering::Release);
+            log::info!("Supabase circuit half-open: letting a probe request through");
+        }
+        won
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.load(Ordering::Acquire) != CLOSED
+    }
+
+    pub fn record_success(&self) {
+        self.failures.store(0, Ordering::Release);
+        if self.state.swap(CLOSED, Ordering::AcqRel) != CLOSED {
+            log::info!("Supabase circuit closed: upstream recovered");
+        }
+    }
+
+    pub fn record_failure(&self) {
+        self.record_failure_at(now_ms())
+    }
+
+    fn record_failure_at(&self, now: u64) {
+        if self.state.load(Ordering::Acquire) != CLOSED {
+            // Probe gagal (atau kegagalan terlambat): mulai cooldown lagi
+            self.opened_at_ms.store(now, Ordering::Release);
+            self.state.store(OPEN, Ordering::Release);
+            return;
+        }
+
+        let window_started = self.window_started_ms.load(Ordering::Acquire);
+        let failures = if now.saturating_sub(window_started) > self.window_ms {
+            self.window_started_ms.store(now, Ordering::Release);
+            self.failures.store(1, Ordering::Release);
+            1
+        } else {
+            self.failures.fetch_add(1, Ordering::AcqRel) + 1
+        };
+
+        if failures >= self.failure_threshold {
+            self.opened_at_ms.store(now, Ordering::Release);
+            if self.state.swap(OPEN, Ordering::AcqRel) == CLOSED {
+                log::error!(
+                    "Supabase circuit opened after {} consecutive failures; failing fast for {}s",
+                    failures,
+                    self.cooldown_ms / 1000
+                );
+            }
+        }
+    }
+
+    /// Network errors and 5xx responses count as failures; 4xx are the caller's problem
+    fn record(&self, result: &Result<Response, reqwest::Error>) {
+        match result {
+            Ok(resp) if resp.status().is_server_error() => self.record_failure(),
+            Ok(_) => self.record_success(),
+            Err(_) => self.record_failure(),
+        }
+    }
+}
+
+/// Breaker bersama untuk semua client Supabase
+pub fn supabase_breaker() -> &'static CircuitBreaker {
+    static BREAKER: OnceLock<CircuitBreaker> = OnceLock::new();
+    BREAKER.get_or_init(CircuitBreaker::from_env)
+}
+
+/// Per-request timeout for Supabase calls (SUPABASE_TIMEOUT_MS, default 10000)
+pub fn supabase_timeout() -> Duration {
+    static TIMEOUT: OnceLock<Duration> = OnceLock::new();
+    *TIMEOUT.get_or_init(|| Duration::from_millis(env_u64("SUPABASE_TIMEOUT_MS", 10_000)))
+}
+
+/// HTTP client for Supabase calls: overall timeout [`supabase_timeout`], connect timeout
+/// the same but at most 5s, so a hung connection can't hold a worker indefinitely
+pub fn build_supabase_client() -> Client {
+    let timeout = supabase_timeout();
+    Client::builder()
+        .user_agent("barterup-be/0.1")
+        .timeout(timeout)
+        .connect_timeout(timeout.min(Duration::from_secs(5)))
+        .build()
+        .expect("failed to build http client")
+}
+
+/// Retries for idempotent reads: SUPABASE_RETRY_COUNT extra attempts (default 2, 0 disables)
+/// with exponential backoff starting at SUPABASE_RETRY_BACKOFF_MS (default 200)
+struct RetryPolicy {
+    retries: u32,
+    base_delay: Duration,
+}
+
+fn retry_policy() -> &'static RetryPolicy {
+    static POLICY: OnceLock<RetryPolicy> = OnceLock::new();
+    POLICY.get_or_init(|| RetryPolicy {
+        retries: env::var("SUPABASE_RETRY_COUNT")
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .unwrap_or(2),
+        base_delay: Duration::from_millis(env_u64("SUPABASE_RETRY_BACKOFF_MS", 200)),
+    })
+}
+
+/// Network-level failure (couldn't connect or timed out), as opposed to an HTTP error status
+pub fn is_unreachable(e: &reqwest::Error) -> bool {
+    e.is_connect() || e.is_timeout()
+}
+
+/// Error of [`SendTracked`]: the request failed, or it was never sent because
+/// [`supabase_breaker`] is open
+#[derive(Debug, Error)]
+pub enum SendError {
+    #[error("supabase circuit open, request not sent")]
+    CircuitOpen,
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+}
+
+impl SendError {
+    /// Supabase is down as far as the caller is concerned: circuit open, or [`is_unreachable`]
+    pub fn is_unreachable(&self) -> bool {
+        match self {
+            SendError::CircuitOpen => true,
+            SendError::Http(e) => is_unreachable(e),
+        }
+    }
+}
+
+/// Postgres error code of a PostgREST error body (`{"code":"23505","message":...}`)
+pub fn postgrest_error_code(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    value.get("code")?.as_str().map(|c| c.to_string())
+}
+
+/// `.send_tracked()` that reports the outcome to [`supabase_breaker`].
+/// Network errors and 5xx responses count as failures; 4xx are the caller's problem.
+/// While the circuit is open the request isn't sent and `SendError::CircuitOpen` is
+/// returned right away, which handlers answer with 503 UPSTREAM_UNAVAILABLE.
+pub trait SendTracked {
+    fn send_tracked(self) -> impl Future<Output = Result<Response, SendError>> + Send;
+
+    /// `.send_tracked()` for idempotent requests (GETs): each attempt gets
+    /// [`supabase_timeout`], and a connect error or timeout is retried with exponential
+    /// backoff (SUPABASE_RETRY_COUNT / SUPABASE_RETRY_BACKOFF_MS). HTTP error statuses
+    /// (4xx and 5xx) are returned as-is, and so is a request whose body can't be cloned.
+    /// Only the final outcome is reported to the breaker, so a retried request is one failure.
+    fn send_with_retry(self) -> impl Future<Output = Result<Response, SendError>> + Send;
+}
+
+impl SendTracked for RequestBuilder {
+    async fn send_tracked(self) -> Result<Response, SendError> {
+        let breaker = supabase_breaker();
+        if !breaker.allow_request() {
+            return Err(SendError::CircuitOpen);
+        }
+
+        let result = self.send().await;
+        breaker.record(&result);
+        Ok(result?)
+    }
+
+    async fn send_with_retry(self) -> Result<Response, SendError> {
+        let breaker = supabase_breaker();
+        if !breaker.allow_request() {
+            return Err(SendError::CircuitOpen);
+        }
+
+        let policy = retry_policy();
+        let mut attempt = 0;
+        let result = loop {
+            let Some(request) = self.try_clone() else {
+                break self.send().await;
+            };
+            match request.timeout(supabase_timeout()).send().await {
+                Err(e) if is_unreachable(&e) && attempt < policy.retries => {
+                    let delay = policy.base_delay * 2u32.pow(attempt);
+                    attempt += 1;
+                    log::warn!(
+                        "Supabase request failed ({}), retry {}/{} in {:?}",
+                        e,
+                        attempt,
+                        policy.retries,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => break result,
+            }
+        };
+        breaker.record(&result);
+        Ok(result?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn postgrest_error_code_reads_the_sqlstate() {
+        let body = r#"{"code":"23505","details":null,"hint":null,"message":"duplicate key"}"#;
+        assert_eq!(postgrest_error_code(body).as_deref(), Some(UNIQUE_VIOLATION));
+        assert_eq!(postgrest_error_code(r#"{"code":"23503"}"#).as_deref(), Some(FOREIGN_KEY_VIOLATION));
+    }
+
+    const T0: u64 = 1_000_000;
+
+    /// 3 failures within 1s open the circuit for 500ms
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(3, 1_000, 500)
+    }
+
+    fn opened_at(t: u64) -> CircuitBreaker {
+        let cb = breaker();
+        for _ in 0..3 {
+            cb.record_failure_at(t);
+        }
+        assert!(cb.is_open());
+        cb
+    }
+
+    #[test]
+    fn opens_after_threshold_failures_in_the_window() {
+        let cb = breaker();
+        cb.record_failure_at(T0);
+        cb.record_failure_at(T0 + 10);
+        assert!(!cb.is_open());
+        assert!(cb.allow_request_at(T0 + 20));
+
+        cb.record_failure_at(T0 + 20);
+        assert!(cb.is_open());
+    }
+
+    #[test]
+    fn failures_outside_the_window_start_a_new_count() {
+        let cb = breaker();
+        cb.record_failure_at(T0);
+        cb.record_failure_at(T0 + 10);
+        cb.record_failure_at(T0 + 1_011);
+        cb.record_failure_at(T0 + 1_020);
+        assert!(!cb.is_open());
+
+        cb.record_failure_at(T0 + 1_030);
+        assert!(cb.is_open());
+    }
+
+    #[test]
+    fn success_resets_the_count() {
+        let cb = breaker();
+        cb.record_failure_at(T0);
+        cb.record_failure_at(T0);
+        cb.record_success();
+        cb.record_failure_at(T0);
+        assert!(!cb.is_open());
+    }
+
+    #[test]
+    fn open_circuit_fails_fast_until_the_cooldown() {
+        let cb = opened_at(T0);
+        assert!(!cb.allow_request_at(T0));
+        assert!(!cb.allow_request_at(T0 + 499));
+    }
+
+    #[test]
+    fn exactly_one_probe_after_the_cooldown() {
+        let cb = opened_at(T0);
+        let barrier = std::sync::Barrier::new(8);
+
+        let allowed: usize = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        cb.allow_request_at(T0 + 500) as usize
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+
+        assert_eq!(allowed, 1);
+        assert!(cb.is_open());
+        // Probe berikutnya baru setelah satu cooldown lagi
+        assert!(!cb.allow_request_at(T0 + 999));
+        assert!(cb.allow_request_at(T0 + 1_000));
+    }
+
+    #[test]
+    fn probe_success_closes_the_circuit() {
+        let cb = opened_at(T0);
+        assert!(cb.allow_request_at(T0 + 500));
+        cb.record_success();
+
+        assert!(!cb.is_open());
+        assert!(cb.allow_request_at(T0 + 501));
+    }
+
+    #[test]
+    fn probe_failure_reopens_the_circuit() {
+        let cb = opened_at(T0);
+        assert!(cb.allow_request_at(T0 + 500));
+        cb.record_failure_at(T0 + 600);
+
+        assert!(cb.is_open());
+        assert!(!cb.allow_request_at(T0 + 1_099));
+        assert!(cb.allow_request_at(T0 + 1_100));
+    }
+
+    #[test]
+    fn postgrest_error_code_is_none_without_a_code() {
+        assert_eq!(postgrest_error_code(""), None);
+        assert_eq!(postgrest_error_code("conflict"), None);
+        assert_eq!(postgrest_error_code(r#"{"message":"conflict"}"#), None);
+    }
+}
@@ -0,0 +1,280 @@
+// src/services/images.rs - helper untuk validasi gambar upload
+use std::env;
+use std::io::Cursor;
+use image::{ImageFormat, ImageReader};
+use image::imageops::FilterType;
+use crate::dtos::profile_picture_dtos::CropBox;
+
+const DEFAULT_MIN_DIMENSION: u32 = 64;
+const DEFAULT_MAX_DIMENSION: u32 = 4096;
+const DEFAULT_MAX_IMAGE_BYTES: usize = 2 * 1024 * 1024;
+/// Sisi thumbnail avatar (persegi) dalam pixel
+pub const THUMBNAIL_SIZE: u32 = 128;
+
+/// Batas ukuran file gambar setelah base64 di-decode (IMAGE_MAX_BYTES, default 2MB)
+pub fn max_image_bytes() -> usize {
+    env::var("IMAGE_MAX_BYTES")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|max| *max > 0)
+        .unwrap_or(DEFAULT_MAX_IMAGE_BYTES)
+}
+
+/// Panjang base64 (dengan padding) untuk `bytes` byte data
+pub fn base64_len(bytes: usize) -> usize {
+    bytes.div_ceil(3) * 4
+}
+
+fn too_large_message(len: usize, max: usize) -> String {
+    let mb = |bytes: usize| format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0));
+    format!("Image is too large ({}). Maximum file size is {}.", mb(len), mb(max))
+}
+
+/// Tolak gambar (sudah di-decode) yang lebih besar dari [`max_image_bytes`]
+pub fn check_image_size(len: usize) -> Result<(), String> {
+    let max = max_image_bytes();
+    if len > max {
+        return Err(too_large_message(len, max));
+    }
+    Ok(())
+}
+
+/// Same limit, checked on the base64 text before decoding so an oversized
+/// payload never gets a decode buffer allocated
+pub fn check_base64_size(encoded_len: usize) -> Result<(), String> {
+    let max = max_image_bytes();
+    if encoded_len > base64_len(max) {
+        return Err(too_large_message(encoded_len / 4 * 3, max));
+    }
+    Ok(())
+}
+
+/// Batas dimensi (min, max) dalam pixel.
+/// Bisa diatur lewat IMAGE_MIN_DIMENSION dan IMAGE_MAX_DIMENSION.
+pub fn dimension_limits() -> (u32, u32) {
+    let read = |key: &str, default: u32| {
+        env::var(key)
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .unwrap_or(default)
+    };
+    (
+        read("IMAGE_MIN_DIMENSION", DEFAULT_MIN_DIMENSION),
+        read("IMAGE_MAX_DIMENSION", DEFAULT_MAX_DIMENSION),
+    )
+}
+
+/// Baca width/height dari header gambar (tanpa decode seluruh pixel)
+/// lalu tolak gambar yang terlalu kecil (mis. tracking pixel 1x1) atau terlalu besar.
+pub fn check_image_dimensions(bytes: &[u8]) -> Result<(u32, u32), String> {
+    let (width, height) = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Unable to read image: {}", e))?
+        .into_dimensions()
+        .map_err(|_| "Unable to read image dimensions. The file may be corrupted.".to_string())?;
+
+    let (min, max) = dimension_limits();
+
+    if width < min || height < min {
+        return Err(format!(
+            "Image is too small ({}x{}). Minimum size is {}x{} pixels.",
+            width, height, min, min
+        ));
+    }
+
+    if width > max || height > max {
+        return Err(format!(
+            "Image is too large ({}x{}). Maximum size is {}x{} pixels.",
+            width, height, max, max
+        ));
+    }
+
+    Ok((width, height))
+}
+
+/// Real format of the image from its magic bytes, as a mime type.
+/// None when the bytes aren't a JPEG, PNG, GIF or WEBP file.
+pub fn detect_image_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Ekstensi file untuk mime hasil [`detect_image_mime`]
+pub fn extension_for_mime(mime: &str) -> &'static str {
+    match mime {
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        _ => "jpg",
+    }
+}
+
+/// Samakan alias mime (image/jpg -> image/jpeg)
+pub fn normalize_mime(mime: &str) -> String {
+    match mime.trim().to_ascii_lowercase().as_str() {
+        "image/jpg" => "image/jpeg".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Split an optional `data:<mime>;base64,<data>` prefix off the uploaded string.
+/// Returns the mime declared in the prefix (if any) and the raw base64 payload.
+pub fn split_data_url(input: &str) -> Result<(Option<String>, &str), String> {
+    let Some(rest) = input.strip_prefix("data:") else {
+        return Ok((None, input));
+    };
+
+    let (meta, data) = rest
+        .split_once(',')
+        .ok_or_else(|| "Invalid data URL: missing ',' separator".to_string())?;
+
+    let mut parts = meta.split(';');
+    let mime = parts.next().unwrap_or_default();
+    if !parts.any(|p| p.eq_ignore_ascii_case("base64")) {
+        return Err("Invalid data URL: image data must be base64 encoded".to_string());
+    }
+
+    Ok((Some(normalize_mime(mime)), data))
+}
+
+
+/// Crop the image to `crop` and re-encode it in its original format.
+/// The box must be non-empty and lie fully inside the image.
+pub fn crop_image(bytes: &[u8], crop: &CropBox) -> Result<Vec<u8>, String> {
+    let reader = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Unable to read image: {}", e))?;
+    let format = reader
+        .format()
+        .ok_or_else(|| "Unsupported image format".to_string())?;
+    let img = reader
+        .decode()
+        .map_err(|_| "Unable to decode image. The file may be corrupted.".to_string())?;
+
+    let fits = |start: u32, len: u32, max: u32| start.checked_add(len).is_some_and(|end| end <= max);
+    if crop.width == 0
+        || crop.height == 0
+        || !fits(crop.x, crop.width, img.width())
+        || !fits(crop.y, crop.height, img.height())
+    {
+        return Err(format!(
+            "Crop area {}x{} at ({}, {}) is outside the {}x{} image",
+            crop.width, crop.height, crop.x, crop.y, img.width(), img.height()
+        ));
+    }
+
+    let cropped = img.crop_imm(crop.x, crop.y, crop.width, crop.height);
+
+    let mut out = Vec::new();
+    cropped
+        .write_to(&mut Cursor::new(&mut out), format)
+        .map_err(|e| format!("Unable to encode cropped image: {}", e))?;
+    Ok(out)
+}
+
+/// The largest centered square of a `width`x`height` image, or None when the image
+/// is already square. Avatars are always stored square so every client shows the same area.
+pub fn center_square(width: u32, height: u32) -> Option<CropBox> {
+    if width == height {
+        return None;
+    }
+    let side = width.min(height);
+    Some(CropBox {
+        x: (width - side) / 2,
+        y: (height - side) / 2,
+        width: side,
+        height: side,
+    })
+}
+
+/// Square JPEG thumbnail of THUMBNAIL_SIZE pixels. The image is scaled to cover the
+/// square and the overflow is cropped from the center, so the aspect ratio is kept.
+pub fn make_thumbnail(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let img = image::load_from_memory(bytes)
+        .map_err(|_| "Unable to decode image. The file may be corrupted.".to_string())?;
+
+    // JPEG tidak punya alpha channel, jadi konversi ke RGB dulu
+    let thumb = img
+        .resize_to_fill(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Triangle)
+        .to_rgb8();
+
+    let mut out = Vec::new();
+    thumb
+        .write_to(&mut Cursor::new(&mut out), ImageFormat::Jpeg)
+        .map_err(|e| format!("Unable to encode thumbnail: {}", e))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, ImageFormat};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        DynamicImage::new_rgb8(width, height)
+            .write_to(&mut Cursor::new(&mut out), ImageFormat::Png)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn dimensions_inside_the_limits_are_accepted() {
+        assert_eq!(check_image_dimensions(&png(64, 200)), Ok((64, 200)));
+    }
+
+    #[test]
+    fn tracking_pixel_is_too_small() {
+        let err = check_image_dimensions(&png(1, 1)).unwrap_err();
+        assert!(err.contains("too small (1x1)"), "{}", err);
+    }
+
+    #[test]
+    fn one_side_over_the_max_is_too_large() {
+        let err = check_image_dimensions(&png(DEFAULT_MAX_DIMENSION + 1, 64)).unwrap_err();
+        assert!(err.contains("too large"), "{}", err);
+    }
+
+    #[test]
+    fn byte_limit_is_inclusive() {
+        let max = max_image_bytes();
+        assert!(check_image_size(max).is_ok());
+        let err = check_image_size(max + 1).unwrap_err();
+        assert!(err.starts_with("Image is too large"), "{}", err);
+    }
+
+    #[test]
+    fn base64_limit_matches_the_decoded_limit() {
+        let max = max_image_bytes();
+        assert_eq!(base64_len(3), 4);
+        assert_eq!(base64_len(4), 8);
+        assert!(check_base64_size(base64_len(max)).is_ok());
+        assert!(check_base64_size(base64_len(max) + 1).is_err());
+    }
+
+    #[test]
+    fn non_square_images_are_cropped_from_the_center() {
+        let crop = center_square(300, 200).unwrap();
+        assert_eq!((crop.x, crop.y, crop.width, crop.height), (50, 0, 200, 200));
+        let crop = center_square(64, 101).unwrap();
+        assert_eq!((crop.x, crop.y, crop.width, crop.height), (0, 18, 64, 64));
+        assert!(center_square(128, 128).is_none());
+
+        let square = crop_image(&png(300, 200), &center_square(300, 200).unwrap()).unwrap();
+        assert_eq!(check_image_dimensions(&square), Ok((200, 200)));
+    }
+
+    #[test]
+    fn unreadable_bytes_are_rejected() {
+        assert!(check_image_dimensions(b"not an image").is_err());
+    }
+}
@@ -0,0 +1,78 @@
+// src/services/markdown.rs - render markdown post content to sanitized HTML
+use std::collections::{HashMap, HashSet};
+use ammonia::{Builder, UrlRelative};
+use pulldown_cmark::{html, Parser};
+
+/// Tags yang boleh muncul di HTML hasil render (bold, italic, link, list)
+const ALLOWED_TAGS: &[&str] = &["p", "br", "strong", "b", "em", "i", "a", "ul", "ol", "li"];
+
+/// Skema URL yang diizinkan untuk link (javascript:, data:, dll. dibuang)
+const ALLOWED_URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Render markdown to HTML and run it through an allow-list sanitizer.
+/// Raw HTML inside the markdown, disallowed tags/attributes and unsafe link
+/// schemes are stripped, so the result is safe to inject into the page.
+pub fn render_markdown(source: &str) -> String {
+    let parser = Parser::new(source);
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser);
+
+    sanitize_html(&unsafe_html)
+}
+
+/// Run HTML through the same allow-list as [`render_markdown`]. Used on stored
+/// `content_html` when a post is read, so a row written outside the API (or by an
+/// older renderer) can't put scripts in the page.
+pub fn sanitize_html(unsafe_html: &str) -> String {
+    let tag_attributes = HashMap::from([("a", HashSet::from(["href"]))]);
+
+    Builder::new()
+        .tags(ALLOWED_TAGS.iter().copied().collect())
+        .generic_attributes(HashSet::new())
+        .tag_attributes(tag_attributes)
+        .url_schemes(ALLOWED_URL_SCHEMES.iter().copied().collect())
+        .url_relative(UrlRelative::Deny)
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .clean(unsafe_html)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_tags_are_removed() {
+        let html = render_markdown("hello <script>alert(1)</script>");
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("alert(1)"));
+
+        assert!(!sanitize_html("<p>hi</p><script>alert(1)</script>").contains("script"));
+    }
+
+    #[test]
+    fn event_handler_attributes_are_removed() {
+        let html = sanitize_html(r#"<p onclick="alert(1)">hi</p><a href="https://x.com" onmouseover="alert(1)">x</a>"#);
+        assert!(!html.contains("onclick"));
+        assert!(!html.contains("onmouseover"));
+        assert!(html.contains(r#"href="https://x.com""#));
+    }
+
+    #[test]
+    fn javascript_links_lose_their_href() {
+        let html = render_markdown("[click](javascript:alert(1))");
+        assert!(!html.contains("javascript:"));
+
+        let html = sanitize_html(r#"<a href="javascript:alert(1)">click</a>"#);
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("click"));
+    }
+
+    #[test]
+    fn markdown_formatting_is_kept() {
+        let html = render_markdown("**bold** and *it* [link](https://barterup.id)");
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(html.contains("<em>it</em>"));
+        assert!(html.contains(r#"<a href="https://barterup.id" rel="noopener noreferrer nofollow">link</a>"#));
+    }
+}
@@ -0,0 +1,154 @@
+// src/services/match_index.rs - indeks skill -> user untuk memilih kandidat match
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Discoverable, active profiles grouped by the skill they teach and the skill they
+/// want to learn. Each list keeps the order of the profiles query that built it
+/// (most recently updated first), the same order the live match query uses.
+#[derive(Debug, Default)]
+pub struct MatchIndex {
+    teachers: HashMap<String, Vec<Uuid>>,
+    learners: HashMap<String, Vec<Uuid>>,
+}
+
+impl MatchIndex {
+    /// Build from (user id, primary_skill, skill_to_learn) rows. Empty skills
+    /// (signup stubs) match nobody and are left out.
+    pub fn build<I, S>(rows: I) -> Self
+    where
+        I: IntoIterator<Item = (Uuid, S, S)>,
+        S: AsRef<str>,
+    {
+        let mut index = Self::default();
+        for (user_id, teaches, learns) in rows {
+            let (teaches, learns) = (teaches.as_ref().trim(), learns.as_ref().trim());
+            if !teaches.is_empty() {
+                index.teachers.entry(teaches.to_string()).or_default().push(user_id);
+            }
+            if !learns.is_empty() {
+                index.learners.entry(learns.to_string()).or_default().push(user_id);
+            }
+        }
+        index
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.teachers.is_empty() && self.learners.is_empty()
+    }
+
+    /// Candidates for someone who teaches `teaches` and wants `learns`, `me` excluded:
+    /// mutual matches first, then the one-directional ones, like `find_mutual_matches`
+    pub fn candidates(&self, me: Uuid, teaches: &str, learns: &str) -> Vec<Uuid> {
+        let teach_me = self.teachers.get(learns.trim()).map(Vec::as_slice).unwrap_or_default();
+        let want_mine = self.learners.get(teaches.trim()).map(Vec::as_slice).unwrap_or_default();
+        let teach_me_set: HashSet<&Uuid> = teach_me.iter().collect();
+        let want_mine_set: HashSet<&Uuid> = want_mine.iter().collect();
+
+        let (mutual, one_way): (Vec<Uuid>, Vec<Uuid>) = teach_me
+            .iter()
+            .chain(want_mine.iter().filter(|id| !teach_me_set.contains(id)))
+            .copied()
+            .filter(|id| *id != me)
+            .partition(|id| teach_me_set.contains(id) && want_mine_set.contains(id));
+
+        mutual.into_iter().chain(one_way).collect()
+    }
+}
+
+/// An index and when it was built
+type Built = Option<(Arc<MatchIndex>, Instant)>;
+
+/// The last built [`MatchIndex`]. Optional: it is only used while MATCH_INDEX_REFRESH_SECS
+/// is set, and only while it is younger than two refresh intervals, so a refresh task that
+/// keeps failing falls back to the live query instead of serving an old index.
+/// Clones share the same index.
+#[derive(Clone)]
+pub struct MatchIndexCache {
+    refresh: Option<Duration>,
+    current: Arc<RwLock<Built>>,
+}
+
+impl MatchIndexCache {
+    pub fn new(refresh: Option<Duration>) -> Self {
+        Self { refresh, current: Arc::default() }
+    }
+
+    /// MATCH_INDEX_REFRESH_SECS; unset or 0 turns the index off
+    pub fn from_env() -> Self {
+        let refresh = env::var("MATCH_INDEX_REFRESH_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs);
+        Self::new(refresh)
+    }
+
+    /// How often the background task rebuilds the index, None when it is off
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        self.refresh
+    }
+
+    pub fn set(&self, index: MatchIndex) {
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Some((Arc::new(index), Instant::now()));
+    }
+
+    /// The index when it is on, non-empty and fresh; None means: use the live query
+    pub fn get(&self) -> Option<Arc<MatchIndex>> {
+        self.get_at(Instant::now())
+    }
+
+    fn get_at(&self, now: Instant) -> Option<Arc<MatchIndex>> {
+        let max_age = self.refresh? * 2;
+        let current = self.current.read().unwrap_or_else(|e| e.into_inner());
+        let (index, built_at) = current.as_ref()?;
+        (now.duration_since(*built_at) < max_age && !index.is_empty()).then(|| index.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutual_candidates_come_first_and_the_caller_is_left_out() {
+        let (me, mutual, teacher, learner, other) = (
+            Uuid::from_u128(1),
+            Uuid::from_u128(2),
+            Uuid::from_u128(3),
+            Uuid::from_u128(4),
+            Uuid::from_u128(5),
+        );
+        let index = MatchIndex::build([
+            (teacher, "Cooking", "Music"),
+            (me, "Coding", "Cooking"),
+            (learner, "Music", "Coding"),
+            (mutual, "Cooking", "Coding"),
+            (other, "Music", "Art"),
+        ]);
+
+        assert_eq!(index.candidates(me, "Coding", "Cooking"), vec![mutual, teacher, learner]);
+        assert!(index.candidates(me, "Dance", "Painting").is_empty());
+    }
+
+    #[test]
+    fn stale_empty_or_disabled_index_is_not_used() {
+        let index = || MatchIndex::build([(Uuid::from_u128(1), "Cooking", "Coding")]);
+
+        let off = MatchIndexCache::new(None);
+        off.set(index());
+        assert!(off.get().is_none());
+
+        let cache = MatchIndexCache::new(Some(Duration::from_secs(60)));
+        cache.set(MatchIndex::default());
+        assert!(cache.get().is_none());
+
+        cache.set(index());
+        let now = Instant::now();
+        assert!(cache.get_at(now).is_some());
+        assert!(cache.get_at(now + Duration::from_secs(119)).is_some());
+        assert!(cache.get_at(now + Duration::from_secs(121)).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+// src/services/matching.rs - skor kecocokan barter antara dua profile
+use crate::dtos::personal::{PersonalDataOut, PublicProfileOut};
+use crate::models::personal::Personal;
+
+pub const SCORE_MUTUAL: u8 = 100;
+/// Kandidat ingin belajar skill yang diajarkan caller (tapi mengajar skill lain)
+pub const SCORE_PARTIAL: u8 = 60;
+/// Kandidat mengajar skill yang ingin dipelajari caller, tapi tidak tertarik skill caller
+pub const SCORE_ONE_WAY: u8 = 30;
+pub const SCORE_NONE: u8 = 0;
+
+/// Anything with a skill it teaches and a skill it wants to learn
+pub trait SkillPair {
+    fn teaches(&self) -> &str;
+    fn learns(&self) -> &str;
+}
+
+impl SkillPair for PersonalDataOut {
+    fn teaches(&self) -> &str {
+        &self.primary_skill
+    }
+
+    fn learns(&self) -> &str {
+        &self.skill_to_learn
+    }
+}
+
+impl SkillPair for Personal {
+    fn teaches(&self) -> &str {
+        &self.primary_skill
+    }
+
+    fn learns(&self) -> &str {
+        &self.skill_to_learn
+    }
+}
+
+impl SkillPair for PublicProfileOut {
+    fn teaches(&self) -> &str {
+        self.primary_skill.as_deref().unwrap_or_default()
+    }
+
+    fn learns(&self) -> &str {
+        self.skill_to_learn.as_deref().unwrap_or_default()
+    }
+}
+
+fn same_skill(a: &str, b: &str) -> bool {
+    !a.trim().is_empty() && a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+/// Match score 0-100 of `candidate` from the caller's point of view:
+/// mutual (100) > candidate wants what the caller teaches (60)
+/// > candidate teaches what the caller wants (30) > nothing in common (0)
+pub fn score_match(caller: &impl SkillPair, candidate: &impl SkillPair) -> u8 {
+    let teaches_me = same_skill(candidate.teaches(), caller.learns());
+    let wants_mine = same_skill(candidate.learns(), caller.teaches());
+
+    match (teaches_me, wants_mine) {
+        (true, true) => SCORE_MUTUAL,
+        (false, true) => SCORE_PARTIAL,
+        (true, false) => SCORE_ONE_WAY,
+        (false, false) => SCORE_NONE,
+    }
+}
+
+/// Badge for a profile page: "mutual", "one_way" (interest in one direction) or "none"
+pub fn match_label(score: u8) -> &'static str {
+    match score {
+        SCORE_MUTUAL => "mutual",
+        SCORE_NONE => "none",
+        _ => "one_way",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Pair(&'static str, &'static str);
+
+    impl SkillPair for Pair {
+        fn teaches(&self) -> &str {
+            self.0
+        }
+
+        fn learns(&self) -> &str {
+            self.1
+        }
+    }
+
+    #[test]
+    fn scores_each_direction_of_interest() {
+        let me = Pair("Guitar", "Cooking");
+        assert_eq!(score_match(&me, &Pair("Cooking", "Guitar")), SCORE_MUTUAL);
+        assert_eq!(score_match(&me, &Pair("Chess", "Guitar")), SCORE_PARTIAL);
+        assert_eq!(score_match(&me, &Pair("Cooking", "Chess")), SCORE_ONE_WAY);
+        assert_eq!(score_match(&me, &Pair("Chess", "Baking")), SCORE_NONE);
+    }
+
+    #[test]
+    fn skills_compare_trimmed_and_case_insensitive_but_never_empty() {
+        let me = Pair("Guitar", "Cooking");
+        assert_eq!(score_match(&me, &Pair(" cooking ", "GUITAR")), SCORE_MUTUAL);
+        assert_eq!(score_match(&Pair("", ""), &Pair("", "")), SCORE_NONE);
+    }
+
+    #[test]
+    fn labels_follow_the_score() {
+        assert_eq!(match_label(SCORE_MUTUAL), "mutual");
+        assert_eq!(match_label(SCORE_PARTIAL), "one_way");
+        assert_eq!(match_label(SCORE_ONE_WAY), "one_way");
+        assert_eq!(match_label(SCORE_NONE), "none");
+    }
+}
@@ -1 +1,8 @@
-pub mod auth_services;
\ No newline at end of file
+pub mod auth_services;
+pub mod images;
+pub mod markdown;
+pub mod user_locks;
+pub mod matching;
+pub mod http_util;
+pub mod content_filter;
+pub mod match_index;
\ No newline at end of file
@@ -0,0 +1,65 @@
+// src/services/user_locks.rs - mutex per user untuk operasi yang tidak boleh balapan
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use uuid::Uuid;
+
+/// Serializes work per user (e.g. two avatar uploads from a double-click),
+/// while requests from different users still run in parallel.
+#[derive(Default)]
+pub struct UserLocks {
+    locks: Mutex<HashMap<Uuid, Arc<AsyncMutex<()>>>>,
+}
+
+impl UserLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait for this user's lock. It's released when the guard is dropped.
+    pub async fn lock(&self, user_id: Uuid) -> OwnedMutexGuard<()> {
+        let user_lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            // Buang lock yang tidak dipegang siapa pun supaya map tidak terus membesar
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(user_id).or_default().clone()
+        };
+
+        user_lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[actix_web::test]
+    async fn same_user_waits_for_the_held_lock() {
+        let locks = UserLocks::new();
+        let user = Uuid::new_v4();
+
+        let guard = locks.lock(user).await;
+        assert!(timeout(Duration::from_millis(50), locks.lock(user)).await.is_err());
+
+        drop(guard);
+        assert!(timeout(Duration::from_millis(50), locks.lock(user)).await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn different_users_do_not_block_each_other() {
+        let locks = UserLocks::new();
+        let _guard = locks.lock(Uuid::new_v4()).await;
+        assert!(timeout(Duration::from_millis(50), locks.lock(Uuid::new_v4())).await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn released_locks_are_pruned() {
+        let locks = UserLocks::new();
+        drop(locks.lock(Uuid::new_v4()).await);
+        drop(locks.lock(Uuid::new_v4()).await);
+        let _guard = locks.lock(Uuid::new_v4()).await;
+        assert_eq!(locks.locks.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,30 @@
+// src/test_support.rs - helper untuk test: Supabase tiruan di port lokal
+use std::sync::Arc;
+use actix_web::web::{self, Bytes};
+use actix_web::{App, HttpRequest, HttpResponse, HttpServer};
+
+type MockHandler = Arc<dyn Fn(&HttpRequest, &Bytes) -> HttpResponse + Send + Sync>;
+
+/// Start an HTTP server on a free local port that answers every request with `handler`
+/// and return its base URL, to pass as `supabase_url`. Call inside `#[actix_web::test]`.
+/// Avoid 5xx answers: they count towards the shared Supabase circuit breaker.
+pub fn mock_supabase(
+    handler: impl Fn(&HttpRequest, &Bytes) -> HttpResponse + Send + Sync + 'static,
+) -> String {
+    let handler: MockHandler = Arc::new(handler);
+    let server = HttpServer::new(move || {
+        let handler = handler.clone();
+        App::new().default_service(web::to(move |req: HttpRequest, body: Bytes| {
+            let handler = handler.clone();
+            async move { handler(&req, &body) }
+        }))
+    })
+    .workers(1)
+    .disable_signals()
+    .bind(("127.0.0.1", 0))
+    .expect("failed to bind mock Supabase");
+
+    let addr = server.addrs()[0];
+    actix_web::rt::spawn(server.run());
+    format!("http://{}", addr)
+}